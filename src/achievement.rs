@@ -0,0 +1,245 @@
+use crate::{
+    ACHIEVEMENTS, ACHIEVEMENTS_PATH, Achievement, HELL_ACHIEVEMENT_LEVEL, MAX_PLAYERS,
+    SKEWER_ACHIEVEMENT_COUNT,
+};
+use dashmap::{DashMap, DashSet};
+use godot::builtin::Color;
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{Button, Control, DirAccess, FileAccess, GridContainer, IControl, Label};
+use godot::obj::{Base, Gd, NewAlloc, OnReady, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+// 成就进度，第一次使用时从档案文件读取
+static PROGRESS: LazyLock<DashMap<Achievement, u32>> = LazyLock::new(|| {
+    let progress = DashMap::new();
+    if let Some(file) = FileAccess::open(ACHIEVEMENTS_PATH, ModeFlags::READ) {
+        let data = file.get_as_text().to_string();
+        if let Ok(saved) = serde_json::from_str::<HashMap<Achievement, u32>>(&data) {
+            for (achievement, value) in saved {
+                progress.insert(achievement, value);
+            }
+        }
+    }
+    progress
+});
+
+// 刚解锁、还没提示过的成就
+static UNLOCKED: LazyLock<DashSet<Achievement>> = LazyLock::new(DashSet::new);
+
+// 每个玩家各自的累计受伤次数，用于判断BOSS战是否无伤
+static HURT_COUNTS: [AtomicU64; MAX_PLAYERS] = [const { AtomicU64::new(0) }; MAX_PLAYERS];
+
+// 当前关卡是否用过刀以外的武器
+static RANGED_USED: AtomicBool = AtomicBool::new(false);
+
+// 当前关卡用刀击杀的僵尸数
+static MELEE_KILLS: AtomicU32 = AtomicU32::new(0);
+
+impl Achievement {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            Achievement::ZombieSlayer => "ZOMBIE SLAYER",
+            Achievement::BoomerBuster => "BOOMER BUSTER",
+            Achievement::PitcherHunter => "PITCHER HUNTER",
+            Achievement::BossHunter => "BOSS HUNTER",
+            Achievement::Untouchable => "UNTOUCHABLE",
+            Achievement::Assassin => "ASSASSIN",
+            Achievement::HellWalker => "HELL WALKER",
+            Achievement::Blademaster => "BLADEMASTER",
+            Achievement::Skewer => "SKEWER",
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            Achievement::ZombieSlayer => String::from("KILL 1000 ZOMBIES"),
+            Achievement::BoomerBuster => String::from("KILL 100 BOOMERS"),
+            Achievement::PitcherHunter => String::from("KILL 100 PITCHERS"),
+            Achievement::BossHunter => String::from("KILL 10 BOSSES"),
+            Achievement::Untouchable => String::from("KILL A BOSS WITHOUT TAKING DAMAGE"),
+            Achievement::Assassin => String::from("ASSASSINATE 50 ZOMBIES"),
+            Achievement::HellWalker => format!("REACH HELL LEVEL {}", HELL_ACHIEVEMENT_LEVEL),
+            Achievement::Blademaster => String::from("CLEAR A LEVEL WITH ONLY THE KNIFE"),
+            Achievement::Skewer => format!(
+                "HIT {} ZOMBIES WITH ONE AWP ROUND",
+                SKEWER_ACHIEVEMENT_COUNT
+            ),
+        }
+    }
+
+    pub fn get_goal(&self) -> u32 {
+        match self {
+            Achievement::ZombieSlayer => 1000,
+            Achievement::BoomerBuster | Achievement::PitcherHunter => 100,
+            Achievement::BossHunter => 10,
+            Achievement::Assassin => 50,
+            Achievement::HellWalker => HELL_ACHIEVEMENT_LEVEL,
+            Achievement::Untouchable | Achievement::Blademaster | Achievement::Skewer => 1,
+        }
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct RustAchievements {
+    list: OnReady<Gd<GridContainer>>,
+    summary: OnReady<Gd<Label>>,
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for RustAchievements {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            list: OnReady::from_node("VBoxContainer/ScrollContainer/List"),
+            summary: OnReady::from_node("VBoxContainer/Summary"),
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let gd = self.to_gd();
+        self.base()
+            .get_node_as::<Button>("VBoxContainer/Back")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_back_pressed);
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl RustAchievements {
+    #[func]
+    pub fn on_back_pressed(&mut self) {
+        self.base_mut().set_visible(false);
+        self.base()
+            .get_parent()
+            .expect("RustAchievements parent not found")
+            .get_node_as::<Control>("VBoxContainer")
+            .set_visible(true);
+    }
+
+    /// 按当前进度重建成就列表
+    pub fn refresh(&mut self) {
+        for mut child in self.list.get_children().iter_shared() {
+            child.queue_free();
+        }
+        let mut unlocked = 0;
+        for achievement in ACHIEVEMENTS {
+            let progress = Self::get_progress(achievement);
+            let goal = achievement.get_goal();
+            let done = progress >= goal;
+            if done {
+                unlocked += 1;
+            }
+            let color = if done { Color::WHITE } else { Color::GRAY };
+            let mut title = Label::new_alloc();
+            title.set_text(achievement.get_title());
+            title.add_theme_color_override("font_color", color);
+            self.list.add_child(&title);
+            let mut description = Label::new_alloc();
+            description.set_text(&achievement.get_description());
+            description.add_theme_color_override("font_color", color);
+            self.list.add_child(&description);
+            let mut state = Label::new_alloc();
+            state.set_text(&if done {
+                String::from("UNLOCKED")
+            } else {
+                format!("{}/{}", progress, goal)
+            });
+            state.add_theme_color_override("font_color", color);
+            self.list.add_child(&state);
+        }
+        self.summary
+            .set_text(&format!("UNLOCKED {}/{}", unlocked, ACHIEVEMENTS.len()));
+    }
+
+    pub fn get_progress(achievement: Achievement) -> u32 {
+        PROGRESS.get(&achievement).map(|v| *v).unwrap_or(0)
+    }
+
+    pub fn is_unlocked(achievement: Achievement) -> bool {
+        Self::get_progress(achievement) >= achievement.get_goal()
+    }
+
+    /// 累加进度，例如击杀数
+    pub fn add_progress(achievement: Achievement, amount: u32) {
+        let progress = Self::get_progress(achievement).saturating_add(amount);
+        Self::set_progress(achievement, progress);
+    }
+
+    /// 记录最好成绩，例如到达的关卡
+    pub fn reach_progress(achievement: Achievement, value: u32) {
+        if value > Self::get_progress(achievement) {
+            Self::set_progress(achievement, value);
+        }
+    }
+
+    pub fn unlock(achievement: Achievement) {
+        Self::reach_progress(achievement, achievement.get_goal());
+    }
+
+    fn set_progress(achievement: Achievement, progress: u32) {
+        let goal = achievement.get_goal();
+        let was_unlocked = Self::is_unlocked(achievement);
+        PROGRESS.insert(achievement, progress.min(goal));
+        if !was_unlocked && progress >= goal {
+            UNLOCKED.insert(achievement);
+            Self::save_profile();
+        }
+    }
+
+    /// 取出刚解锁的成就，由玩家弹出提示
+    pub fn take_unlocked() -> Vec<Achievement> {
+        let achievements: Vec<Achievement> = UNLOCKED.iter().map(|a| *a).collect();
+        for achievement in &achievements {
+            UNLOCKED.remove(achievement);
+        }
+        achievements
+    }
+
+    pub fn save_profile() {
+        let progress: HashMap<Achievement, u32> = PROGRESS
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect();
+        DirAccess::make_dir_recursive_absolute("user://data");
+        if let Some(mut file) = FileAccess::open(ACHIEVEMENTS_PATH, ModeFlags::WRITE) {
+            file.store_string(&serde_json::to_string_pretty(&progress).unwrap());
+        }
+    }
+
+    pub fn on_player_hurt(player_index: usize) {
+        HURT_COUNTS[player_index].fetch_add(1, Ordering::Release);
+    }
+
+    pub fn get_hurt_count(player_index: usize) -> u64 {
+        HURT_COUNTS[player_index].load(Ordering::Acquire)
+    }
+
+    /// 开枪或扔雷
+    pub fn on_ranged_used() {
+        RANGED_USED.store(true, Ordering::Release);
+    }
+
+    pub fn on_melee_kill() {
+        MELEE_KILLS.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// 关卡结束时结算只用刀的成就，为下一关重新计数，并保存进度以免中途退出丢失
+    pub fn on_level_cleared(cleared: bool) {
+        if cleared
+            && !RANGED_USED.load(Ordering::Acquire)
+            && MELEE_KILLS.load(Ordering::Acquire) > 0
+        {
+            Self::unlock(Achievement::Blademaster);
+        }
+        RANGED_USED.store(false, Ordering::Release);
+        MELEE_KILLS.store(0, Ordering::Release);
+        Self::save_profile();
+    }
+}
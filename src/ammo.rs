@@ -0,0 +1,166 @@
+use crate::{
+    AMMO_MAX_RESERVE, AMMO_START_RESERVE, AMMO_TYPES, AmmoType, EXPLOSIVE_AMMO_DAMAGE_RATE,
+    HOLLOW_POINT_DAMAGE_RATE, MAX_PLAYERS, RICOCHET_DAMAGE_RATE, RICOCHET_MAX_BOUNCES,
+};
+use godot::builtin::{Color, real};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+// 每个玩家各种弹药的备弹，普通弹药不限量
+static RESERVES: [[AtomicU32; AMMO_TYPES.len()]; MAX_PLAYERS] =
+    [const { [const { AtomicU32::new(0) }; AMMO_TYPES.len()] }; MAX_PLAYERS];
+
+/// 一发弹药，散弹枪的弹丸共用，特殊弹药的效果只在第一颗命中的弹丸上触发
+#[derive(Debug, Default, Clone)]
+pub struct SpecialRound(Arc<AtomicBool>);
+
+impl SpecialRound {
+    /// 只有第一次调用返回true
+    pub fn trigger(&self) -> bool {
+        !self.0.swap(true, Ordering::AcqRel)
+    }
+}
+
+impl AmmoType {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            AmmoType::Standard => "STANDARD",
+            AmmoType::Incendiary => "INCENDIARY",
+            AmmoType::ArmorPiercing => "ARMOR-PIERCING",
+            AmmoType::HollowPoint => "HOLLOW-POINT",
+            AmmoType::Explosive => "EXPLOSIVE",
+            AmmoType::Ricochet => "RICOCHET",
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            AmmoType::Standard => String::from("UNLIMITED"),
+            AmmoType::Incendiary => String::from("SETS ZOMBIES ON FIRE"),
+            AmmoType::ArmorPiercing => String::from("PIERCES BOSSES FOR FREE"),
+            AmmoType::HollowPoint => format!(
+                "DAMAGE +{:.0}%, NO PENETRATION",
+                (HOLLOW_POINT_DAMAGE_RATE - 1.0) * 100.0
+            ),
+            AmmoType::Explosive => format!(
+                "{:.0}% SPLASH DAMAGE, NO PENETRATION",
+                EXPLOSIVE_AMMO_DAMAGE_RATE * 100.0
+            ),
+            AmmoType::Ricochet => format!(
+                "BOUNCES {} TIMES AT {:.0}% DAMAGE",
+                RICOCHET_MAX_BOUNCES,
+                RICOCHET_DAMAGE_RATE * 100.0
+            ),
+        }
+    }
+
+    /// HUD上的弹药颜色
+    pub fn get_color(&self) -> Color {
+        match self {
+            AmmoType::Standard => Color::WHITE,
+            AmmoType::Incendiary => Color::from_rgb(1.0, 0.5, 0.15),
+            AmmoType::ArmorPiercing => Color::from_rgb(0.6, 0.8, 1.0),
+            AmmoType::HollowPoint => Color::from_rgb(1.0, 0.35, 0.35),
+            AmmoType::Explosive => Color::from_rgb(1.0, 0.85, 0.2),
+            AmmoType::Ricochet => Color::from_rgb(0.6, 1.0, 0.6),
+        }
+    }
+
+    /// 按弹药类型调整子弹的最终属性，燃烧和爆炸在命中时触发
+    pub fn adjust(&self, damage: &mut i64, penetrate: &mut real) {
+        match self {
+            AmmoType::HollowPoint => {
+                *damage = (*damage as real * HOLLOW_POINT_DAMAGE_RATE) as i64;
+                *penetrate = 0.0;
+            }
+            AmmoType::Explosive => *penetrate = 0.0,
+            _ => {}
+        }
+    }
+
+    /// 穿甲弹穿透BOSS不消耗穿透，BOSS身体大，其他子弹消耗更多
+    pub fn get_boss_cost(&self) -> i16 {
+        if AmmoType::ArmorPiercing == *self {
+            0
+        } else {
+            2
+        }
+    }
+
+    pub fn is_special(&self) -> bool {
+        AmmoType::Standard != *self
+    }
+
+    pub fn get_reserve(&self, player_index: usize) -> u32 {
+        if !self.is_special() {
+            return u32::MAX;
+        }
+        RESERVES[player_index][*self as usize].load(Ordering::Acquire)
+    }
+
+    /// 射出一发，备弹不足时返回false
+    pub fn take(&self, player_index: usize) -> bool {
+        if !self.is_special() {
+            return true;
+        }
+        RESERVES[player_index][*self as usize]
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |reserve| {
+                reserve.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    pub fn set_reserve(&self, player_index: usize, reserve: u32) {
+        if self.is_special() {
+            RESERVES[player_index][*self as usize]
+                .store(reserve.min(AMMO_MAX_RESERVE), Ordering::Release);
+        }
+    }
+
+    /// 给所有玩家补充每种特殊弹药
+    pub fn resupply(amount: u32) {
+        for player_index in 0..MAX_PLAYERS {
+            for ammo_type in AMMO_TYPES {
+                let reserve = ammo_type.get_reserve(player_index);
+                ammo_type.set_reserve(player_index, reserve.saturating_add(amount));
+            }
+        }
+    }
+
+    /// 开局时的备弹
+    pub fn reset_reserves(player_index: usize) {
+        for ammo_type in AMMO_TYPES {
+            ammo_type.set_reserve(player_index, AMMO_START_RESERVE);
+        }
+    }
+
+    pub fn get_reserves(player_index: usize) -> Vec<(AmmoType, u32)> {
+        AMMO_TYPES
+            .into_iter()
+            .filter(|ammo_type| ammo_type.is_special())
+            .map(|ammo_type| (ammo_type, ammo_type.get_reserve(player_index)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpecialRound;
+
+    #[test]
+    fn trigger_once_across_clones() {
+        let round = SpecialRound::default();
+        let pellet = round.clone();
+        assert!(pellet.trigger());
+        assert!(!round.trigger());
+        assert!(!pellet.trigger());
+    }
+
+    #[test]
+    fn separate_rounds_trigger_separately() {
+        let first = SpecialRound::default();
+        let second = SpecialRound::default();
+        assert!(first.trigger());
+        assert!(second.trigger());
+    }
+}
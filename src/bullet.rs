@@ -1,13 +1,25 @@
+use crate::achievement::RustAchievements;
+use crate::ammo::SpecialRound;
+use crate::combat::{CombatEventBus, KillCredit};
+use crate::grenade::RustGrenade;
+use crate::mastery::WeaponMastery;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
+use crate::weapon::definition::FalloffModel;
 use crate::zombie::boss::RustBoss;
-use crate::{is_boss, not_boss};
-use godot::builtin::{Vector2, real};
+use crate::{
+    Achievement, AmmoType, EXPLOSIVE_AMMO_DAMAGE_RATE, HitZone, RICOCHET_DAMAGE_RATE,
+    RICOCHET_DISTANCE, RICOCHET_MAX_BOUNCES, SKEWER_ACHIEVEMENT_COUNT, StatusEffect, is_alive,
+    is_boss, is_survivor, is_zombie,
+};
+use godot::builtin::{StringName, Vector2, real};
 use godot::classes::node::PhysicsInterpolationMode;
 use godot::classes::{
-    Area2D, AudioStreamPlayer2D, CollisionShape2D, IArea2D, INode2D, Node2D, Object, RayCast2D,
+    Area2D, AudioStreamPlayer2D, CollisionShape2D, IArea2D, INode2D, Node, Node2D, Object,
+    SceneTree,
 };
 use godot::meta::ToGodot;
-use godot::obj::{Base, Gd, OnReady, WithBaseField, WithUserSignals};
+use godot::obj::{Base, Gd, InstanceId, OnReady, WithBaseField, WithUserSignals};
 use godot::register::{GodotClass, godot_api};
 
 #[derive(GodotClass)]
@@ -20,8 +32,22 @@ pub struct RustBullet {
     final_repel: real,
     final_damage: i64,
     final_penetrate: real,
+    final_effect: StatusEffect,
+    ammo_type: AmmoType,
+    round: SpecialRound,
+    falloff: FalloffModel,
+    // 这一发是否暴击
+    crit: bool,
+    // 跳弹已经弹跳的次数
+    bounces: u32,
     hit_count: i16,
+    // 穿透的僵尸数
+    zombie_count: u32,
     direction: Vector2,
+    // 发射子弹的玩家
+    player_index: usize,
+    // 发射子弹的武器
+    weapon_name: StringName,
     base: Base<Node2D>,
 }
 
@@ -35,13 +61,25 @@ impl INode2D for RustBullet {
             final_repel: 0.0,
             final_damage: 0,
             final_penetrate: 0.0,
+            final_effect: StatusEffect::None,
+            ammo_type: AmmoType::Standard,
+            round: SpecialRound::default(),
+            falloff: FalloffModel::default(),
+            crit: false,
+            bounces: 0,
             hit_count: 0,
+            zombie_count: 0,
             direction: Vector2::ZERO,
+            player_index: 0,
+            weapon_name: StringName::default(),
             base,
         }
     }
 
     fn physics_process(&mut self, delta: f64) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         let direction = self.direction;
         let speed = self.speed;
         let bullet_point = self.bullet_point;
@@ -65,8 +103,10 @@ impl INode2D for RustBullet {
     fn ready(&mut self) {
         self.base_mut()
             .set_physics_interpolation_mode(PhysicsInterpolationMode::ON);
-        let mouse_position = self.get_mouse_position();
-        self.base_mut().look_at(mouse_position);
+        let aim_position = RustPlayer::get_aim_position(self.player_index);
+        self.base_mut().look_at(aim_position);
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &[".:position", ".:rotation"]);
     }
 }
 
@@ -92,17 +132,122 @@ impl RustBullet {
         self.final_penetrate = penetrate;
     }
 
+    pub fn set_final_effect(&mut self, effect: StatusEffect) {
+        self.final_effect = effect;
+    }
+
+    pub fn set_falloff(&mut self, falloff: FalloffModel) {
+        self.falloff = falloff;
+    }
+
+    pub fn set_crit(&mut self, crit: bool) {
+        self.crit = crit;
+    }
+
+    /// 按弹药类型调整最终属性，需要在其他属性之后设置
+    pub fn set_ammo_type(&mut self, ammo_type: AmmoType, round: SpecialRound) {
+        self.ammo_type = ammo_type;
+        self.round = round;
+        ammo_type.adjust(&mut self.final_damage, &mut self.final_penetrate);
+    }
+
     pub fn set_direction(&mut self, direction: Vector2) {
         self.direction = direction;
     }
 
-    pub fn get_mouse_position(&self) -> Vector2 {
-        self.base().get_canvas_transform().affine_inverse()
-            * self
-                .base()
-                .get_viewport()
-                .expect("Viewport not found")
-                .get_mouse_position()
+    pub fn set_player_index(&mut self, player_index: usize) {
+        self.player_index = player_index;
+    }
+
+    pub fn set_weapon_name(&mut self, weapon_name: StringName) {
+        self.weapon_name = weapon_name;
+    }
+
+    fn on_zombie_hit(&mut self) {
+        self.zombie_count += 1;
+        Self::check_skewer(self.zombie_count, &self.weapon_name.to_string());
+    }
+
+    /// 一发子弹穿透足够多的僵尸时解锁成就
+    pub fn check_skewer(zombie_count: u32, weapon_name: &str) {
+        if SKEWER_ACHIEVEMENT_COUNT == zombie_count && "AWP" == weapon_name {
+            RustAchievements::unlock(Achievement::Skewer);
+        }
+    }
+
+    /// 跳弹的下一个目标，命中点附近最近的另一只僵尸
+    pub fn find_ricochet_target(
+        tree: &Gd<SceneTree>,
+        position: Vector2,
+        exclude: InstanceId,
+    ) -> Option<Vector2> {
+        let mut nearest: Option<(real, Vector2)> = None;
+        for node in tree.get_nodes_in_group("zombie").iter_shared() {
+            if !node.is_instance_valid() || !is_alive(&node) || node.instance_id() == exclude {
+                continue;
+            }
+            let Ok(zombie) = node.try_cast::<Node2D>() else {
+                continue;
+            };
+            let zombie_position = zombie.get_global_position();
+            let distance = position.distance_to(zombie_position);
+            if distance > RICOCHET_DISTANCE {
+                continue;
+            }
+            if nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance) {
+                nearest = Some((distance, zombie_position));
+            }
+        }
+        nearest.map(|(_, target)| target)
+    }
+
+    // 命中时的属性，跳弹会改变后续的伤害和方向，需要在跳弹之前取
+    fn get_hit(&self) -> BulletHit {
+        BulletHit {
+            damage: self.final_damage,
+            direction: self.direction,
+            repel: self.final_repel,
+            origin: self.bullet_point,
+            position: self.base().get_global_position(),
+            falloff: self.falloff,
+            crit: self.crit,
+            effect: self.final_effect,
+            ammo_type: self.ammo_type,
+            round: self.round.clone(),
+            player_index: self.player_index,
+            weapon_name: self.weapon_name.to_string(),
+        }
+    }
+
+    // 跳弹弹向附近另一只僵尸，找不到目标时按普通子弹处理
+    fn ricochet(&mut self, body: &Gd<Node2D>) -> bool {
+        if AmmoType::Ricochet != self.ammo_type || self.bounces >= RICOCHET_MAX_BOUNCES {
+            return false;
+        }
+        let Some(tree) = self.base().get_tree() else {
+            return false;
+        };
+        let position = self.base().get_global_position();
+        let Some(target) = Self::find_ricochet_target(&tree, position, body.instance_id()) else {
+            return false;
+        };
+        self.bounces += 1;
+        self.final_damage = (self.final_damage as real * RICOCHET_DAMAGE_RATE) as i64;
+        self.direction = position.direction_to(target).normalized();
+        self.bullet_point = position;
+        self.final_distance = RICOCHET_DISTANCE;
+        self.base_mut().look_at(target);
+        true
+    }
+
+    // 打中僵尸以外的物体，爆炸弹药就地引爆
+    fn on_obstacle_hit(&mut self) {
+        if AmmoType::Explosive != self.ammo_type {
+            return;
+        }
+        self.get_hit().hit_obstacle();
+        self.base_mut().set_physics_process(false);
+        self.base_mut().queue_free();
     }
 
     fn on_hit(&mut self, cost: i16) {
@@ -115,12 +260,128 @@ impl RustBullet {
     }
 }
 
+/// 子弹命中时的属性，投射物和即时命中共用同一套结算
+#[derive(Debug, Clone)]
+pub struct BulletHit {
+    pub damage: i64,
+    pub direction: Vector2,
+    pub repel: real,
+    // 射出或最后一次弹跳的位置，用来计算伤害衰减
+    pub origin: Vector2,
+    pub position: Vector2,
+    pub falloff: FalloffModel,
+    pub crit: bool,
+    pub effect: StatusEffect,
+    pub ammo_type: AmmoType,
+    pub round: SpecialRound,
+    pub player_index: usize,
+    pub weapon_name: String,
+}
+
+impl BulletHit {
+    /// 结算命中部位、熟练度、伤害、状态、溅射和分数
+    pub fn apply(&self, mut body: Gd<Node2D>) {
+        if !body.is_instance_valid() {
+            return;
+        }
+        let zone = HitZone::locate(&body, self.position, self.direction);
+        let (damage_rate, repel_rate) = zone.get_rates(&body);
+        let falloff_rate = self
+            .falloff
+            .get_rate(self.origin.distance_to(self.position));
+        let damage = (self.damage as real * damage_rate * falloff_rate) as i64;
+        let repel = self.repel * repel_rate;
+        if HitZone::Head == zone {
+            if let Some(mut player) = RustPlayer::get_by_index(self.player_index) {
+                player.bind_mut().headshot();
+            }
+        }
+        WeaponMastery::record_damage(&self.weapon_name, damage);
+        CombatEventBus::record_hit(
+            body.instance_id(),
+            KillCredit::new(self.player_index, &self.weapon_name),
+        );
+        WeaponMastery::record_zone_hit(&self.weapon_name, zone);
+        let effect = self.trigger_round();
+        if is_boss(&***body) {
+            let mut boss = body.cast::<RustBoss>();
+            boss.bind_mut().on_zone_hit(
+                damage,
+                self.direction,
+                repel,
+                self.position,
+                zone,
+                self.crit,
+            );
+            boss.bind_mut()
+                .apply_player_effect(effect, self.player_index as u32);
+        } else {
+            body.call_deferred(
+                "on_zone_hit",
+                &[
+                    damage.to_variant(),
+                    self.direction.to_variant(),
+                    repel.to_variant(),
+                    self.position.to_variant(),
+                    zone.to_variant(),
+                    self.crit.to_variant(),
+                ],
+            );
+            if StatusEffect::None != effect {
+                body.call_deferred(
+                    "apply_player_effect",
+                    &[effect.to_variant(), (self.player_index as u32).to_variant()],
+                );
+            }
+        }
+        if damage > 0 {
+            if let Some(mut player) = RustPlayer::get_by_index(self.player_index) {
+                player.call_deferred("add_score", &[damage.to_variant()]);
+            }
+        }
+    }
+
+    // 特殊弹药每发只触发一次，返回这次命中附带的状态
+    fn trigger_round(&self) -> StatusEffect {
+        match self.ammo_type {
+            AmmoType::Incendiary if self.round.trigger() => StatusEffect::Burning,
+            AmmoType::Explosive if self.round.trigger() => {
+                self.detonate();
+                self.effect
+            }
+            _ => self.effect,
+        }
+    }
+
+    /// 打中僵尸以外的物体，爆炸弹药同样在命中点爆炸
+    pub fn hit_obstacle(&self) {
+        if AmmoType::Explosive == self.ammo_type && self.round.trigger() {
+            self.detonate();
+        }
+    }
+
+    // 在命中点引爆一颗小型榴弹，溅射伤害按手雷的规则结算
+    fn detonate(&self) {
+        let Some(mut parent) =
+            RustPlayer::get_by_index(self.player_index).and_then(|player| player.get_parent())
+        else {
+            return;
+        };
+        RustGrenade::detonate_round(
+            &mut parent,
+            self.position,
+            (self.damage as real * EXPLOSIVE_AMMO_DAMAGE_RATE) as i64,
+            self.repel,
+            self.player_index,
+            &self.weapon_name,
+        );
+    }
+}
+
 #[derive(GodotClass)]
 #[class(base=Area2D)]
 pub struct BulletDamageArea {
     collision_shape2d: OnReady<Gd<CollisionShape2D>>,
-    headshot_ray1: OnReady<Gd<RayCast2D>>,
-    headshot_ray2: OnReady<Gd<RayCast2D>>,
     hit_audio: OnReady<Gd<AudioStreamPlayer2D>>,
     base: Base<Area2D>,
 }
@@ -130,8 +391,6 @@ impl IArea2D for BulletDamageArea {
     fn init(base: Base<Area2D>) -> Self {
         Self {
             collision_shape2d: OnReady::from_node("CollisionShape2D"),
-            headshot_ray1: OnReady::from_node("HeadshotRay1"),
-            headshot_ray2: OnReady::from_node("HeadshotRay2"),
             hit_audio: OnReady::from_node("HitAudio"),
             base,
         }
@@ -139,8 +398,6 @@ impl IArea2D for BulletDamageArea {
 
     fn exit_tree(&mut self) {
         self.collision_shape2d.queue_free();
-        self.headshot_ray1.queue_free();
-        self.headshot_ray2.queue_free();
         self.hit_audio.set_stream(Gd::null_arg());
         self.hit_audio.queue_free();
     }
@@ -158,51 +415,35 @@ impl BulletDamageArea {
     pub fn sig();
 
     #[func]
-    pub fn on_area_2d_body_entered(&mut self, mut body: Gd<Node2D>) {
+    pub fn on_area_2d_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         let mut rust_bullet = self
             .base()
             .get_parent()
             .expect("RustBullet not found")
             .cast::<RustBullet>();
-        let mut damage = 0;
-        if not_boss(&***body) {
-            damage = rust_bullet.bind().final_damage;
-            self.hit_audio.play();
-            rust_bullet.bind_mut().on_hit(1);
-            if self.headshot_ray1.is_colliding() || self.headshot_ray2.is_colliding() {
-                RustPlayer::get().bind_mut().headshot();
-                damage *= 3;
-            }
-            if !body.is_instance_valid() {
-                return;
+        if !is_zombie(&***body) {
+            if !is_survivor(&***body) {
+                rust_bullet.bind_mut().on_obstacle_hit();
             }
-            body.call_deferred(
-                "on_hit",
-                &[
-                    damage.to_variant(),
-                    rust_bullet.bind().direction.to_variant(),
-                    rust_bullet.bind().final_repel.to_variant(),
-                    rust_bullet.get_global_position().to_variant(),
-                ],
-            );
-        } else if is_boss(&***body) {
-            damage = rust_bullet.bind().final_damage;
-            self.hit_audio.play();
-            // BOSS身体大，消耗更多穿透
-            rust_bullet.bind_mut().on_hit(2);
-            if self.headshot_ray1.is_colliding() || self.headshot_ray2.is_colliding() {
-                RustPlayer::get().bind_mut().headshot();
-                damage *= 3;
-            }
-            body.cast::<RustBoss>().bind_mut().on_hit(
-                damage,
-                rust_bullet.bind().direction,
-                rust_bullet.bind().final_repel,
-                rust_bullet.get_global_position(),
-            );
+            return;
         }
-        if damage > 0 {
-            RustPlayer::get().call_deferred("add_score", &[damage.to_variant()]);
+        let boss = is_boss(&***body);
+        self.hit_audio.play();
+        let hit = rust_bullet.bind().get_hit();
+        if !boss {
+            rust_bullet.bind_mut().on_zombie_hit();
+        }
+        if !rust_bullet.bind_mut().ricochet(&body) {
+            let cost = if boss {
+                hit.ammo_type.get_boss_cost()
+            } else {
+                1
+            };
+            rust_bullet.bind_mut().on_hit(cost);
         }
+        hit.apply(body);
     }
 }
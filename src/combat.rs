@@ -0,0 +1,120 @@
+use crate::achievement::RustAchievements;
+use crate::mastery::WeaponMastery;
+use crate::{Achievement, MAX_PLAYERS};
+use dashmap::DashMap;
+use godot::builtin::Vector2;
+use godot::obj::InstanceId;
+use std::sync::LazyLock;
+
+/// 战斗中发生的事件，由玩家在自己的帧里统一处理，
+/// 避免在僵尸或武器的回调里再去借用玩家
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CombatEvent {
+    // 有僵尸死亡，附带死亡位置
+    Kill(Vector2),
+    // 开始换弹
+    Reload,
+    // 爆头
+    Headshot,
+    // 受到伤害，附带伤害值
+    Damaged(i64),
+}
+
+// 按玩家下标保存未处理的事件，只有订阅了的玩家才会收到
+static EVENTS: LazyLock<DashMap<usize, Vec<CombatEvent>>> = LazyLock::new(DashMap::new);
+
+// 僵尸最后一次受到的玩家伤害，死亡时把击杀记给这次伤害的来源
+static LAST_HITS: LazyLock<DashMap<InstanceId, KillCredit>> = LazyLock::new(DashMap::new);
+
+/// 击杀归属，和分数一样只在玩家造成伤害时记录
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KillCredit {
+    pub player_index: usize,
+    // 枪械和榴弹发射器为武器名，刀和手投的手雷为空
+    pub weapon_name: String,
+    pub melee: bool,
+}
+
+impl KillCredit {
+    pub fn new(player_index: usize, weapon_name: &str) -> Self {
+        Self {
+            player_index,
+            weapon_name: weapon_name.to_string(),
+            melee: false,
+        }
+    }
+
+    /// 刀砍和飞刀
+    pub fn melee(player_index: usize) -> Self {
+        Self {
+            player_index,
+            weapon_name: String::new(),
+            melee: true,
+        }
+    }
+}
+
+pub struct CombatEventBus;
+
+impl CombatEventBus {
+    pub fn subscribe(player_index: usize) {
+        EVENTS.entry(player_index).or_default();
+    }
+
+    pub fn unsubscribe(player_index: usize) {
+        EVENTS.remove(&player_index);
+    }
+
+    /// 发给指定玩家
+    pub fn emit(player_index: usize, event: CombatEvent) {
+        if let Some(mut events) = EVENTS.get_mut(&player_index) {
+            events.push(event);
+        }
+    }
+
+    /// 发给所有玩家
+    pub fn broadcast(event: CombatEvent) {
+        for player_index in 0..MAX_PLAYERS {
+            Self::emit(player_index, event);
+        }
+    }
+
+    /// 后一次伤害覆盖前一次，用刀补刀时不会记到枪上
+    pub fn record_hit(zombie: InstanceId, credit: KillCredit) {
+        LAST_HITS.insert(zombie, credit);
+    }
+
+    /// 僵尸阵营的伤害不记击杀，之后被它炸死不算任何玩家的击杀
+    pub fn clear_hit(zombie: InstanceId) {
+        LAST_HITS.remove(&zombie);
+    }
+
+    /// 僵尸死亡时只通知击杀者，并把击杀记给最后一击的武器和对应的成就，
+    /// 返回击杀者的玩家序号
+    pub fn on_zombie_killed(
+        zombie: InstanceId,
+        position: Vector2,
+        achievement: Achievement,
+    ) -> Option<usize> {
+        let (_, credit) = LAST_HITS.remove(&zombie)?;
+        Self::emit(credit.player_index, CombatEvent::Kill(position));
+        WeaponMastery::on_kill(&credit.weapon_name);
+        RustAchievements::add_progress(achievement, 1);
+        if credit.melee {
+            RustAchievements::on_melee_kill();
+        }
+        Some(credit.player_index)
+    }
+
+    /// 换关时清理已被移除的僵尸
+    pub fn clear_hits() {
+        LAST_HITS.clear();
+    }
+
+    pub fn drain(player_index: usize) -> Vec<CombatEvent> {
+        EVENTS
+            .get_mut(&player_index)
+            .map(|mut events| std::mem::take(&mut *events))
+            .unwrap_or_default()
+    }
+}
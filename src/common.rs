@@ -1,4 +1,5 @@
-use crate::PlayerUpgrade;
+use crate::{CRIT_MESSAGE_SCALE, HitZone, PlayerUpgrade};
+use godot::builtin::{Color, Vector2};
 use godot::classes::{INode2D, Label, Node2D};
 use godot::meta::ToGodot;
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
@@ -50,33 +51,16 @@ impl RustMessage {
         tween.tween_callback(&self.base().callable("clean"));
     }
 
-    pub fn show_hit_value(&mut self, value: i64) {
-        let position = self.base().get_global_position();
-        self.message.set_text(&format!("-{}", value));
+    /// 伤害数字按部位着色，暴击时放大并加上感叹号，动画由伤害数字渲染器驱动
+    pub fn set_hit_value(&mut self, value: i64, zone: Option<HitZone>, crit: bool) {
+        let scale = if crit { CRIT_MESSAGE_SCALE } else { 1.0 };
+        let mut base_mut = self.base_mut();
+        base_mut.set_modulate(zone.map_or(Color::WHITE, |zone| zone.get_color()));
+        base_mut.set_scale(Vector2::new(scale, scale));
+        drop(base_mut);
+        self.message
+            .set_text(&format!("-{}{}", value, if crit { "!" } else { "" }));
         self.message.show();
-        let mut tween = self
-            .base_mut()
-            .create_tween()
-            .expect("Failed to create tween");
-        tween
-            .tween_property(
-                &self.base.to_gd(),
-                "position:y",
-                &(position.y - 50.0).to_variant(),
-                0.5,
-            )
-            .expect("tween failed");
-        tween.tween_property(
-            &self.base.to_gd(),
-            "position:y",
-            &(position.y - 35.0).to_variant(),
-            0.3,
-        );
-        tween
-            .parallel()
-            .expect("tween parallel failed")
-            .tween_property(&self.base.to_gd(), "modulate:a", &0.0_f32.to_variant(), 0.3);
-        tween.tween_callback(&self.base().callable("clean"));
     }
 
     #[func]
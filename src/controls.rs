@@ -0,0 +1,345 @@
+use crate::KEYMAP_PATH;
+use godot::builtin::{GString, Vector2};
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{
+    Button, Control, DirAccess, FileAccess, GridContainer, IControl, InputEvent,
+    InputEventJoypadButton, InputEventJoypadMotion, InputEventKey, InputEventMouseButton, InputMap,
+    Label,
+};
+use godot::global::{JoyAxis, JoyButton, Key, MouseButton};
+use godot::meta::ToGodot;
+use godot::obj::{Base, EngineEnum, Gd, NewAlloc, NewGd, OnReady, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 每个动作可绑定的按键数
+const BINDING_SLOTS: usize = 2;
+
+// 摇杆推到一半以上才算一次输入
+const JOY_AXIS_THRESHOLD: f32 = 0.5;
+
+/// 游戏内可重新绑定的动作，以及在设置界面上显示的名称
+pub const CONTROL_ACTIONS: [(&str, &str); 41] = [
+    ("move_up", "MOVE UP"),
+    ("move_down", "MOVE DOWN"),
+    ("move_left", "MOVE LEFT"),
+    ("move_right", "MOVE RIGHT"),
+    ("aim_up", "AIM UP"),
+    ("aim_down", "AIM DOWN"),
+    ("aim_left", "AIM LEFT"),
+    ("aim_right", "AIM RIGHT"),
+    ("shift", "RUN"),
+    ("mouse_right", "RUN (ALT)"),
+    ("dodge", "DODGE"),
+    ("sneak", "SNEAK"),
+    ("ability", "ABILITY"),
+    ("mouse_left", "FIRE"),
+    ("e", "CHOP (HOLD FOR HEAVY)"),
+    ("parry", "PARRY"),
+    ("throw_knife", "THROW / RECALL KNIFE"),
+    ("next_melee", "SWITCH MELEE"),
+    ("loadout", "LOADOUT"),
+    ("fire_mode", "FIRE MODE"),
+    ("ammo_type", "SWITCH AMMO"),
+    ("r", "RELOAD"),
+    ("q", "GRENADE"),
+    ("mouse_middle", "GRENADE (ALT)"),
+    ("next_grenade", "SWITCH GRENADE"),
+    ("next_weapon", "NEXT WEAPON"),
+    ("mouse_side2", "NEXT WEAPON (ALT)"),
+    ("last_weapon", "LAST WEAPON"),
+    ("mouse_side1", "LAST WEAPON (ALT)"),
+    ("weapon_wheel", "WEAPON WHEEL (HOLD)"),
+    ("1", "WEAPON 1"),
+    ("2", "WEAPON 2"),
+    ("3", "WEAPON 3"),
+    ("4", "WEAPON 4"),
+    ("5", "WEAPON 5"),
+    ("6", "WEAPON 6"),
+    ("7", "WEAPON 7"),
+    ("8", "WEAPON 8"),
+    ("9", "WEAPON 9"),
+    ("esc", "PAUSE"),
+    ("l", "REFRESH ZOMBIES"),
+];
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum Binding {
+    Key(i32),
+    Mouse(i32),
+    JoyButton(i32),
+    JoyAxis(i32, f32),
+}
+
+impl Binding {
+    pub fn of(event: &Gd<InputEvent>) -> Option<Self> {
+        if let Ok(key) = event.clone().try_cast::<InputEventKey>() {
+            // 项目默认配置只有物理键码
+            let physical_keycode = key.get_physical_keycode();
+            if Key::NONE != physical_keycode {
+                return Some(Self::Key(physical_keycode.ord()));
+            }
+            return Some(Self::Key(key.get_keycode().ord()));
+        }
+        if let Ok(mouse) = event.clone().try_cast::<InputEventMouseButton>() {
+            return Some(Self::Mouse(mouse.get_button_index().ord()));
+        }
+        if let Ok(joy_button) = event.clone().try_cast::<InputEventJoypadButton>() {
+            return Some(Self::JoyButton(joy_button.get_button_index().ord()));
+        }
+        if let Ok(joy_motion) = event.clone().try_cast::<InputEventJoypadMotion>() {
+            let value = joy_motion.get_axis_value();
+            if value.abs() >= JOY_AXIS_THRESHOLD {
+                return Some(Self::JoyAxis(joy_motion.get_axis().ord(), value.signum()));
+            }
+        }
+        None
+    }
+
+    pub fn to_event(self) -> Option<Gd<InputEvent>> {
+        // 键鼠响应所有设备，1P手柄固定为0号设备，1号手柄留给2P
+        let device = match self {
+            Self::JoyButton(_) | Self::JoyAxis(..) => 0,
+            _ => -1,
+        };
+        let mut event = match self {
+            Self::Key(keycode) => {
+                let mut event = InputEventKey::new_gd();
+                event.set_physical_keycode(Key::try_from_ord(keycode)?);
+                event.upcast::<InputEvent>()
+            }
+            Self::Mouse(button_index) => {
+                let mut event = InputEventMouseButton::new_gd();
+                event.set_button_index(MouseButton::try_from_ord(button_index)?);
+                event.upcast::<InputEvent>()
+            }
+            Self::JoyButton(button_index) => {
+                let mut event = InputEventJoypadButton::new_gd();
+                event.set_button_index(JoyButton::try_from_ord(button_index)?);
+                event.upcast::<InputEvent>()
+            }
+            Self::JoyAxis(axis, value) => {
+                let mut event = InputEventJoypadMotion::new_gd();
+                event.set_axis(JoyAxis::try_from_ord(axis)?);
+                event.set_axis_value(value);
+                event.upcast::<InputEvent>()
+            }
+        };
+        event.set_device(device);
+        Some(event)
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct RustControls {
+    // 正在等待绑定的动作下标及按键槽位
+    waiting: Option<(usize, usize)>,
+    bindings: OnReady<Gd<GridContainer>>,
+    message: OnReady<Gd<Label>>,
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for RustControls {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            waiting: None,
+            bindings: OnReady::from_node("VBoxContainer/ScrollContainer/Bindings"),
+            message: OnReady::from_node("VBoxContainer/Message"),
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let gd = self.to_gd();
+        self.bindings.set_columns(1 + BINDING_SLOTS as i32);
+        for (index, (action, label)) in CONTROL_ACTIONS.iter().enumerate() {
+            let mut action_label = Label::new_alloc();
+            action_label.set_text(*label);
+            self.bindings.add_child(&action_label);
+            for slot in 0..BINDING_SLOTS {
+                let mut button = Button::new_alloc();
+                button.set_name(&format!("{}_{}", action, slot));
+                button.set_custom_minimum_size(Vector2::new(120.0, 0.0));
+                button.connect(
+                    "pressed",
+                    &self
+                        .base()
+                        .callable("start_capture")
+                        .bind(&[(index as i64).to_variant(), (slot as i64).to_variant()]),
+                );
+                self.bindings.add_child(&button);
+            }
+        }
+        let container = self.base().get_node_as::<Control>("VBoxContainer");
+        container
+            .get_node_as::<Button>("HBoxContainer/Reset")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_reset_pressed);
+        container
+            .get_node_as::<Button>("HBoxContainer/Back")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_back_pressed);
+        self.refresh();
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let Some((index, slot)) = self.waiting else {
+            return;
+        };
+        if event.is_echo() || !event.is_pressed() && !event.is_class("InputEventJoypadMotion") {
+            return;
+        }
+        let Some(binding) = Binding::of(&event) else {
+            return;
+        };
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+        if Binding::Key(Key::ESCAPE.ord()) == binding {
+            // ESC取消绑定
+            self.waiting = None;
+            self.show_message("");
+            self.refresh();
+            return;
+        }
+        let (action, label) = CONTROL_ACTIONS[index];
+        if let Some(conflict) = Self::find_conflict(action, binding) {
+            self.show_message(&format!("ALREADY USED BY {}, TRY ANOTHER", conflict));
+            return;
+        }
+        let mut bindings = Self::get_bindings(action);
+        if slot < bindings.len() {
+            bindings[slot] = binding;
+        } else {
+            bindings.push(binding);
+        }
+        Self::apply_bindings(action, &bindings);
+        Self::save_keymap();
+        self.waiting = None;
+        self.show_message(&format!("{} REBOUND", label));
+        self.refresh();
+    }
+}
+
+#[godot_api]
+impl RustControls {
+    #[func]
+    pub fn start_capture(&mut self, index: i64, slot: i64) {
+        let index = index as usize;
+        let slot = slot as usize;
+        self.waiting = Some((index, slot));
+        self.refresh();
+        let (action, label) = CONTROL_ACTIONS[index];
+        self.bindings
+            .get_node_as::<Button>(&format!("{}_{}", action, slot))
+            .set_text("...");
+        self.show_message(&format!(
+            "PRESS A KEY, MOUSE OR GAMEPAD BUTTON FOR {}, ESC TO CANCEL",
+            label
+        ));
+    }
+
+    #[func]
+    pub fn on_reset_pressed(&mut self) {
+        self.waiting = None;
+        InputMap::singleton().load_from_project_settings();
+        DirAccess::remove_absolute(KEYMAP_PATH);
+        self.show_message("CONTROLS RESET");
+        self.refresh();
+    }
+
+    #[func]
+    pub fn on_back_pressed(&mut self) {
+        self.waiting = None;
+        self.show_message("");
+        self.base_mut().set_visible(false);
+        self.base()
+            .get_parent()
+            .expect("RustControls parent not found")
+            .get_node_as::<Control>("VBoxContainer")
+            .set_visible(true);
+    }
+
+    pub fn refresh(&mut self) {
+        for (action, _) in CONTROL_ACTIONS {
+            let events = InputMap::singleton().action_get_events(action);
+            for slot in 0..BINDING_SLOTS {
+                let text = events
+                    .get(slot)
+                    .map(|event| event.as_text())
+                    .unwrap_or_else(|| GString::from("-"));
+                self.bindings
+                    .get_node_as::<Button>(&format!("{}_{}", action, slot))
+                    .set_text(&text);
+            }
+        }
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.message.set_text(message);
+        self.message.show();
+    }
+
+    fn find_conflict(action: &str, binding: Binding) -> Option<&'static str> {
+        for (other_action, other_label) in CONTROL_ACTIONS {
+            if other_action == action {
+                continue;
+            }
+            if Self::get_bindings(other_action).contains(&binding) {
+                return Some(other_label);
+            }
+        }
+        None
+    }
+
+    pub fn get_bindings(action: &str) -> Vec<Binding> {
+        InputMap::singleton()
+            .action_get_events(action)
+            .iter_shared()
+            .filter_map(|event| Binding::of(&event))
+            .collect()
+    }
+
+    pub fn apply_bindings(action: &str, bindings: &[Binding]) {
+        let mut input_map = InputMap::singleton();
+        if !input_map.has_action(action) {
+            input_map.add_action(action);
+        }
+        input_map.action_erase_events(action);
+        for binding in bindings {
+            if let Some(event) = binding.to_event() {
+                input_map.action_add_event(action, &event);
+            }
+        }
+    }
+
+    pub fn save_keymap() {
+        let mut keymap = HashMap::new();
+        for (action, _) in CONTROL_ACTIONS {
+            keymap.insert(action.to_string(), Self::get_bindings(action));
+        }
+        DirAccess::make_dir_recursive_absolute("user://data");
+        if let Some(mut file) = FileAccess::open(KEYMAP_PATH, ModeFlags::WRITE) {
+            file.store_string(&serde_json::to_string_pretty(&keymap).unwrap());
+        }
+    }
+
+    /// 启动时用玩家保存的按键重建InputMap
+    pub fn load_keymap() {
+        if let Some(file) = FileAccess::open(KEYMAP_PATH, ModeFlags::READ) {
+            let data = file.get_as_text().to_string();
+            if let Ok(keymap) = serde_json::from_str::<HashMap<String, Vec<Binding>>>(&data) {
+                for (action, _) in CONTROL_ACTIONS {
+                    if let Some(bindings) = keymap.get(action) {
+                        Self::apply_bindings(action, bindings);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::level::RustLevel;
+use crate::settings::GameSettings;
+use crate::{FriendlyFire, REDUCED_DAMAGE_RATE, Team};
+use godot::builtin::real;
+use serde::{Deserialize, Serialize};
+
+/// 伤害来源，爆炸据此判断能不能伤到玩家和僵尸
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct DamageSource {
+    pub team: Team,
+    // 幸存者阵营时为发射者的玩家序号
+    pub player_index: usize,
+}
+
+impl DamageSource {
+    pub fn survivor(player_index: usize) -> Self {
+        Self {
+            team: Team::Survivor,
+            player_index,
+        }
+    }
+
+    pub fn zombie() -> Self {
+        Self {
+            team: Team::Zombie,
+            player_index: 0,
+        }
+    }
+
+    /// 当前模式下对玩家的伤害倍率
+    pub fn get_player_rate(&self, player_index: usize) -> real {
+        DamageRules::current().get_player_rate(self, player_index)
+    }
+
+    /// 当前模式下对僵尸的伤害倍率
+    pub fn get_zombie_rate(&self) -> real {
+        DamageRules::current().get_zombie_rate(self)
+    }
+}
+
+impl FriendlyFire {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            FriendlyFire::Off => "OFF",
+            FriendlyFire::Reduced => "HALF",
+            FriendlyFire::Full => "FULL",
+        }
+    }
+
+    pub fn get_rate(&self) -> real {
+        match self {
+            FriendlyFire::Off => 0.0,
+            FriendlyFire::Reduced => REDUCED_DAMAGE_RATE,
+            FriendlyFire::Full => 1.0,
+        }
+    }
+}
+
+/// 爆炸伤害规则，普通模式和地狱模式各存一份
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct DamageRules {
+    // 自己的榴弹和手雷对自己
+    pub self_damage: FriendlyFire,
+    // 队友的榴弹和手雷对自己
+    pub team_damage: FriendlyFire,
+    // 自爆者和投掷者的爆炸伤害僵尸
+    pub zombie_damage: bool,
+}
+
+impl Default for DamageRules {
+    fn default() -> Self {
+        Self {
+            self_damage: FriendlyFire::Full,
+            team_damage: FriendlyFire::Off,
+            zombie_damage: true,
+        }
+    }
+}
+
+impl DamageRules {
+    /// 地狱模式默认全额自伤和友伤，僵尸之间不再误伤
+    pub fn hell() -> Self {
+        Self {
+            self_damage: FriendlyFire::Full,
+            team_damage: FriendlyFire::Full,
+            zombie_damage: false,
+        }
+    }
+
+    /// 对玩家的伤害倍率，自己的爆炸算自伤，队友的爆炸算友伤
+    pub fn get_player_rate(&self, source: &DamageSource, player_index: usize) -> real {
+        match source.team {
+            Team::Zombie => 1.0,
+            Team::Survivor if source.player_index == player_index => self.self_damage.get_rate(),
+            Team::Survivor => self.team_damage.get_rate(),
+        }
+    }
+
+    /// 对僵尸的伤害倍率，自爆者和投掷者的爆炸能否误伤僵尸由设置决定
+    pub fn get_zombie_rate(&self, source: &DamageSource) -> real {
+        match source.team {
+            Team::Survivor => 1.0,
+            Team::Zombie if self.zombie_damage => 1.0,
+            Team::Zombie => 0.0,
+        }
+    }
+
+    /// 当前模式生效的规则
+    pub fn current() -> Self {
+        let settings = GameSettings::get();
+        if RustLevel::is_hell() {
+            settings.hell_damage_rules
+        } else {
+            settings.damage_rules
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DamageRules, DamageSource};
+    use crate::{FriendlyFire, REDUCED_DAMAGE_RATE};
+
+    #[test]
+    fn normal_rules() {
+        let rules = DamageRules::default();
+        let own = DamageSource::survivor(0);
+        assert_eq!(1.0, rules.get_player_rate(&own, 0));
+        assert_eq!(0.0, rules.get_player_rate(&own, 1));
+        assert_eq!(1.0, rules.get_zombie_rate(&own));
+        let zombie = DamageSource::zombie();
+        assert_eq!(1.0, rules.get_player_rate(&zombie, 0));
+        assert_eq!(1.0, rules.get_zombie_rate(&zombie));
+    }
+
+    #[test]
+    fn hell_rules() {
+        let rules = DamageRules::hell();
+        let own = DamageSource::survivor(1);
+        assert_eq!(1.0, rules.get_player_rate(&own, 1));
+        assert_eq!(1.0, rules.get_player_rate(&own, 0));
+        assert_eq!(1.0, rules.get_zombie_rate(&own));
+        let zombie = DamageSource::zombie();
+        assert_eq!(1.0, rules.get_player_rate(&zombie, 1));
+        assert_eq!(0.0, rules.get_zombie_rate(&zombie));
+    }
+
+    #[test]
+    fn reduced_rules() {
+        let rules = DamageRules {
+            self_damage: FriendlyFire::Reduced,
+            team_damage: FriendlyFire::Reduced,
+            zombie_damage: true,
+        };
+        let own = DamageSource::survivor(0);
+        assert_eq!(REDUCED_DAMAGE_RATE, rules.get_player_rate(&own, 0));
+        assert_eq!(REDUCED_DAMAGE_RATE, rules.get_player_rate(&own, 1));
+    }
+}
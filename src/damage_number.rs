@@ -0,0 +1,188 @@
+use crate::common::RustMessage;
+use crate::settings::GameSettings;
+use crate::{
+    DAMAGE_NUMBER_FADE_TIME, DAMAGE_NUMBER_FALL, DAMAGE_NUMBER_MAX, DAMAGE_NUMBER_MERGE_TIME,
+    DAMAGE_NUMBER_RISE, DAMAGE_NUMBER_RISE_TIME, HIT_ZONE_DAMAGE_RATES, HitZone, MESSAGE,
+};
+use dashmap::DashMap;
+use godot::builtin::{Vector2, real};
+use godot::classes::{INode2D, Node2D};
+use godot::obj::{Base, Gd, InstanceId, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use std::sync::LazyLock;
+
+// 还没显示的命中，同一帧里打中同一个目标的先合并
+static PENDING: LazyLock<DashMap<InstanceId, DamageHit>> = LazyLock::new(DashMap::new);
+
+#[derive(Debug, Copy, Clone)]
+struct DamageHit {
+    position: Vector2,
+    value: i64,
+    zone: Option<HitZone>,
+    crit: bool,
+}
+
+impl DamageHit {
+    // 数字跟着目标走，合并后显示伤害倍率最高的部位
+    fn merge(&mut self, other: &DamageHit) {
+        self.position = other.position;
+        self.value += other.value;
+        self.crit |= other.crit;
+        if Self::get_priority(other.zone) > Self::get_priority(self.zone) {
+            self.zone = other.zone;
+        }
+    }
+
+    fn get_priority(zone: Option<HitZone>) -> real {
+        zone.map_or(0.0, |zone| HIT_ZONE_DAMAGE_RATES[zone as usize])
+    }
+}
+
+struct DamageNumber {
+    target: InstanceId,
+    label: Gd<RustMessage>,
+    hit: DamageHit,
+    age: real,
+}
+
+impl DamageNumber {
+    fn refresh(&mut self) {
+        self.label
+            .bind_mut()
+            .set_hit_value(self.hit.value, self.hit.zone, self.hit.crit);
+        self.update();
+    }
+
+    // 先上升再回落并淡出，和原来两段补间动画的效果一致
+    fn update(&mut self) {
+        let rise = (self.age / DAMAGE_NUMBER_RISE_TIME).min(1.0);
+        let fade = ((self.age - DAMAGE_NUMBER_RISE_TIME) / DAMAGE_NUMBER_FADE_TIME).clamp(0.0, 1.0);
+        let offset = DAMAGE_NUMBER_FALL * fade - DAMAGE_NUMBER_RISE * rise;
+        self.label
+            .set_global_position(self.hit.position + Vector2::new(0.0, offset));
+        let mut modulate = self.label.get_modulate();
+        modulate.a = 1.0 - fade;
+        self.label.set_modulate(modulate);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.age >= DAMAGE_NUMBER_RISE_TIME + DAMAGE_NUMBER_FADE_TIME
+    }
+}
+
+/// 伤害数字的渲染器，复用消息节点，短时间内打中同一个目标的伤害合并成一个数字
+#[derive(GodotClass)]
+#[class(base=Node2D)]
+pub struct RustDamageNumbers {
+    // 按出现的先后排列
+    numbers: Vec<DamageNumber>,
+    pool: Vec<Gd<RustMessage>>,
+    base: Base<Node2D>,
+}
+
+#[godot_api]
+impl INode2D for RustDamageNumbers {
+    fn init(base: Base<Node2D>) -> Self {
+        Self {
+            numbers: Vec::new(),
+            pool: Vec::new(),
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        PENDING.clear();
+    }
+
+    fn process(&mut self, delta: f64) {
+        let targets: Vec<InstanceId> = PENDING.iter().map(|hit| *hit.key()).collect();
+        for target in targets {
+            if let Some((_, hit)) = PENDING.remove(&target) {
+                self.show(target, hit);
+            }
+        }
+        let delta = delta as real;
+        for number in self.numbers.iter_mut() {
+            number.age += delta;
+            number.update();
+        }
+        //播完的数字放回池里
+        let (finished, numbers): (Vec<_>, Vec<_>) = std::mem::take(&mut self.numbers)
+            .into_iter()
+            .partition(DamageNumber::is_finished);
+        self.numbers = numbers;
+        for number in finished {
+            self.release(number.label);
+        }
+    }
+}
+
+#[godot_api]
+impl RustDamageNumbers {
+    /// 记下一次命中，由渲染器在下一帧显示
+    pub fn push(
+        target: InstanceId,
+        position: Vector2,
+        value: i64,
+        zone: Option<HitZone>,
+        crit: bool,
+    ) {
+        if !GameSettings::get().damage_numbers {
+            return;
+        }
+        let hit = DamageHit {
+            position,
+            value,
+            zone,
+            crit,
+        };
+        PENDING
+            .entry(target)
+            .and_modify(|pending| pending.merge(&hit))
+            .or_insert(hit);
+    }
+
+    fn show(&mut self, target: InstanceId, hit: DamageHit) {
+        if let Some(number) = self
+            .numbers
+            .iter_mut()
+            .find(|number| number.target == target && number.age < DAMAGE_NUMBER_MERGE_TIME)
+        {
+            number.hit.merge(&hit);
+            number.refresh();
+            return;
+        }
+        let label = if self.numbers.len() >= DAMAGE_NUMBER_MAX {
+            // 数字太多时直接复用最早的那个
+            Some(self.numbers.remove(0).label)
+        } else {
+            self.acquire()
+        };
+        if let Some(label) = label {
+            let mut number = DamageNumber {
+                target,
+                label,
+                hit,
+                age: 0.0,
+            };
+            number.refresh();
+            self.numbers.push(number);
+        }
+    }
+
+    fn acquire(&mut self) -> Option<Gd<RustMessage>> {
+        if let Some(mut label) = self.pool.pop() {
+            label.set_visible(true);
+            return Some(label);
+        }
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let label = MESSAGE.try_instantiate_as::<RustMessage>()?;
+        self.base_mut().add_child(&label);
+        Some(label)
+    }
+
+    fn release(&mut self, mut label: Gd<RustMessage>) {
+        label.set_visible(false);
+        self.pool.push(label);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::damage_number::RustDamageNumbers;
+use crate::player::RustPlayer;
+use crate::{
+    BLEED_DAMAGE, BLEED_MAX_STACKS, BLEED_TIME, BLIND_TIME, BURNING_DAMAGE, BURNING_TIME,
+    EFFECT_TICK_INTERVAL, POISON_DAMAGE, POISON_MAX_STACKS, POISON_TIME, SLOW_RATE, SLOW_TIME,
+    STUN_TIME, StatusEffect, VULNERABLE_RATE, VULNERABLE_TIME,
+};
+use godot::builtin::{Color, Vector2, real};
+use godot::meta::ToGodot;
+use godot::obj::InstanceId;
+use std::collections::BTreeMap;
+
+// 颜色优先级从高到低
+const COLOR_PRIORITY: [StatusEffect; 7] = [
+    StatusEffect::Stun,
+    StatusEffect::Blind,
+    StatusEffect::Burning,
+    StatusEffect::Poison,
+    StatusEffect::Bleed,
+    StatusEffect::Slow,
+    StatusEffect::Vulnerable,
+];
+
+impl StatusEffect {
+    pub fn duration(self) -> f64 {
+        match self {
+            StatusEffect::None => 0.0,
+            StatusEffect::Poison => POISON_TIME,
+            StatusEffect::Burning => BURNING_TIME,
+            StatusEffect::Slow => SLOW_TIME,
+            StatusEffect::Stun => STUN_TIME,
+            StatusEffect::Bleed => BLEED_TIME,
+            StatusEffect::Vulnerable => VULNERABLE_TIME,
+            StatusEffect::Blind => BLIND_TIME,
+        }
+    }
+
+    pub fn max_stacks(self) -> u32 {
+        match self {
+            StatusEffect::Poison => POISON_MAX_STACKS,
+            StatusEffect::Bleed => BLEED_MAX_STACKS,
+            _ => 1,
+        }
+    }
+
+    // 每层每跳造成的伤害
+    pub fn tick_damage(self) -> i64 {
+        match self {
+            StatusEffect::Poison => POISON_DAMAGE,
+            StatusEffect::Burning => BURNING_DAMAGE,
+            StatusEffect::Bleed => BLEED_DAMAGE,
+            _ => 0,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            StatusEffect::None => Color::WHITE,
+            StatusEffect::Poison => Color::from_rgb(0.55, 1.0, 0.45),
+            StatusEffect::Burning => Color::from_rgb(1.0, 0.6, 0.3),
+            StatusEffect::Slow => Color::from_rgb(0.55, 0.75, 1.0),
+            StatusEffect::Stun => Color::from_rgb(1.0, 1.0, 0.4),
+            StatusEffect::Bleed => Color::from_rgb(1.0, 0.4, 0.4),
+            StatusEffect::Vulnerable => Color::from_rgb(0.85, 0.55, 1.0),
+            StatusEffect::Blind => Color::from_rgb(0.8, 0.8, 0.8),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct EffectInstance {
+    stacks: u32,
+    left_time: f64,
+    tick_cooldown: f64,
+    // 最后一次施加该效果的玩家，僵尸之间施加的效果没有来源
+    source: Option<usize>,
+}
+
+/// 玩家和僵尸身上的状态效果
+#[derive(Debug, Default, Clone)]
+pub struct StatusEffects {
+    effects: BTreeMap<StatusEffect, EffectInstance>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) -> bool {
+        self.apply_from(effect, None)
+    }
+
+    /// 记下施加效果的玩家，持续伤害的分数归这名玩家
+    pub fn apply_from(&mut self, effect: StatusEffect, source: Option<usize>) -> bool {
+        if StatusEffect::None == effect {
+            return false;
+        }
+        if let Some(instance) = self.effects.get_mut(&effect) {
+            if StatusEffect::Stun == effect {
+                // 眩晕期间不能叠加，防止被控到死
+                return false;
+            }
+            // 叠层并刷新持续时间
+            instance.stacks = (instance.stacks + 1).min(effect.max_stacks());
+            instance.left_time = effect.duration();
+            instance.source = source;
+            return true;
+        }
+        self.effects.insert(
+            effect,
+            EffectInstance {
+                stacks: 1,
+                left_time: effect.duration(),
+                tick_cooldown: EFFECT_TICK_INTERVAL,
+                source,
+            },
+        );
+        true
+    }
+
+    /// 推进所有效果的计时，返回本帧造成的持续伤害
+    pub fn process(&mut self, delta: f64) -> i64 {
+        self.tick(delta).iter().map(|(damage, _)| damage).sum()
+    }
+
+    /// 僵尸的持续伤害显示伤害数字，分数记给施加效果的玩家，返回本帧造成的持续伤害
+    pub fn process_zombie(&mut self, delta: f64, zombie: InstanceId, position: Vector2) -> i64 {
+        let hits = self.tick(delta);
+        let damage: i64 = hits.iter().map(|(damage, _)| damage).sum();
+        if damage > 0 {
+            RustDamageNumbers::push(zombie, position, damage, None, false);
+        }
+        for (damage, source) in hits {
+            if let Some(mut player) = source.and_then(RustPlayer::get_by_index) {
+                player.call_deferred("add_score", &[damage.to_variant()]);
+            }
+        }
+        damage
+    }
+
+    // 每个效果本帧跳出的伤害和来源玩家
+    fn tick(&mut self, delta: f64) -> Vec<(i64, Option<usize>)> {
+        let mut hits = Vec::new();
+        for (effect, instance) in self.effects.iter_mut() {
+            instance.left_time -= delta;
+            instance.tick_cooldown -= delta;
+            if instance.tick_cooldown <= 0.0 {
+                instance.tick_cooldown += EFFECT_TICK_INTERVAL;
+                let damage = effect.tick_damage() * instance.stacks as i64;
+                if damage > 0 {
+                    hits.push((damage, instance.source));
+                }
+            }
+        }
+        self.effects.retain(|_, instance| instance.left_time > 0.0);
+        hits
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.effects.contains_key(&effect)
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.has(StatusEffect::Stun)
+    }
+
+    pub fn speed_rate(&self) -> real {
+        if self.is_stunned() {
+            0.0
+        } else if self.has(StatusEffect::Slow) {
+            SLOW_RATE
+        } else {
+            1.0
+        }
+    }
+
+    pub fn scale_damage(&self, hit_val: i64) -> i64 {
+        if hit_val > 0 && self.has(StatusEffect::Vulnerable) {
+            (hit_val as real * VULNERABLE_RATE) as i64
+        } else {
+            hit_val
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        COLOR_PRIORITY
+            .iter()
+            .find(|effect| self.has(**effect))
+            .map_or(Color::WHITE, |effect| effect.color())
+    }
+
+    pub fn describe(&self) -> String {
+        self.effects
+            .iter()
+            .map(|(effect, instance)| {
+                if instance.stacks > 1 {
+                    format!(
+                        "{:?}x{} {:.1}s",
+                        effect, instance.stacks, instance.left_time
+                    )
+                } else {
+                    format!("{:?} {:.1}s", effect, instance.left_time)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+            .to_uppercase()
+    }
+}
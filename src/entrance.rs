@@ -1,7 +1,13 @@
-use crate::scale_rate;
+use crate::achievement::RustAchievements;
+use crate::controls::RustControls;
+use crate::lobby::RustLobby;
+use crate::player::RustPlayer;
+use crate::settings::GameSettings;
 use crate::world::RustWorld;
+use crate::{FRIENDLY_FIRE_OPTIONS, PLAYER_CLASSES, scale_rate};
 use godot::classes::{
-    AudioStreamPlayer2D, Button, ColorRect, Control, IControl, PackedScene, Tween, VBoxContainer,
+    AudioStreamPlayer2D, Button, CheckButton, ColorRect, Control, IControl, OptionButton,
+    PackedScene, Tween, VBoxContainer,
 };
 use godot::meta::ToGodot;
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
@@ -30,8 +36,34 @@ impl IControl for RustEntrance {
     }
 
     fn ready(&mut self) {
+        RustControls::load_keymap();
         let gd = self.to_gd();
         let container = self.base().get_node_as::<VBoxContainer>("VBoxContainer");
+        let mut class_option = container.get_node_as::<OptionButton>("Class");
+        let mut p2_class_option = container.get_node_as::<OptionButton>("P2Class");
+        class_option.clear();
+        p2_class_option.clear();
+        let player_class = RustWorld::get_player_class(0);
+        let p2_player_class = RustWorld::get_player_class(1);
+        for (index, option) in PLAYER_CLASSES.iter().enumerate() {
+            let title = format!("{:?}", option).to_uppercase();
+            class_option.add_item(&title);
+            p2_class_option.add_item(&format!("2P {}", title));
+            if player_class == *option {
+                class_option.select(index as i32);
+            }
+            if p2_player_class == *option {
+                p2_class_option.select(index as i32);
+            }
+        }
+        class_option
+            .signals()
+            .item_selected()
+            .connect_obj(&gd, Self::on_class_selected);
+        p2_class_option
+            .signals()
+            .item_selected()
+            .connect_obj(&gd, Self::on_p2_class_selected);
         container
             .get_node_as::<Button>("Load")
             .signals()
@@ -47,6 +79,57 @@ impl IControl for RustEntrance {
             .signals()
             .pressed()
             .connect_obj(&gd, Self::on_endless_mode_pressed);
+        container
+            .get_node_as::<Button>("Online")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_online_pressed);
+        container
+            .get_node_as::<Button>("Controls")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_controls_pressed);
+        container
+            .get_node_as::<Button>("Achievements")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_achievements_pressed);
+        let mut damage_numbers = container.get_node_as::<CheckButton>("DamageNumbers");
+        damage_numbers.set_pressed_no_signal(GameSettings::get().damage_numbers);
+        damage_numbers
+            .signals()
+            .toggled()
+            .connect_obj(&gd, Self::on_damage_numbers_toggled);
+        // 入口界面修改普通模式的爆炸伤害规则，地狱模式使用自己的规则
+        let rules = GameSettings::get().damage_rules;
+        let mut self_damage = container.get_node_as::<OptionButton>("SelfDamage");
+        let mut team_damage = container.get_node_as::<OptionButton>("TeamDamage");
+        self_damage.clear();
+        team_damage.clear();
+        for (index, option) in FRIENDLY_FIRE_OPTIONS.iter().enumerate() {
+            self_damage.add_item(&format!("SELF DAMAGE {}", option.get_title()));
+            team_damage.add_item(&format!("TEAM DAMAGE {}", option.get_title()));
+            if rules.self_damage == *option {
+                self_damage.select(index as i32);
+            }
+            if rules.team_damage == *option {
+                team_damage.select(index as i32);
+            }
+        }
+        self_damage
+            .signals()
+            .item_selected()
+            .connect_obj(&gd, Self::on_self_damage_selected);
+        team_damage
+            .signals()
+            .item_selected()
+            .connect_obj(&gd, Self::on_team_damage_selected);
+        let mut zombie_damage = container.get_node_as::<CheckButton>("ZombieDamage");
+        zombie_damage.set_pressed_no_signal(rules.zombie_damage);
+        zombie_damage
+            .signals()
+            .toggled()
+            .connect_obj(&gd, Self::on_zombie_damage_toggled);
         container
             .get_node_as::<Button>("ExitGame")
             .signals()
@@ -74,6 +157,20 @@ impl RustEntrance {
         self.bgm.play();
     }
 
+    #[func]
+    pub fn on_class_selected(&mut self, index: i64) {
+        if let Some(player_class) = PLAYER_CLASSES.get(index as usize) {
+            RustWorld::set_player_class(0, *player_class);
+        }
+    }
+
+    #[func]
+    pub fn on_p2_class_selected(&mut self, index: i64) {
+        if let Some(player_class) = PLAYER_CLASSES.get(index as usize) {
+            RustWorld::set_player_class(1, *player_class);
+        }
+    }
+
     #[func]
     pub fn on_load_pressed(&mut self) {
         self.prepare().tween_callback(
@@ -96,6 +193,12 @@ impl RustEntrance {
 
     fn prepare(&mut self) -> Gd<Tween> {
         let container = self.base().get_node_as::<VBoxContainer>("VBoxContainer");
+        container
+            .get_node_as::<OptionButton>("Class")
+            .set_visible(false);
+        container
+            .get_node_as::<OptionButton>("P2Class")
+            .set_visible(false);
         container.get_node_as::<Button>("Load").set_visible(false);
         container
             .get_node_as::<Button>("HellMode")
@@ -103,6 +206,25 @@ impl RustEntrance {
         container
             .get_node_as::<Button>("EndlessMode")
             .set_visible(false);
+        container.get_node_as::<Button>("Online").set_visible(false);
+        container
+            .get_node_as::<Button>("Controls")
+            .set_visible(false);
+        container
+            .get_node_as::<Button>("Achievements")
+            .set_visible(false);
+        container
+            .get_node_as::<CheckButton>("DamageNumbers")
+            .set_visible(false);
+        container
+            .get_node_as::<OptionButton>("SelfDamage")
+            .set_visible(false);
+        container
+            .get_node_as::<OptionButton>("TeamDamage")
+            .set_visible(false);
+        container
+            .get_node_as::<CheckButton>("ZombieDamage")
+            .set_visible(false);
         container
             .get_node_as::<Button>("ExitGame")
             .set_visible(false);
@@ -131,6 +253,17 @@ impl RustEntrance {
         );
     }
 
+    /// 联机建好后进入游戏世界，存档只在单机时读取
+    #[func]
+    pub fn start_online(&mut self) {
+        self.prepare().tween_callback(
+            &self
+                .base_mut()
+                .callable("change_scene")
+                .bind(&[false.to_variant(), false.to_variant()]),
+        );
+    }
+
     #[func]
     pub fn change_scene(&mut self, hell: bool, load: bool) {
         if let Some(mut world) = self.world_scene.try_instantiate_as::<RustWorld>() {
@@ -138,12 +271,80 @@ impl RustEntrance {
                 if let Some(mut root) = tree.get_root() {
                     world.bind_mut().set_hell(hell);
                     world.bind_mut().set_load(load);
+                    world
+                        .get_node_as::<RustPlayer>("RustPlayer")
+                        .bind_mut()
+                        .set_player_class(RustWorld::get_player_class(0));
                     root.add_child(&world);
                 }
             }
         }
     }
 
+    #[func]
+    pub fn on_controls_pressed(&mut self) {
+        self.base()
+            .get_node_as::<VBoxContainer>("VBoxContainer")
+            .set_visible(false);
+        let mut controls = self.base().get_node_as::<RustControls>("RustControls");
+        controls.bind_mut().refresh();
+        controls.set_visible(true);
+    }
+
+    #[func]
+    pub fn on_achievements_pressed(&mut self) {
+        self.base()
+            .get_node_as::<VBoxContainer>("VBoxContainer")
+            .set_visible(false);
+        let mut achievements = self
+            .base()
+            .get_node_as::<RustAchievements>("RustAchievements");
+        achievements.bind_mut().refresh();
+        achievements.set_visible(true);
+    }
+
+    #[func]
+    pub fn on_online_pressed(&mut self) {
+        self.base()
+            .get_node_as::<VBoxContainer>("VBoxContainer")
+            .set_visible(false);
+        self.base()
+            .get_node_as::<RustLobby>("RustLobby")
+            .set_visible(true);
+    }
+
+    #[func]
+    pub fn on_damage_numbers_toggled(&mut self, toggled_on: bool) {
+        let mut settings = GameSettings::get();
+        settings.damage_numbers = toggled_on;
+        GameSettings::set(settings);
+    }
+
+    #[func]
+    pub fn on_self_damage_selected(&mut self, index: i64) {
+        if let Some(option) = FRIENDLY_FIRE_OPTIONS.get(index as usize) {
+            let mut settings = GameSettings::get();
+            settings.damage_rules.self_damage = *option;
+            GameSettings::set(settings);
+        }
+    }
+
+    #[func]
+    pub fn on_team_damage_selected(&mut self, index: i64) {
+        if let Some(option) = FRIENDLY_FIRE_OPTIONS.get(index as usize) {
+            let mut settings = GameSettings::get();
+            settings.damage_rules.team_damage = *option;
+            GameSettings::set(settings);
+        }
+    }
+
+    #[func]
+    pub fn on_zombie_damage_toggled(&mut self, toggled_on: bool) {
+        let mut settings = GameSettings::get();
+        settings.damage_rules.zombie_damage = toggled_on;
+        GameSettings::set(settings);
+    }
+
     #[func]
     pub fn on_exit_game_pressed(&mut self) {
         if let Some(mut tree) = self.base().get_tree() {
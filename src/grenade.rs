@@ -1,21 +1,40 @@
+use crate::combat::{CombatEventBus, KillCredit};
+use crate::common::RustMessage;
+use crate::damage::DamageSource;
+use crate::mastery::WeaponMastery;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
-use crate::{EXPLODE_AUDIOS, NO_NOISE, is_survivor, is_zombie};
+use crate::{
+    EXPLODE_AUDIOS, FLASH_DISTANCE, GrenadeType, HEAL_GRENADE_HEAL, MELEE_PARRY_DAMAGE, MESSAGE,
+    NO_NOISE, SMOKE_RADIUS, SMOKE_TIME, StatusEffect, Team, ZOMBIE_GRENADE_DISTANCE, is_alive,
+    is_survivor, is_zombie,
+};
 use crossbeam_utils::atomic::AtomicCell;
-use godot::builtin::{Callable, Vector2, real};
+use dashmap::DashMap;
+use godot::builtin::{Callable, StringName, Vector2, real};
 use godot::classes::node::PhysicsInterpolationMode;
 use godot::classes::{
-    AnimatedSprite2D, Area2D, AudioStreamPlayer2D, GpuParticles2D, INode2D, Node2D, Object,
-    Sprite2D, TextureRect,
+    AnimatedSprite2D, Area2D, AudioStreamPlayer2D, GpuParticles2D, INode2D, Node, Node2D, Object,
+    PackedScene, Sprite2D, TextureRect,
 };
 use godot::global::godot_error;
 use godot::meta::ToGodot;
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
+use godot::tools::load;
+use std::sync::LazyLock;
 
 static NOISE_POSITION: AtomicCell<Vector2> = AtomicCell::new(NO_NOISE);
 
+#[allow(clippy::declare_interior_mutable_const)]
+const EXPLOSIVE_ROUND: LazyLock<Gd<PackedScene>> =
+    LazyLock::new(|| load("res://scenes/bullets/rust_explosive_round.tscn"));
+
 static ZOMBIE_NOISE_POSITION: AtomicCell<Vector2> = AtomicCell::new(NO_NOISE);
 
+// 正在生效的烟雾，key为烟雾弹的instance_id
+static SMOKES: LazyLock<DashMap<i64, Vector2>> = LazyLock::new(DashMap::new);
+
 #[derive(GodotClass)]
 #[class(base=Node2D)]
 pub struct RustGrenade {
@@ -29,12 +48,24 @@ pub struct RustGrenade {
     timed: bool,
     #[export]
     from_zombie: bool,
+    #[export]
+    effect: StatusEffect,
+    #[export]
+    #[doc = "手雷类型"]
+    grenade_type: GrenadeType,
+    #[export]
+    #[doc = "冷却时间倍率"]
+    cooldown_rate: real,
     hit: bool,
     bullet_point: Vector2,
     final_distance: real,
     final_repel: real,
     final_damage: i64,
     direction: Vector2,
+    // 投掷手雷的玩家
+    player_index: usize,
+    // 发射榴弹的武器，手投的手雷为空
+    weapon_name: StringName,
     hit_area: OnReady<Gd<Area2D>>,
     damage_area: OnReady<Gd<Area2D>>,
     explode_audio: OnReady<Gd<AudioStreamPlayer2D>>,
@@ -52,12 +83,17 @@ impl INode2D for RustGrenade {
             countdown: 1.0,
             timed: true,
             from_zombie: false,
+            effect: StatusEffect::None,
+            grenade_type: GrenadeType::Frag,
+            cooldown_rate: 1.0,
             hit: false,
             bullet_point: Vector2::ZERO,
             final_distance: 0.0,
             final_repel: 0.0,
             final_damage: 0,
             direction: Vector2::ZERO,
+            player_index: 0,
+            weapon_name: StringName::default(),
             hit_area: OnReady::from_node("HitArea"),
             damage_area: OnReady::from_node("DamageArea"),
             explode_audio: OnReady::from_node("ExplodeAudio"),
@@ -68,9 +104,15 @@ impl INode2D for RustGrenade {
     }
 
     fn physics_process(&mut self, delta: f64) {
-        if self.is_cleaned() || self.hit {
+        if !RustNetwork::is_authority() || self.is_cleaned() || self.hit {
             return;
         }
+        if self.from_zombie {
+            let position = self.base().get_global_position();
+            if let Some(player_index) = RustPlayer::get_parrying_index(position) {
+                self.deflect(player_index);
+            }
+        }
         let direction = self.direction;
         let speed = self.speed;
         let bullet_point = self.bullet_point;
@@ -95,8 +137,10 @@ impl INode2D for RustGrenade {
         self.explode_flash.set_visible(false);
         self.base_mut()
             .set_physics_interpolation_mode(PhysicsInterpolationMode::ON);
-        let mouse_position = self.get_mouse_position();
-        self.base_mut().look_at(mouse_position);
+        let aim_position = RustPlayer::get_aim_position(self.player_index);
+        self.base_mut().look_at(aim_position);
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &[".:position", ".:rotation"]);
         if self.timed {
             if let Some(mut tree) = self.base().get_tree() {
                 if let Some(mut timer) = tree.create_timer(self.countdown) {
@@ -118,6 +162,10 @@ impl INode2D for RustGrenade {
             .animation_finished()
             .connect_obj(&gd, Self::clean_body);
     }
+
+    fn exit_tree(&mut self) {
+        SMOKES.remove(&self.base().instance_id().to_i64());
+    }
 }
 
 #[godot_api]
@@ -141,12 +189,69 @@ impl RustGrenade {
         self.final_repel = final_repel;
     }
 
+    pub fn set_effect(&mut self, effect: StatusEffect) {
+        self.effect = effect;
+    }
+
     pub fn set_direction(&mut self, direction: Vector2) {
         self.direction = direction;
     }
 
+    pub fn set_player_index(&mut self, player_index: usize) {
+        self.player_index = player_index;
+    }
+
+    pub fn set_weapon_name(&mut self, weapon_name: StringName) {
+        self.weapon_name = weapon_name;
+    }
+
+    pub fn get_grenade_type(&self) -> GrenadeType {
+        self.grenade_type
+    }
+
+    pub fn get_cooldown_rate(&self) -> real {
+        self.cooldown_rate
+    }
+
+    /// 僵尸手雷属于僵尸阵营，其余属于投掷或发射它的玩家
+    pub fn get_source(&self) -> DamageSource {
+        if self.from_zombie {
+            DamageSource::zombie()
+        } else {
+            DamageSource::survivor(self.player_index)
+        }
+    }
+
+    // 被格挡的僵尸手雷飞向格挡玩家瞄准的方向，改为炸僵尸
+    fn deflect(&mut self, player_index: usize) {
+        let position = self.base().get_global_position();
+        self.from_zombie = false;
+        self.player_index = player_index;
+        self.direction = position
+            .direction_to(RustPlayer::get_aim_position(player_index))
+            .normalized();
+        self.bullet_point = position;
+        self.final_distance = ZOMBIE_GRENADE_DISTANCE;
+        self.final_damage = MELEE_PARRY_DAMAGE;
+        let direction = self.direction;
+        self.base_mut().look_at(position + direction);
+        #[allow(clippy::borrow_interior_mutable_const)]
+        if let Some(mut parry_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
+            parry_label.set_global_position(position);
+            if let Some(tree) = self.base().get_tree() {
+                if let Some(mut root) = tree.get_root() {
+                    root.add_child(&parry_label);
+                    parry_label.bind_mut().show_message("PARRY");
+                }
+            }
+        }
+    }
+
     #[func]
     pub fn explode_ext(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if self.from_zombie {
             if is_survivor(&***body) {
                 self.hit = true;
@@ -166,20 +271,13 @@ impl RustGrenade {
 
     #[func]
     pub fn explode(&mut self) {
-        if self.is_cleaned() {
+        if !RustNetwork::is_authority() || self.is_cleaned() {
             return;
         }
-        //播放音效
-        if self.explode_audio.get_stream().is_none() {
-            #[allow(clippy::borrow_interior_mutable_const)]
-            if let Some(audio) = EXPLODE_AUDIOS.pick_random() {
-                self.explode_audio.set_stream(&audio);
-            }
+        self.play_explosion();
+        if RustNetwork::is_host() {
+            self.base_mut().rpc("show_explosion", &[]);
         }
-        self.explode_audio.play();
-        self.explode_flash.set_visible(true);
-        self.explode_flash.set_global_rotation_degrees(0.0);
-        self.explode_flash.play_ex().name("default").done();
         self.hit_area.queue_free();
         self.texture_rect.queue_free();
         // 兼容火箭类型雷
@@ -193,36 +291,25 @@ impl RustGrenade {
             particles.queue_free();
         }
         let position = self.base().get_global_position();
-        for mut body in self.damage_area.get_overlapping_bodies().iter_shared() {
-            if !body.is_instance_valid() {
-                continue;
+        match self.grenade_type {
+            GrenadeType::Frag => self.damage_bodies(position),
+            GrenadeType::Flash => self.flash_zombies(position),
+            GrenadeType::Smoke => {
+                // 烟雾弹不造成伤害也不发出噪音
+                self.damage_area.queue_free();
+                self.release_smoke(position);
+                return;
             }
-            if is_survivor(&***body) {
-                body.cast::<RustPlayer>()
-                    .bind_mut()
-                    .on_hit(self.final_damage, position);
-            } else if !self.from_zombie && is_zombie(&***body) {
-                let direction = position.direction_to(body.get_global_position());
-                body.call_deferred(
-                    "on_hit",
-                    &[
-                        self.final_damage.to_variant(),
-                        direction.to_variant(),
-                        self.final_repel.to_variant(),
-                        position.to_variant(),
-                    ],
-                );
-                if self.final_damage > 0 {
-                    RustPlayer::get().call_deferred("add_score", &[self.final_damage.to_variant()]);
+            GrenadeType::Heal => {
+                // 治疗雷只治疗玩家，同样不发出噪音
+                self.heal_survivors();
+                self.damage_area.queue_free();
+                if let Some(mut tree) = self.base().get_tree() {
+                    if let Some(mut timer) = tree.create_timer(1.0) {
+                        timer.connect("timeout", &self.base().callable("dissipate"));
+                    }
                 }
-            } else if body.is_class("RustGrenade") || is_zombie(&***body) {
-                // ok
-            } else {
-                godot_error!(
-                    "{} hit an unexpected body: {}",
-                    self.base().get_name(),
-                    body.get_class()
-                );
+                return;
             }
         }
         self.damage_area.queue_free();
@@ -256,6 +343,215 @@ impl RustGrenade {
         }
     }
 
+    /// 客户端只播放主机通知的爆炸效果
+    #[rpc(authority, call_remote, reliable)]
+    pub fn show_explosion(&mut self) {
+        self.play_explosion();
+        self.texture_rect.set_visible(false);
+    }
+
+    fn play_explosion(&mut self) {
+        //播放音效
+        if self.explode_audio.get_stream().is_none() {
+            #[allow(clippy::borrow_interior_mutable_const)]
+            if let Some(audio) = EXPLODE_AUDIOS.pick_random() {
+                self.explode_audio.set_stream(&audio);
+            }
+        }
+        self.explode_audio.play();
+        self.explode_flash.set_visible(true);
+        self.explode_flash.set_global_rotation_degrees(0.0);
+        self.explode_flash.play_ex().name("default").done();
+    }
+
+    fn damage_bodies(&mut self, position: Vector2) {
+        let source = self.get_source();
+        for mut body in self.damage_area.get_overlapping_bodies().iter_shared() {
+            if !body.is_instance_valid() {
+                continue;
+            }
+            if is_survivor(&***body) {
+                let mut player = body.cast::<RustPlayer>();
+                let rate = source.get_player_rate(player.bind().get_player_index());
+                if rate > 0.0 {
+                    let damage = (self.final_damage as real * rate) as i64;
+                    player.bind_mut().on_hit(damage, position);
+                    player.bind_mut().apply_effect(self.effect);
+                }
+            } else if is_zombie(&***body) {
+                let rate = source.get_zombie_rate();
+                if rate > 0.0 {
+                    Self::damage_zombie(
+                        &mut body,
+                        position,
+                        (self.final_damage as real * rate) as i64,
+                        self.final_repel,
+                        self.effect,
+                        source,
+                        &self.weapon_name.to_string(),
+                    );
+                }
+            } else if body.is_class("RustGrenade") {
+                // ok
+            } else {
+                godot_error!(
+                    "{} hit an unexpected body: {}",
+                    self.base().get_name(),
+                    body.get_class()
+                );
+            }
+        }
+    }
+
+    /// 爆炸对僵尸造成伤害，爆炸弹药也走这里
+    fn damage_zombie(
+        body: &mut Gd<Node2D>,
+        position: Vector2,
+        damage: i64,
+        repel: real,
+        effect: StatusEffect,
+        source: DamageSource,
+        weapon_name: &str,
+    ) {
+        let direction = position.direction_to(body.get_global_position());
+        // 僵尸之间的误伤不给分数、熟练度和击杀
+        let from_survivor = Team::Survivor == source.team;
+        if from_survivor {
+            WeaponMastery::record_damage(weapon_name, damage);
+            CombatEventBus::record_hit(
+                body.instance_id(),
+                KillCredit::new(source.player_index, weapon_name),
+            );
+        } else {
+            CombatEventBus::clear_hit(body.instance_id());
+        }
+        body.call_deferred(
+            "on_hit",
+            &[
+                damage.to_variant(),
+                direction.to_variant(),
+                repel.to_variant(),
+                position.to_variant(),
+            ],
+        );
+        if StatusEffect::None != effect {
+            if from_survivor {
+                body.call_deferred(
+                    "apply_player_effect",
+                    &[
+                        effect.to_variant(),
+                        (source.player_index as u32).to_variant(),
+                    ],
+                );
+            } else {
+                body.call_deferred("apply_effect", &[effect.to_variant()]);
+            }
+        }
+        if from_survivor && damage > 0 {
+            if let Some(mut player) = RustPlayer::get_by_index(source.player_index) {
+                player.call_deferred("add_score", &[damage.to_variant()]);
+            }
+        }
+    }
+
+    /// 爆炸弹药在命中点引爆一颗小型榴弹，伤害和误伤规则与其他榴弹相同
+    pub fn detonate_round(
+        parent: &mut Gd<Node>,
+        position: Vector2,
+        damage: i64,
+        repel: real,
+        player_index: usize,
+        weapon_name: &str,
+    ) {
+        #[allow(clippy::borrow_interior_mutable_const)]
+        let Some(mut grenade) = EXPLOSIVE_ROUND.try_instantiate_as::<RustGrenade>() else {
+            godot_error!("Failed to instantiate explosive round");
+            return;
+        };
+        grenade.set_global_position(position);
+        let mut gd_mut = grenade.bind_mut();
+        gd_mut.set_bullet_point(position);
+        gd_mut.set_final_damage(damage);
+        gd_mut.set_final_repel(repel);
+        gd_mut.set_player_index(player_index);
+        gd_mut.set_weapon_name(StringName::from(weapon_name));
+        drop(gd_mut);
+        RustNetwork::name_spawned(&mut grenade.clone().upcast());
+        // 命中时可能正在物理回调里，延迟加入场景
+        parent.call_deferred("add_child", &[grenade.to_variant()]);
+    }
+
+    // 闪光弹只对能看到爆点的僵尸生效
+    fn flash_zombies(&mut self, position: Vector2) {
+        let Some(tree) = self.base().get_tree() else {
+            return;
+        };
+        for mut node in tree.get_nodes_in_group("zombie").iter_shared() {
+            if !node.is_instance_valid() || !is_alive(&node) {
+                continue;
+            }
+            let zombie_position = node.clone().cast::<Node2D>().get_global_position();
+            if zombie_position.distance_to(position) > FLASH_DISTANCE
+                || Self::is_vision_blocked(zombie_position, position)
+            {
+                continue;
+            }
+            let direction = node.call("get_current_direction", &[]).to::<Vector2>();
+            let angle = direction
+                .angle_to(zombie_position.direction_to(position))
+                .to_degrees()
+                .abs();
+            if angle > 90.0 {
+                continue;
+            }
+            node.call_deferred("on_flashed", &[]);
+        }
+    }
+
+    fn heal_survivors(&mut self) {
+        for body in self.damage_area.get_overlapping_bodies().iter_shared() {
+            if body.is_instance_valid() && is_survivor(&***body) {
+                body.cast::<RustPlayer>().bind_mut().heal(HEAL_GRENADE_HEAL);
+            }
+        }
+    }
+
+    fn release_smoke(&mut self, position: Vector2) {
+        SMOKES.insert(self.base().instance_id().to_i64(), position);
+        if let Some(mut tree) = self.base().get_tree() {
+            if let Some(mut timer) = tree.create_timer(SMOKE_TIME) {
+                timer.connect("timeout", &self.base().callable("dissipate"));
+            }
+        }
+    }
+
+    #[func]
+    pub fn dissipate(&mut self) {
+        if !self.base().is_instance_valid() {
+            return;
+        }
+        SMOKES.remove(&self.base().instance_id().to_i64());
+        // 烟雾动画是循环的，停下后才能回收
+        self.explode_flash.stop();
+        self.clean_body();
+    }
+
+    /// 视线是否被烟雾遮挡
+    pub fn is_vision_blocked(from: Vector2, to: Vector2) -> bool {
+        SMOKES.iter().any(|smoke| {
+            let center = *smoke.value();
+            let segment = to - from;
+            let length_squared = segment.length_squared();
+            let closest = if length_squared > 0.0 {
+                let t = ((center - from).dot(segment) / length_squared).clamp(0.0, 1.0);
+                from + segment * t
+            } else {
+                from
+            };
+            closest.distance_to(center) < SMOKE_RADIUS
+        })
+    }
+
     #[func]
     pub fn clean_body(&mut self) {
         if !self.base().is_instance_valid() {
@@ -284,15 +580,6 @@ impl RustGrenade {
             || self.explode_flash.is_playing()
     }
 
-    pub fn get_mouse_position(&self) -> Vector2 {
-        self.base().get_canvas_transform().affine_inverse()
-            * self
-                .base()
-                .get_viewport()
-                .expect("Viewport not found")
-                .get_mouse_position()
-    }
-
     pub fn get_noise_position() -> Option<Vector2> {
         let r = NOISE_POSITION.load();
         if NO_NOISE == r { None } else { Some(r) }
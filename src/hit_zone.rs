@@ -0,0 +1,113 @@
+use crate::{
+    HIT_ZONE_DAMAGE_RATES, HIT_ZONE_LIMB_OFFSET, HIT_ZONE_REPEL_RATES, HIT_ZONE_WEAK_POINT_RADIUS,
+    HIT_ZONES, HitZone, StatusEffect,
+};
+use godot::builtin::{Color, PackedFloat32Array, Variant, Vector2, real};
+use godot::classes::{CircleShape2D, CollisionShape2D, Node2D};
+use godot::obj::Gd;
+
+impl HitZone {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            HitZone::Head => "HEAD",
+            HitZone::Torso => "TORSO",
+            HitZone::Limb => "LIMB",
+            HitZone::WeakPoint => "WEAK POINT",
+        }
+    }
+
+    /// 伤害数字的颜色
+    pub fn get_color(&self) -> Color {
+        match self {
+            HitZone::Head => Color::from_rgb(1.0, 0.3, 0.3),
+            HitZone::Torso => Color::WHITE,
+            HitZone::Limb => Color::from_rgb(0.6, 0.8, 1.0),
+            HitZone::WeakPoint => Color::from_rgb(1.0, 0.85, 0.2),
+        }
+    }
+
+    /// 按弹道判断命中部位，从背后穿过弱点算弱点，穿过头部算爆头，偏离身体中心太远算四肢
+    pub fn locate(body: &Gd<Node2D>, position: Vector2, direction: Vector2) -> HitZone {
+        let direction = direction.normalized();
+        let facing = Vector2::RIGHT.rotated(body.get_global_rotation());
+        if direction.dot(facing) > 0.0 {
+            if let Some(weak_point) = body.try_get_node_as::<Node2D>("WeakPoint") {
+                let offset =
+                    Self::get_offset(position, direction, weak_point.get_global_position());
+                if offset <= HIT_ZONE_WEAK_POINT_RADIUS {
+                    return HitZone::WeakPoint;
+                }
+            }
+        }
+        if let Some((center, radius)) = Self::get_circle(body, "HeadShape2D") {
+            if Self::get_offset(position, direction, center) <= radius {
+                return HitZone::Head;
+            }
+        }
+        if let Some((center, radius)) = Self::get_circle(body, "CollisionShape2D") {
+            if Self::get_offset(position, direction, center) > radius * HIT_ZONE_LIMB_OFFSET {
+                return HitZone::Limb;
+            }
+        }
+        HitZone::Torso
+    }
+
+    /// 这个部位的伤害倍率和击退倍率，僵尸场景可以用元数据覆盖
+    pub fn get_rates(&self, body: &Gd<Node2D>) -> (real, real) {
+        let index = *self as usize;
+        let get_rate = |name: &str, defaults: &[real; HIT_ZONES.len()]| {
+            body.get_meta_ex(name)
+                .default(&Variant::nil())
+                .done()
+                .try_to::<PackedFloat32Array>()
+                .ok()
+                .and_then(|rates| rates.get(index))
+                .unwrap_or(defaults[index])
+        };
+        (
+            get_rate("zone_damage_rates", &HIT_ZONE_DAMAGE_RATES),
+            get_rate("zone_repel_rates", &HIT_ZONE_REPEL_RATES),
+        )
+    }
+
+    // 点到弹道所在直线的距离
+    fn get_offset(position: Vector2, direction: Vector2, point: Vector2) -> real {
+        direction.cross(point - position).abs()
+    }
+
+    // 圆形碰撞体的圆心和半径，僵尸死亡时碰撞体已经释放
+    fn get_circle(body: &Gd<Node2D>, name: &str) -> Option<(Vector2, real)> {
+        let shape2d = body.try_get_node_as::<CollisionShape2D>(name)?;
+        let circle = shape2d.get_shape()?.try_cast::<CircleShape2D>().ok()?;
+        Some((
+            shape2d.get_global_position(),
+            circle.get_radius() * shape2d.get_global_scale().x.abs(),
+        ))
+    }
+}
+
+/// 僵尸这次受击的部位和是否暴击，伤害数字据此显示，各种僵尸共用
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ZoneHit {
+    zone: Option<HitZone>,
+    crit: bool,
+}
+
+impl ZoneHit {
+    /// 子弹按命中部位结算前记下部位，打中四肢时返回减速
+    pub fn record(&mut self, zone: HitZone, crit: bool) -> StatusEffect {
+        self.zone = Some(zone);
+        self.crit = crit;
+        if HitZone::Limb == zone {
+            StatusEffect::Slow
+        } else {
+            StatusEffect::None
+        }
+    }
+
+    /// 取出这次受击的部位和暴击，爆炸等伤害没有部位
+    pub fn take(&mut self) -> (Option<HitZone>, bool) {
+        let hit = std::mem::take(self);
+        (hit.zone, hit.crit)
+    }
+}
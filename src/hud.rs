@@ -1,13 +1,20 @@
 use crate::player::RustPlayer;
-use godot::builtin::real;
+use crate::weapon::RustWeapon;
+use crate::{
+    AimSource, Attachment, CROSSHAIR_BLOOM_PIXELS, CROSSHAIR_MAX_BLOOM, CROSSHAIR_SCALE,
+    PlayerClass,
+};
+use godot::builtin::{Color, GString, Vector2, real};
+use godot::classes::control::{GrowDirection, LayoutPreset};
 use godot::classes::input::MouseMode;
 use godot::classes::notify::NodeNotification;
 use godot::classes::{
     Button, CanvasLayer, Control, Engine, HBoxContainer, ICanvasLayer, Input, Label, Texture2D,
     TextureRect, VBoxContainer,
 };
-use godot::meta::AsObjectArg;
-use godot::obj::{Base, Gd, OnReady, WithBaseField};
+use godot::global::HorizontalAlignment;
+use godot::meta::{AsObjectArg, ToGodot};
+use godot::obj::{Base, Gd, NewAlloc, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
 
 #[derive(GodotClass)]
@@ -17,6 +24,8 @@ pub struct RustHUD {
     cross_hair: OnReady<Gd<TextureRect>>,
     control: OnReady<Gd<Control>>,
     upgrade: OnReady<Gd<Control>>,
+    loadout: OnReady<Gd<Control>>,
+    weapon_wheel: OnReady<Gd<Control>>,
     base: Base<CanvasLayer>,
 }
 
@@ -28,6 +37,8 @@ impl ICanvasLayer for RustHUD {
             cross_hair: OnReady::from_node("CrossHair"),
             control: OnReady::from_node("Control"),
             upgrade: OnReady::from_node("Upgrade"),
+            loadout: OnReady::from_node("Loadout"),
+            weapon_wheel: OnReady::from_node("WeaponWheel"),
             base,
         }
     }
@@ -49,9 +60,22 @@ impl ICanvasLayer for RustHUD {
     fn process(&mut self, delta: f64) {
         let viewport = self.base().get_viewport().unwrap();
         let affine_inverse = self.base().get_transform().affine_inverse();
-        let mouse_position =
-            affine_inverse * viewport.get_mouse_position() - self.cross_hair.get_size() / 2.0;
-        self.cross_hair.set_position(mouse_position);
+        let player_index = RustPlayer::find_owner_index(self.to_gd().upcast());
+        let screen_position = match RustPlayer::get_aim_source(player_index) {
+            AimSource::Mouse => viewport.get_mouse_position(),
+            // 摇杆瞄准点在世界坐标中，需要转换到屏幕坐标
+            AimSource::Stick => {
+                viewport.get_canvas_transform() * RustPlayer::get_aim_position(player_index)
+            }
+        };
+        let cross_hair_position =
+            affine_inverse * screen_position - self.cross_hair.get_size() / 2.0;
+        self.cross_hair.set_position(cross_hair_position);
+        // 准星随当前武器的散布放大
+        let bloom = (1.0 + RustWeapon::get_player_spread(player_index) / CROSSHAIR_BLOOM_PIXELS)
+            .min(CROSSHAIR_MAX_BLOOM);
+        self.cross_hair
+            .set_scale(Vector2::new(CROSSHAIR_SCALE, CROSSHAIR_SCALE) * bloom);
         self.start_time += delta;
         self.update_fps_hud();
         self.update_played_time_hud();
@@ -95,6 +119,56 @@ impl ICanvasLayer for RustHUD {
                 .signals()
                 .pressed()
                 .connect_obj(&player, RustPlayer::upgrade_health);
+            self.get_container()
+                .get_node_as::<Button>("Stamina")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_stamina);
+            self.get_container()
+                .get_node_as::<Button>("Recover")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_recover);
+            self.get_container()
+                .get_node_as::<Button>("CritChance")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_crit_chance);
+            self.get_container()
+                .get_node_as::<Button>("CritDamage")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_crit_damage);
+            self.get_container()
+                .get_node_as::<Button>("Class")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_class);
+            self.get_container()
+                .get_node_as::<Button>("Perk")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::upgrade_perk);
+            self.loadout
+                .get_node_as::<Button>("VBoxContainer/Close")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::close_loadout);
+            self.loadout
+                .get_node_as::<Button>("VBoxContainer/Order/MoveUp")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::move_weapon_up);
+            self.loadout
+                .get_node_as::<Button>("VBoxContainer/Order/MoveDown")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::move_weapon_down);
+            self.loadout
+                .get_node_as::<Button>("VBoxContainer/Order/Favorite")
+                .signals()
+                .pressed()
+                .connect_obj(&player, RustPlayer::toggle_favorite_weapon);
         }
     }
 }
@@ -119,6 +193,60 @@ impl RustHUD {
         hp_hud.show();
     }
 
+    pub fn update_stamina_hud(&mut self, stamina: real, max_stamina: real) {
+        let mut stamina_hud = self
+            .get_left_top_container()
+            .get_node_as::<Label>("Stamina");
+        stamina_hud.set_text(&format!("STAMINA {:.0}/{:.0}", stamina, max_stamina));
+        stamina_hud.show();
+    }
+
+    pub fn update_ability_hud(&mut self, player_class: PlayerClass, cooldown: f64, active: f64) {
+        let mut ability_hud = self
+            .get_left_top_container()
+            .get_node_as::<Label>("Ability");
+        let name = format!("{:?}", player_class).to_uppercase();
+        ability_hud.set_text(&if active > 0.0 {
+            format!("{} ACTIVE {:.1}S", name, active)
+        } else if cooldown > 0.0 {
+            format!("{} ABILITY {:.0}S", name, cooldown.ceil())
+        } else {
+            format!("{} ABILITY READY", name)
+        });
+        ability_hud.show();
+    }
+
+    pub fn update_perks_hud(&mut self, perks: &str) {
+        let mut perks_hud = self.get_left_top_container().get_node_as::<Label>("Perks");
+        perks_hud.set_text(&if perks.is_empty() {
+            String::from("PERKS NONE")
+        } else {
+            format!("PERKS {}", perks)
+        });
+        perks_hud.show();
+    }
+
+    /// 天赋集齐后不再提供天赋升级
+    pub fn update_perk_upgrade(&mut self, visible: bool) {
+        self.get_container()
+            .get_node_as::<Button>("Perk")
+            .set_visible(visible);
+    }
+
+    /// 暴击率到上限后不再提供暴击率升级
+    pub fn update_crit_chance_upgrade(&mut self, visible: bool) {
+        self.get_container()
+            .get_node_as::<Button>("CritChance")
+            .set_visible(visible);
+    }
+
+    /// 职业专属升级，无职业时隐藏
+    pub fn update_class_upgrade(&mut self, text: &str) {
+        let mut class_upgrade = self.get_container().get_node_as::<Button>("Class");
+        class_upgrade.set_text(text);
+        class_upgrade.set_visible(!text.is_empty());
+    }
+
     pub fn update_damage_hud(&mut self, weapon_damage: i64, player_damage: i64) {
         let mut damage_hud = self.get_left_top_container().get_node_as::<Label>("Damage");
         damage_hud.set_text(&format!(
@@ -174,6 +302,19 @@ impl RustHUD {
         jitter_hud.show();
     }
 
+    pub fn update_effects_hud(&mut self, effects: &str, poisoned: bool) {
+        let mut effects_hud = self
+            .get_left_top_container()
+            .get_node_as::<HBoxContainer>("Effects");
+        effects_hud
+            .get_node_as::<TextureRect>("Poison")
+            .set_visible(poisoned);
+        effects_hud
+            .get_node_as::<Label>("Label")
+            .set_text(&format!("EFFECTS {}", effects));
+        effects_hud.set_visible(!effects.is_empty());
+    }
+
     pub fn update_killed_hud(&mut self, kill_count: u32) {
         let mut repel_hud = self.get_hcontainer().get_node_as::<Label>("Killed");
         repel_hud.set_text(&format!("KILLED {}", kill_count,));
@@ -339,6 +480,41 @@ impl RustHUD {
         label.show();
     }
 
+    pub fn update_stealth_hud(&mut self, visibility: real, sneaking: bool, in_bush: bool) {
+        let mut stealth_hud = self
+            .get_left_top_container()
+            .get_node_as::<Label>("Stealth");
+        let mut text = format!("VISIBILITY {:.0}%", visibility * 100.0);
+        if sneaking {
+            text.push_str(" SNEAK");
+        }
+        if in_bush {
+            text.push_str(" HIDDEN");
+        }
+        stealth_hud.set_text(&text);
+        // 越难被发现颜色越暗
+        stealth_hud.set_modulate(
+            Color::WHITE.lerp(Color::from_rgb(0.4, 0.6, 1.0), 1.0 - visibility.min(1.0)),
+        );
+        stealth_hud.show();
+    }
+
+    pub fn update_melee_hud(&mut self, melee_name: &str) {
+        let mut melee_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("Melee");
+        melee_hud.set_text(melee_name);
+        melee_hud.show();
+    }
+
+    pub fn update_grenade_hud(&mut self, text: &str) {
+        let mut grenade_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("Grenade");
+        grenade_hud.set_text(text);
+        grenade_hud.show();
+    }
+
     pub fn update_weapon_name_hud(&mut self, weapon_name: &str) {
         let mut ammo_hud = self
             .get_right_bottom_container()
@@ -355,6 +531,47 @@ impl RustHUD {
         ammo_hud.show();
     }
 
+    /// 熟练度进度，武器图标按熟练度外观着色
+    pub fn update_mastery_hud(&mut self, progress: &str, color: Color) {
+        let mut container = self.get_right_bottom_container();
+        let mut mastery_hud = container.get_node_as::<Label>("Mastery");
+        mastery_hud.set_text(progress);
+        mastery_hud.add_theme_color_override("font_color", color);
+        mastery_hud.show();
+        container
+            .get_node_as::<TextureRect>("WeaponTexture")
+            .set_self_modulate(color);
+    }
+
+    #[func]
+    pub fn update_fire_mode_hud(&mut self, text: GString) {
+        let mut fire_mode_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("FireMode");
+        fire_mode_hud.set_text(&text);
+        fire_mode_hud.show();
+    }
+
+    /// 转管转速和机枪热量，没有这些机制的武器隐藏
+    #[func]
+    pub fn update_mechanic_hud(&mut self, text: GString) {
+        let mut mechanic_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("Mechanic");
+        mechanic_hud.set_text(&text);
+        mechanic_hud.set_visible(!text.is_empty());
+    }
+
+    #[func]
+    pub fn update_ammo_type_hud(&mut self, text: GString, color: Color) {
+        let mut ammo_type_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("AmmoType");
+        ammo_type_hud.set_text(&text);
+        ammo_type_hud.add_theme_color_override("font_color", color);
+        ammo_type_hud.show();
+    }
+
     #[func]
     pub fn update_ammo_hud(&mut self, ammo: i32, clip: i32) {
         let mut ammo_hud = self
@@ -364,10 +581,164 @@ impl RustHUD {
         ammo_hud.show();
     }
 
+    /// 用热量代替弹匣的武器不显示弹药，清空文字让客户端同步到的也是空的
+    #[func]
+    pub fn hide_ammo_hud(&mut self) {
+        let mut ammo_hud = self
+            .get_right_bottom_container()
+            .get_node_as::<Label>("WeaponAmmo");
+        ammo_hud.set_text("");
+        ammo_hud.hide();
+    }
+
+    pub fn update_downed_hud(&mut self, left_bleed_time: f64, revive_progress: f64) {
+        let mut hp_hud = self.get_left_top_container().get_node_as::<Label>("HP");
+        hp_hud.set_text(&format!(
+            "DOWNED {:.1}s REVIVE {:.0}%",
+            left_bleed_time.max(0.0),
+            revive_progress.min(1.0) * 100.0
+        ));
+        hp_hud.show();
+    }
+
+    /// 2P的HUD挪到屏幕下方，避免和1P重叠
+    pub fn use_teammate_layout(&mut self) {
+        self.get_center_container().set_visible(false);
+        self.get_right_top_container().set_visible(false);
+        let mut left = self.get_left_top_container();
+        left.set_anchors_and_offsets_preset(LayoutPreset::BOTTOM_LEFT);
+        left.set_v_grow_direction(GrowDirection::BEGIN);
+        let mut right = self.get_right_bottom_container();
+        right.set_anchors_and_offsets_preset(LayoutPreset::CENTER_BOTTOM);
+        right.set_h_grow_direction(GrowDirection::BOTH);
+        // 2P准星用不同颜色区分
+        self.cross_hair.set_modulate(Color::from_rgb(0.4, 1.0, 0.4));
+        self.upgrade.set_visible(false);
+    }
+
     pub fn set_upgrade_visible(&mut self, visible: bool) {
         self.upgrade.set_visible(visible);
     }
 
+    pub fn set_loadout_visible(&mut self, visible: bool) {
+        self.loadout.set_visible(visible);
+    }
+
+    /// 按当前武器重建配件列表，每一项是配件、是否已装上、是否已解锁
+    pub fn update_loadout(
+        &mut self,
+        weapon_name: &str,
+        entries: &[(Attachment, bool, bool)],
+        player: &Gd<RustPlayer>,
+    ) {
+        self.loadout
+            .get_node_as::<Label>("VBoxContainer/Title")
+            .set_text(&format!("{} LOADOUT", weapon_name));
+        let mut list = self
+            .loadout
+            .get_node_as::<VBoxContainer>("VBoxContainer/List");
+        for mut child in list.get_children().iter_shared() {
+            list.remove_child(&child);
+            child.queue_free();
+        }
+        for (attachment, equipped, unlocked) in entries {
+            let mut button = Button::new_alloc();
+            button.set_text(&if !unlocked {
+                format!(
+                    "{} (LEVEL {})",
+                    attachment.get_title(),
+                    attachment.get_unlock_level()
+                )
+            } else if *equipped {
+                format!(
+                    "[X] {}: {}",
+                    attachment.get_title(),
+                    attachment.get_description()
+                )
+            } else {
+                format!(
+                    "[ ] {}: {}",
+                    attachment.get_title(),
+                    attachment.get_description()
+                )
+            });
+            button.set_disabled(!unlocked);
+            button.connect(
+                "pressed",
+                &player
+                    .callable("toggle_attachment")
+                    .bind(&[attachment.to_variant()]),
+            );
+            list.add_child(&button);
+        }
+    }
+
+    /// 当前武器在切换顺序里的位置，排在两头时不能再移动
+    pub fn update_loadout_slot(&mut self, slot: usize, slot_count: usize, favorite: bool) {
+        let mut order = self
+            .loadout
+            .get_node_as::<HBoxContainer>("VBoxContainer/Order");
+        order
+            .get_node_as::<Button>("MoveUp")
+            .set_disabled(0 == slot);
+        order
+            .get_node_as::<Button>("MoveDown")
+            .set_disabled(slot + 1 >= slot_count);
+        order
+            .get_node_as::<Button>("Favorite")
+            .set_text(if favorite {
+                "[X] FAVORITE"
+            } else {
+                "[ ] FAVORITE"
+            });
+        order
+            .get_node_as::<Label>("Slot")
+            .set_text(&format!("SLOT {}", slot + 1));
+    }
+
+    /// 按位置摆放武器名，每一项是名称、相对轮盘中心的位置、是否为当前武器
+    pub fn show_weapon_wheel(&mut self, entries: &[(String, Vector2, bool)]) {
+        for mut child in self.weapon_wheel.get_children().iter_shared() {
+            self.weapon_wheel.remove_child(&child);
+            child.queue_free();
+        }
+        for (title, position, current) in entries {
+            let mut label = Label::new_alloc();
+            label.set_text(title);
+            label.set_horizontal_alignment(HorizontalAlignment::CENTER);
+            label.set_size(Vector2::new(120.0, 24.0));
+            label.set_position(*position - Vector2::new(60.0, 12.0));
+            if *current {
+                label.set_modulate(Color::from_rgb(0.4, 1.0, 0.4));
+            }
+            self.weapon_wheel.add_child(&label);
+        }
+        self.weapon_wheel.set_visible(true);
+    }
+
+    /// 轮盘中心放在玩家的屏幕位置上
+    pub fn move_weapon_wheel(&mut self, center: Vector2) {
+        let position = self.base().get_transform().affine_inverse() * center;
+        self.weapon_wheel.set_position(position);
+    }
+
+    pub fn hide_weapon_wheel(&mut self) {
+        self.weapon_wheel.set_visible(false);
+    }
+
+    /// 放大准星指着的武器
+    pub fn select_weapon_wheel(&mut self, slot: Option<usize>) {
+        for (i, child) in self.weapon_wheel.get_children().iter_shared().enumerate() {
+            let mut label = child.cast::<Label>();
+            label.set_pivot_offset(label.get_size() / 2.0);
+            label.set_scale(if Some(i) == slot {
+                Vector2::new(1.5, 1.5)
+            } else {
+                Vector2::ONE
+            });
+        }
+    }
+
     fn get_left_top_container(&mut self) -> Gd<VBoxContainer> {
         self.control.get_node_as::<VBoxContainer>("VBoxTopLeft")
     }
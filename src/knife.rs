@@ -1,15 +1,28 @@
+use crate::achievement::RustAchievements;
+use crate::combat::{CombatEventBus, KillCredit};
 use crate::common::RustMessage;
+use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::zombie::boss::RustBoss;
-use crate::{MESSAGE, is_boss, is_survivor, not_boss};
-use godot::builtin::{Array, Vector2, real};
+use crate::{
+    ASSASSINATE_ANGLE, ASSASSINATE_SCORE_BONUS, Achievement, BOSS_COMBO_STAGGER_TIME,
+    BOSS_HEAVY_STAGGER_TIME, KNIFE_CATCH_DISTANCE, KNIFE_THROW_DISTANCE, KNIFE_THROW_SPEED,
+    MELEE_PATH, MESSAGE, MeleeWeapon, StatusEffect, is_boss, is_survivor, not_boss, random_chance,
+};
+use godot::builtin::{Array, Color, Vector2, real};
+use godot::classes::file_access::ModeFlags;
 use godot::classes::tween::{EaseType, TransitionType};
-use godot::classes::{Area2D, AudioStream, AudioStreamPlayer2D, IArea2D, Node2D};
+use godot::classes::{
+    Area2D, AudioStream, AudioStreamPlayer2D, FileAccess, IArea2D, Node2D, Sprite2D,
+};
 use godot::global::godot_error;
 use godot::meta::ToGodot;
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
 use godot::tools::load;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 #[allow(clippy::declare_interior_mutable_const)]
@@ -24,15 +37,116 @@ const HIT_AUDIOS: LazyLock<Array<Gd<AudioStream>>> = LazyLock::new(|| {
     audios
 });
 
+/// 三段连击每段的挥砍角度倍率、伤害倍率，以及是否反向挥砍
+pub const COMBO_STEPS: [(real, real, bool); 3] =
+    [(1.0, 1.0, false), (1.0, 1.2, true), (0.4, 1.6, false)];
+
+// 重击的挥砍角度、击退和出手时间倍率
+const HEAVY_ANGLE_RATE: real = 1.3;
+
+const HEAVY_REPEL_RATE: real = 2.0;
+
+const HEAVY_SWING_RATE: f64 = 1.5;
+
+// 投掷伤害倍率
+const THROW_DAMAGE_RATE: real = 1.5;
+
+// 近战武器的数值，读取失败时用默认数值
+static MELEE_DATA: LazyLock<HashMap<MeleeWeapon, MeleeData>> = LazyLock::new(|| {
+    let Some(file) = FileAccess::open(MELEE_PATH, ModeFlags::READ) else {
+        godot_error!("Melee definitions not found: {}", MELEE_PATH);
+        return HashMap::new();
+    };
+    match serde_json::from_str(&file.get_as_text().to_string()) {
+        Ok(data) => data,
+        Err(e) => {
+            godot_error!("Failed to parse melee definitions: {}", e);
+            HashMap::new()
+        }
+    }
+});
+
+/// 近战武器的数值
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MeleeData {
+    pub name: String,
+    pub damage: i64,
+    pub repel: real,
+    // 挥砍角度的一半
+    pub attack_angle: real,
+    // 挥砍一次的时间
+    pub swing_time: f64,
+    // 相对玩家斩击冷却的倍率
+    pub cooldown_rate: real,
+    // 重击伤害倍率
+    pub heavy_rate: real,
+    pub effect: StatusEffect,
+    pub effect_chance: real,
+    pub throwable: bool,
+    // 刀身颜色和大小
+    pub color: [f32; 3],
+    pub scale: real,
+}
+
+impl Default for MeleeData {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            damage: 80,
+            repel: 30.0,
+            attack_angle: 67.5,
+            swing_time: 0.2,
+            cooldown_rate: 1.0,
+            heavy_rate: 2.5,
+            effect: StatusEffect::None,
+            effect_chance: 0.0,
+            throwable: true,
+            color: [1.0, 1.0, 1.0],
+            scale: 1.0,
+        }
+    }
+}
+
+impl MeleeWeapon {
+    pub fn get_data(&self) -> MeleeData {
+        MELEE_DATA.get(self).cloned().unwrap_or_else(|| MeleeData {
+            name: format!("{:?}", self).to_uppercase(),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum KnifeFlight {
+    // 在玩家手上
+    #[default]
+    Held,
+    // 投掷飞行中
+    Thrown,
+    // 插在地上等待回收
+    Stuck,
+    // 飞回玩家
+    Recall,
+}
+
 #[derive(GodotClass)]
 #[class(base=Area2D)]
 pub struct RustKnife {
+    #[doc = "近战武器，决定伤害、挥砍角度等数值"]
     #[export]
-    max_attack_angle: real,
-    #[export]
-    anticlockwise: bool,
+    melee: MeleeWeapon,
     final_repel: real,
     final_damage: i64,
+    // 允许暗杀的最大背后夹角
+    final_assassinate_angle: real,
+    // 命中BOSS造成的硬直时间
+    final_stagger: f64,
+    parrying: bool,
+    flight: KnifeFlight,
+    throw_origin: Vector2,
+    throw_direction: Vector2,
+    sprite: OnReady<Gd<Sprite2D>>,
     damage_area: OnReady<Gd<Area2D>>,
     chop_audio: OnReady<Gd<AudioStreamPlayer2D>>,
     hit_audio: OnReady<Gd<AudioStreamPlayer2D>>,
@@ -43,10 +157,16 @@ pub struct RustKnife {
 impl IArea2D for RustKnife {
     fn init(base: Base<Area2D>) -> Self {
         Self {
-            max_attack_angle: 67.5,
-            anticlockwise: false,
+            melee: MeleeWeapon::Katana,
             final_repel: 0.0,
             final_damage: 0,
+            final_assassinate_angle: ASSASSINATE_ANGLE,
+            final_stagger: 0.0,
+            parrying: false,
+            flight: KnifeFlight::Held,
+            throw_origin: Vector2::ZERO,
+            throw_direction: Vector2::ZERO,
+            sprite: OnReady::from_node("Sprite2D"),
             damage_area: OnReady::from_node("DamageArea"),
             chop_audio: OnReady::from_node("ChopAudio"),
             hit_audio: OnReady::from_node("HitAudio"),
@@ -64,21 +184,83 @@ impl IArea2D for RustKnife {
             .signals()
             .body_entered()
             .connect_obj(&gd, Self::on_area_2d_body_entered);
+        let melee = self.melee;
+        self.set_melee(melee);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
+        let position = self.base().get_global_position();
+        let step = KNIFE_THROW_SPEED * delta as real;
+        match self.flight {
+            KnifeFlight::Held | KnifeFlight::Stuck => {}
+            KnifeFlight::Thrown => {
+                let new_position = position + self.throw_direction * step;
+                self.base_mut().set_global_position(new_position);
+                self.spin(delta);
+                if new_position.distance_to(self.throw_origin) >= KNIFE_THROW_DISTANCE {
+                    self.flight = KnifeFlight::Stuck;
+                    let rotation = self.throw_direction.angle();
+                    self.base_mut().set_global_rotation(rotation);
+                }
+            }
+            KnifeFlight::Recall => {
+                let player_position = RustPlayer::get_position_of(self.get_player_index());
+                if position.distance_to(player_position) <= KNIFE_CATCH_DISTANCE.max(step) {
+                    self.catch();
+                    return;
+                }
+                let direction = position.direction_to(player_position);
+                self.base_mut()
+                    .set_global_position(position + direction * step);
+                self.spin(delta);
+            }
+        }
     }
 }
 
 #[godot_api]
 impl RustKnife {
-    pub fn chop(&mut self, final_damage: i64, final_repel: real) {
-        if self.base().is_visible() {
+    /// 挥砍，combo_step为连击的第几段，重击不计入连击
+    pub fn chop(
+        &mut self,
+        damage: i64,
+        repel: real,
+        final_assassinate_angle: real,
+        combo_step: usize,
+        heavy: bool,
+    ) {
+        if !self.is_ready() {
             return;
         }
-        self.final_damage = final_damage;
-        self.final_repel = final_repel;
-        let max_attack_angle = if self.anticlockwise {
-            -self.max_attack_angle
+        let data = self.melee.get_data();
+        let (angle_rate, damage_rate, reverse) = if heavy {
+            (HEAVY_ANGLE_RATE, data.heavy_rate, false)
+        } else {
+            COMBO_STEPS[combo_step % COMBO_STEPS.len()]
+        };
+        self.final_damage = ((data.damage + damage) as real * damage_rate) as i64;
+        self.final_repel = data.repel + repel;
+        self.final_stagger = 0.0;
+        if heavy {
+            self.final_repel *= HEAVY_REPEL_RATE;
+            self.final_stagger = BOSS_HEAVY_STAGGER_TIME;
+        } else if combo_step + 1 == COMBO_STEPS.len() {
+            self.final_stagger = BOSS_COMBO_STAGGER_TIME;
+        }
+        self.final_assassinate_angle = final_assassinate_angle;
+        self.parrying = false;
+        let max_attack_angle = if reverse {
+            -data.attack_angle * angle_rate
         } else {
-            self.max_attack_angle
+            data.attack_angle * angle_rate
+        };
+        let swing_time = if heavy {
+            data.swing_time * HEAVY_SWING_RATE
+        } else {
+            data.swing_time
         };
         self.base_mut()
             .set_global_rotation_degrees(-max_attack_angle);
@@ -94,7 +276,7 @@ impl RustKnife {
                 &self.base.to_gd(),
                 "rotation",
                 &max_attack_angle.to_radians().to_variant(),
-                0.2,
+                swing_time,
             )
             .expect("tween failed")
             .from(&(-max_attack_angle).to_radians().to_variant());
@@ -102,11 +284,96 @@ impl RustKnife {
         self.chop_audio.play();
     }
 
+    /// 横刀格挡一段时间，期间不造成伤害
+    pub fn parry(&mut self, time: f64) {
+        if !self.is_ready() {
+            return;
+        }
+        self.parrying = true;
+        self.base_mut().set_rotation_degrees(90.0);
+        self.base_mut().set_visible(true);
+        let mut tween = self
+            .base_mut()
+            .create_tween()
+            .expect("Failed to create tween");
+        tween.tween_interval(time);
+        tween.tween_callback(&self.base().callable("hide"));
+    }
+
+    /// 朝指定方向投掷，不能投掷或刀不在手上时返回false
+    pub fn throw(&mut self, damage: i64, repel: real, direction: Vector2) -> bool {
+        let data = self.melee.get_data();
+        if !self.is_ready() || !data.throwable {
+            return false;
+        }
+        self.final_damage = ((data.damage + damage) as real * THROW_DAMAGE_RATE) as i64;
+        self.final_repel = data.repel + repel;
+        self.final_stagger = 0.0;
+        self.parrying = false;
+        self.flight = KnifeFlight::Thrown;
+        let position = self.base().get_global_position();
+        self.throw_origin = position;
+        self.throw_direction = direction.normalized();
+        let mut base_mut = self.base_mut();
+        // 脱离玩家，不再跟随玩家移动
+        base_mut.set_as_top_level(true);
+        base_mut.set_global_position(position);
+        base_mut.set_global_rotation(direction.angle());
+        base_mut.set_visible(true);
+        drop(base_mut);
+        self.chop_audio.play();
+        true
+    }
+
+    /// 召回投掷出去的刀，飞回途中同样造成伤害
+    pub fn recall(&mut self) {
+        if matches!(self.flight, KnifeFlight::Thrown | KnifeFlight::Stuck) {
+            self.flight = KnifeFlight::Recall;
+            self.chop_audio.play();
+        }
+    }
+
+    fn catch(&mut self) {
+        self.flight = KnifeFlight::Held;
+        let mut base_mut = self.base_mut();
+        base_mut.set_as_top_level(false);
+        base_mut.set_position(Vector2::ZERO);
+        base_mut.set_rotation(0.0);
+        base_mut.set_visible(false);
+    }
+
+    fn spin(&mut self, delta: f64) {
+        let rotation = self.base().get_rotation() + 20.0 * delta as real;
+        self.base_mut().set_rotation(rotation);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        KnifeFlight::Held == self.flight && !self.base().is_visible()
+    }
+
+    pub fn is_thrown(&self) -> bool {
+        KnifeFlight::Held != self.flight
+    }
+
+    pub fn set_melee(&mut self, melee: MeleeWeapon) {
+        self.melee = melee;
+        let data = melee.get_data();
+        let [r, g, b] = data.color;
+        self.sprite.set_modulate(Color::from_rgb(r, g, b));
+        if KnifeFlight::Held == self.flight {
+            self.base_mut().set_scale(Vector2::splat(data.scale));
+        }
+    }
+
     #[func]
     pub fn on_area_2d_body_entered(&mut self, mut body: Gd<Node2D>) {
-        if !self.base().is_visible() {
+        if !RustNetwork::is_authority() {
             return;
         }
+        if !self.base().is_visible() || self.parrying || KnifeFlight::Stuck == self.flight {
+            return;
+        }
+        let data = self.melee.get_data();
         let position = self.base().get_global_position();
         let mut damage = 0;
         if not_boss(&***body) {
@@ -118,16 +385,28 @@ impl RustKnife {
             }
             let zombie_position = body.get_global_position();
             let direction = position.direction_to(zombie_position);
-            // 暗杀判定
-            if self.try_assassinate(&mut body) {
+            // 暗杀判定，飞刀不能暗杀
+            if KnifeFlight::Held == self.flight && self.try_assassinate(&mut body) {
                 damage *= 3;
+                RustAchievements::add_progress(Achievement::Assassin, 1);
+                // 未被发现时暗杀有额外分数
+                let unalerted =
+                    !body.call("is_alarmed", &[]).to::<bool>() && !RustLevel::is_rampage();
+                let message = if unalerted {
+                    if let Some(mut player) = RustPlayer::get_by_index(self.get_player_index()) {
+                        player.call_deferred("add_score", &[ASSASSINATE_SCORE_BONUS.to_variant()]);
+                    }
+                    format!("SILENT ASSASSINATE +{}", ASSASSINATE_SCORE_BONUS)
+                } else {
+                    String::from("ASSASSINATE")
+                };
                 #[allow(clippy::borrow_interior_mutable_const)]
                 if let Some(mut assassinate_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
                     assassinate_label.set_global_position(position);
                     if let Some(tree) = self.base().get_tree() {
                         if let Some(mut root) = tree.get_root() {
                             root.add_child(&assassinate_label);
-                            assassinate_label.bind_mut().show_message("ASSASSINATE");
+                            assassinate_label.bind_mut().show_message(&message);
                         }
                     }
                 }
@@ -135,6 +414,10 @@ impl RustKnife {
             if !body.is_instance_valid() {
                 return;
             }
+            CombatEventBus::record_hit(
+                body.instance_id(),
+                KillCredit::melee(self.get_player_index()),
+            );
             body.call_deferred(
                 "on_hit",
                 &[
@@ -144,6 +427,15 @@ impl RustKnife {
                     (zombie_position + direction).to_variant(),
                 ],
             );
+            if random_chance(data.effect_chance) {
+                body.call_deferred(
+                    "apply_player_effect",
+                    &[
+                        data.effect.to_variant(),
+                        (self.get_player_index() as u32).to_variant(),
+                    ],
+                );
+            }
         } else if is_boss(&***body) {
             damage = self.final_damage;
             #[allow(clippy::borrow_interior_mutable_const)]
@@ -154,38 +446,59 @@ impl RustKnife {
             let mut boss = body.cast::<RustBoss>();
             let boss_position = boss.get_global_position();
             let direction = position.direction_to(boss_position);
+            CombatEventBus::record_hit(
+                boss.instance_id(),
+                KillCredit::melee(self.get_player_index()),
+            );
             boss.bind_mut().on_hit(
                 damage,
                 direction,
                 self.final_repel,
                 boss_position + direction,
             );
+            if random_chance(data.effect_chance) {
+                boss.bind_mut()
+                    .apply_player_effect(data.effect, self.get_player_index() as u32);
+            }
+            if self.final_stagger > 0.0 {
+                boss.bind_mut().stagger(self.final_stagger);
+            }
         } else if is_survivor(&***body) {
             // ok
         } else {
             godot_error!("Knife hit an unexpected body: {}", body.get_class());
         }
         if damage > 0 {
-            RustPlayer::get().call_deferred("add_score", &[damage.to_variant()]);
+            if let Some(mut player) = RustPlayer::get_by_index(self.get_player_index()) {
+                player.call_deferred("add_score", &[damage.to_variant()]);
+            }
         }
     }
 
     #[func]
     pub fn hide(&mut self) {
+        self.parrying = false;
         self.base_mut().set_visible(false);
-        RustPlayer::get().bind_mut().chopped();
+        if let Some(mut player) = RustPlayer::get_by_index(self.get_player_index()) {
+            player.bind_mut().chopped();
+        }
+    }
+
+    // 刀挂在所属玩家节点下
+    fn get_player_index(&self) -> usize {
+        RustPlayer::find_owner_index(self.to_gd().upcast())
     }
 
     // 僵尸背对玩家，则判定可暗杀
     pub fn try_assassinate(&self, zombie: &mut Gd<Node2D>) -> bool {
         let zombie_position = zombie.get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.get_player_index());
         let to_player_dir = player_position.direction_to(zombie_position).normalized();
         let angle = zombie
             .call("get_current_direction", &[])
             .to::<Vector2>()
             .angle_to(to_player_dir)
             .to_degrees();
-        (-60.0..=60.0).contains(&angle)
+        (-self.final_assassinate_angle..=self.final_assassinate_angle).contains(&angle)
     }
 }
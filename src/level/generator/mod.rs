@@ -1,4 +1,5 @@
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::world::RustWorld;
 use crate::{ZOMBIE_MAX_SCREEN_COUNT, ZOMBIE_REFRESH_BARRIER, random_degree};
@@ -78,6 +79,9 @@ impl INode2D for ZombieGenerator {
     }
 
     fn input(&mut self, event: Gd<InputEvent>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if event.is_action_pressed("esc") {
             RustPlayer::reset_last_score_update();
             if self.timer.is_stopped() {
@@ -157,6 +161,9 @@ impl ZombieGenerator {
 
     #[func]
     pub fn generate(&mut self) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         for _ in 0..self.current_refresh_count {
             let kill_count = self.get_killed();
             let live_count = self
@@ -204,7 +211,7 @@ impl ZombieGenerator {
     }
 
     pub fn generate_zombie(&self) {
-        if !self.base().is_visible() {
+        if !RustNetwork::is_authority() || !self.base().is_visible() {
             return;
         }
         let mut zombies = Vec::new();
@@ -213,6 +220,7 @@ impl ZombieGenerator {
                 zombie
                     .set_global_position(RustPlayer::get_position() + RustWorld::random_position());
                 zombie.set_global_rotation_degrees(random_degree());
+                RustNetwork::name_spawned(&mut zombie.clone().upcast());
                 zombies.push(zombie.to_variant());
             }
         }
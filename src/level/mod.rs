@@ -1,13 +1,17 @@
+use crate::achievement::RustAchievements;
+use crate::combat::CombatEventBus;
 use crate::hud::RustHUD;
 use crate::level::generator::ZombieGenerator;
+use crate::mastery::WeaponMastery;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::save::RustSaveLoader;
 use crate::world::RustWorld;
 use crate::{
-    BOOMER_MAX_SCREEN_COUNT, BOOMER_REFRESH_BARRIER, BOSS_MAX_SCREEN_COUNT, BOSS_REFRESH_BARRIER,
-    LEVEL_GROW_RATE, LEVEL_RAMPAGE_TIME, PITCHER_MAX_SCREEN_COUNT, PITCHER_REFRESH_BARRIER,
-    RUSHER_MAX_SCREEN_COUNT, RUSHER_REFRESH_BARRIER, ZOMBIE_MAX_SCREEN_COUNT,
-    ZOMBIE_REFRESH_BARRIER, kill_all_zombies,
+    Achievement, BOOMER_MAX_SCREEN_COUNT, BOOMER_REFRESH_BARRIER, BOSS_MAX_SCREEN_COUNT,
+    BOSS_REFRESH_BARRIER, LEVEL_GROW_RATE, LEVEL_RAMPAGE_TIME, PITCHER_MAX_SCREEN_COUNT,
+    PITCHER_REFRESH_BARRIER, RUSHER_MAX_SCREEN_COUNT, RUSHER_REFRESH_BARRIER,
+    ZOMBIE_MAX_SCREEN_COUNT, ZOMBIE_REFRESH_BARRIER, kill_all_zombies,
 };
 use godot::builtin::real;
 use godot::classes::{AudioStreamPlayer2D, INode2D, InputEvent, Node2D, Timer};
@@ -28,6 +32,9 @@ static RAMPAGE: AtomicBool = AtomicBool::new(false);
 
 static LIVE_COUNT: AtomicU32 = AtomicU32::new(0);
 
+// 当前是否地狱模式，爆炸伤害规则据此切换
+static HELL: AtomicBool = AtomicBool::new(false);
+
 #[derive(GodotClass)]
 #[class(base=Node2D)]
 pub struct RustLevel {
@@ -101,7 +108,7 @@ impl INode2D for RustLevel {
         self.bgm.set_global_position(player_position);
         self.rampage_bgm.set_global_position(player_position);
         self.boss_bgm.set_global_position(player_position);
-        if RustWorld::is_paused() {
+        if RustWorld::is_paused() || !RustNetwork::is_authority() {
             return;
         }
         let zombie_killed = self.get_zombie_killed();
@@ -198,10 +205,14 @@ impl INode2D for RustLevel {
             .signals()
             .finished()
             .connect_obj(&gd, Self::play_boss_bgm);
+        HELL.store(self.hell, Ordering::Release);
         self.level_up(false);
     }
 
     fn input(&mut self, event: Gd<InputEvent>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if event.is_action_pressed("l") {
             self.left_rampage_time = 0.0;
         } else if cfg!(feature = "develop") && event.is_action_pressed("j") {
@@ -383,8 +394,14 @@ impl RustLevel {
         }
         RAMPAGE.store(false, Ordering::Release);
         RustPlayer::reset_last_score_update();
+        RustAchievements::on_level_cleared(self.level > 0 && !jump);
+        CombatEventBus::clear_hits();
+        WeaponMastery::save_profile();
         let rate = self.grow_rate.powf(self.level as f32);
         self.level += 1;
+        if self.hell {
+            RustAchievements::reach_progress(Achievement::HellWalker, self.level);
+        }
         // 加强M4A1，不然后续的消音没什么意义
         // self.left_rampage_time = self.rampage_time / rate;
         self.left_rampage_time = self.rampage_time;
@@ -446,64 +463,8 @@ impl RustLevel {
     }
 
     fn unlock_weapons(&mut self) {
-        // 这里的判断不加else，恢复存档时方便解锁武器
-        let mut player = RustPlayer::get();
-        if self.level >= 33 {
-            // 相对强力的武器
-            player.call_deferred("unlock_skull_5", &[]);
-        }
-        if self.level >= 32 {
-            // 相对强力的武器
-            player.call_deferred("unlock_xm1134", &[]);
-        }
-        if self.level >= 31 {
-            player.call_deferred("unlock_m32", &[]);
-        }
-        if self.level >= 30 {
-            player.call_deferred("unlock_m134", &[]);
-        }
-        if self.level >= 29 {
-            player.call_deferred("unlock_m95", &[]);
-        }
-        if self.level >= 28 {
-            // 相对强力的武器
-            player.call_deferred("unlock_skull_6", &[]);
-        }
-        if self.level >= 27 {
-            player.call_deferred("unlock_mg3", &[]);
-        }
-        if self.level >= 26 {
-            player.call_deferred("unlock_m249", &[]);
-        }
-        if self.level >= 25 {
-            player.call_deferred("unlock_rpg_7", &[]);
-        }
-        if self.level >= 24 {
-            // 相对强力的武器
-            player.call_deferred("unlock_ak47_60r", &[]);
-        }
-        if self.level >= 21 {
-            player.call_deferred("unlock_xm1014", &[]);
-        }
-        if self.level >= 18 {
-            player.call_deferred("unlock_ak47", &[]);
-        }
-        if self.level >= 15 {
-            player.call_deferred("unlock_m4a1", &[]);
-        }
-        if self.level >= 12 {
-            // 相对强力的武器
-            player.call_deferred("unlock_m79", &[]);
-        }
-        if self.level >= 9 {
-            player.call_deferred("unlock_awp", &[]);
-        }
-        if self.level >= 6 {
-            player.call_deferred("unlock_m1887", &[]);
-        }
-        if self.level >= 3 {
-            player.call_deferred("unlock_deagle", &[]);
-        }
+        // 解锁所有不高于当前关卡的武器，恢复存档时方便解锁武器
+        RustPlayer::get().call_deferred("unlock_level_weapons", &[self.level.to_variant()]);
     }
 
     pub fn play_bgm(&mut self) {
@@ -565,6 +526,7 @@ impl RustLevel {
         self.rampage_time = 0.0;
         self.left_rampage_time = self.rampage_time;
         self.hell = true;
+        HELL.store(true, Ordering::Release);
         self.zombie_generator
             .bind_mut()
             .refresh_timer(self.zombie_refresh_time);
@@ -586,6 +548,10 @@ impl RustLevel {
         RAMPAGE.load(Ordering::Acquire)
     }
 
+    pub fn is_hell() -> bool {
+        HELL.load(Ordering::Acquire)
+    }
+
     pub fn get_live_count() -> u32 {
         LIVE_COUNT.load(Ordering::Acquire)
     }
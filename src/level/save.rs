@@ -55,6 +55,7 @@ impl RustLevel {
             let json = vec.iter().next().unwrap();
             if let Ok(save_data) = serde_json::from_str::<LevelData>(json) {
                 self.hell = save_data.hell;
+                HELL.store(self.hell, Ordering::Release);
                 self.level = save_data.level;
                 self.grow_rate = save_data.grow_rate;
                 self.rampage_time = save_data.rampage_time;
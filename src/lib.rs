@@ -1,13 +1,14 @@
 use dashmap::DashMap;
 use godot::builtin::{Array, GString, Vector2, real};
 use godot::classes::{
-    AudioStream, DisplayServer, Input, InputEvent, InputEventAction, Object, PackedScene,
+    AudioStream, DisplayServer, Input, InputEvent, InputEventAction, Node, Object, PackedScene,
 };
 use godot::init::{ExtensionLibrary, gdextension};
 use godot::obj::{Gd, NewGd};
-use godot::register::GodotConvert;
+use godot::register::{Export, GodotConvert, Var};
 use godot::tools::load;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::ops::Deref;
 use std::sync::LazyLock;
@@ -45,6 +46,34 @@ pub mod zombie;
 
 pub mod save;
 
+pub mod controls;
+
+pub mod effect;
+
+pub mod network;
+
+pub mod lobby;
+
+pub mod achievement;
+
+pub mod combat;
+
+pub mod perk;
+
+pub mod mastery;
+
+pub mod ammo;
+
+pub mod hit_zone;
+
+pub mod settings;
+
+pub mod damage_number;
+
+pub mod damage;
+
+pub mod loadout;
+
 // game info
 const DEFAULT_SCREEN_SIZE: Vector2 = Vector2::new(960.0, 540.0);
 
@@ -53,6 +82,63 @@ const SAVE_PATH: &str = "user://data/rust_data.json";
 
 static SAVE: LazyLock<DashMap<String, HashSet<String>>> = LazyLock::new(DashMap::new);
 
+// controls
+const KEYMAP_PATH: &str = "user://data/rust_keymap.json";
+
+// weapon
+const WEAPONS_PATH: &str = "res://data/weapons.json";
+
+// 近战武器的数值，按MeleeWeapon的名字索引
+const MELEE_PATH: &str = "res://data/melee.json";
+
+// 玩家自制的武器定义，每个json文件一把枪
+const WEAPON_MODS_DIR: &str = "user://mods/weapons";
+
+// achievement
+const ACHIEVEMENTS_PATH: &str = "user://data/rust_achievements.json";
+
+const HELL_ACHIEVEMENT_LEVEL: u32 = 10;
+
+// 一发AWP子弹穿透的僵尸数
+const SKEWER_ACHIEVEMENT_COUNT: u32 = 3;
+
+// mastery
+const MASTERY_PATH: &str = "user://data/rust_mastery.json";
+
+// 每个熟练度等级需要的累计击杀数
+const MASTERY_TIER_KILLS: [u32; 4] = [50, 200, 500, 1000];
+
+const MASTERY_RELOAD_RATE: real = 0.9;
+
+const MASTERY_JITTER_RATE: real = 0.8;
+
+const MASTERY_PENETRATE: real = 0.5;
+
+// settings
+const SETTINGS_PATH: &str = "user://data/rust_settings.json";
+
+// loadout
+const LOADOUT_PATH: &str = "user://data/rust_loadout.json";
+
+// 武器轮盘上武器名到中心的距离
+const WEAPON_WHEEL_RADIUS: real = 150.0;
+
+// 准星离玩家太近时不选中任何武器
+const WEAPON_WHEEL_DEADZONE: real = 24.0;
+
+// perk
+const PERK_KILL_HEAL: u32 = 2;
+
+const PERK_SHOCKWAVE_DISTANCE: real = 150.0;
+
+const PERK_SHOCKWAVE_REPEL: real = 40.0;
+
+// 精英僵尸掉落天赋的概率，BOSS必定掉落
+const PERK_DROP_CHANCE: real = 0.05;
+
+// 天赋掉落物无人拾取时自动消失
+const PERK_DROP_LIFETIME: f64 = 30.0;
+
 // common
 #[allow(clippy::declare_interior_mutable_const)]
 const MESSAGE: LazyLock<Gd<PackedScene>> = LazyLock::new(|| load("res://scenes/rust_message.tscn"));
@@ -82,15 +168,156 @@ const PLAYER_MOVE_SPEED: real = 225.0;
 
 const PLAYER_ALARM_DISTANCE: real = 200.0;
 
+// stamina
+const PLAYER_MAX_STAMINA: real = 100.0;
+
+const STAMINA_RECOVER_RATE: real = 20.0;
+
+const STAMINA_RUN_COST: real = 25.0;
+
+const STAMINA_RECOVER_BARRIER: real = 0.3;
+
+const DODGE_STAMINA_COST: real = 30.0;
+
+const DODGE_TIME: f64 = 0.4;
+
+const DODGE_INVINCIBLE_TIME: f64 = 0.25;
+
+const DODGE_SPEED_RATE: real = 2.5;
+
+// stealth
+const SNEAK_SPEED_RATE: real = 0.5;
+
+const SNEAK_VISIBILITY_RATE: real = 0.5;
+
+const BUSH_VISIBILITY_RATE: real = 0.4;
+
+const RUN_VISIBILITY_RATE: real = 1.25;
+
+const ASSASSINATE_SCORE_BONUS: u32 = 50;
+
+// 僵尸背对玩家的夹角在此范围内可暗杀
+const ASSASSINATE_ANGLE: real = 60.0;
+
+// class
+const ABILITY_COOLDOWN: f64 = 30.0;
+
+const MEDIC_REGEN: real = 1.0;
+
+const MEDIC_HEAL: u32 = 40;
+
+const MEDIC_HEAL_DISTANCE: real = 250.0;
+
+const HEAL_GRENADE_HEAL: u32 = 60;
+
+const DEMOLITIONS_GRENADE_RATE: real = 1.5;
+
+const DEMOLITIONS_AFFINITY_RATE: real = 1.25;
+
+const SCOUT_SPEED_RATE: real = 1.2;
+
+const SCOUT_ASSASSINATE_ANGLE: real = 90.0;
+
+const SCOUT_CLOAK_TIME: f64 = 6.0;
+
+const SCOUT_CLOAK_VISIBILITY_RATE: real = 0.1;
+
+const HEAVY_REPEL: real = 10.0;
+
+const HEAVY_FORTIFY_TIME: f64 = 6.0;
+
+const HEAVY_FORTIFY_RATE: real = 0.5;
+
+// co-op，本地双人加上联机队友
+const MAX_PLAYERS: usize = 4;
+
+const COOP_MAX_DISTANCE: real = 700.0;
+
+const COOP_CAMERA_MARGIN: real = 320.0;
+
+const COOP_MIN_ZOOM: real = 0.5;
+
+const BLEED_OUT_TIME: f64 = 20.0;
+
+const REVIVE_TIME: f64 = 3.0;
+
+const REVIVE_DISTANCE: real = 80.0;
+
+const REVIVE_HEALTH_RATE: real = 0.3;
+
+// network
+const NETWORK_PORT: i32 = 7788;
+
+const NETWORK_DEFAULT_ADDRESS: &str = "127.0.0.1";
+
+// 联机输入每秒发送次数
+const NETWORK_INPUT_RATE: f64 = 30.0;
+
+// aim
+const AIM_STICK_DEADZONE: real = 0.2;
+
+const AIM_MIN_DISTANCE: real = 80.0;
+
+const AIM_MAX_DISTANCE: real = 400.0;
+
+const AIM_ASSIST_ANGLE: real = 12.0;
+
+const AIM_ASSIST_STRENGTH: real = 0.35;
+
+// effect
+const EFFECT_TICK_INTERVAL: f64 = 0.5;
+
+const POISON_DAMAGE: i64 = 2;
+
+const POISON_TIME: f64 = 5.0;
+
+const POISON_MAX_STACKS: u32 = 5;
+
+const BURNING_DAMAGE: i64 = 6;
+
+const BURNING_TIME: f64 = 3.0;
+
+const BLEED_DAMAGE: i64 = 3;
+
+const BLEED_TIME: f64 = 4.0;
+
+const BLEED_MAX_STACKS: u32 = 3;
+
+const SLOW_TIME: f64 = 3.0;
+
+const SLOW_RATE: real = 0.5;
+
+const STUN_TIME: f64 = 1.0;
+
+const VULNERABLE_TIME: f64 = 5.0;
+
+const VULNERABLE_RATE: real = 1.5;
+
+const BLIND_TIME: f64 = 4.0;
+
+const ZOMBIE_BLEED_CHANCE: real = 0.2;
+
 // grenade
 const GRENADE_DAMAGE: i64 = 300;
 
+const GRENADE_COOLDOWN: real = 10.0;
+
 const GRENADE_REPEL: real = 120.0;
 
 const GRENADE_DISTANCE: real = 400.0;
 
 const GRENADE_ALARM_DISTANCE: real = 800.0;
 
+const FLASH_DISTANCE: real = 500.0;
+
+const SMOKE_RADIUS: real = 150.0;
+
+const SMOKE_TIME: f64 = 12.0;
+
+// damage rule
+// 自伤和友伤减半档位的伤害倍率
+const REDUCED_DAMAGE_RATE: real = 0.5;
+
 // weapon
 const WEAPON_FIRE_COOLDOWN: real = 0.1;
 
@@ -112,6 +339,151 @@ const NO_NOISE: Vector2 = Vector2::new(real::MAX, real::MAX);
 
 const GUN_ALARM_DISTANCE: real = 400.0;
 
+// crit
+const WEAPON_CRIT_CHANCE: real = 0.05;
+
+const WEAPON_CRIT_DAMAGE: real = 1.5;
+
+// 每次升级增加的暴击率和暴击伤害倍率
+const CRIT_CHANCE_UPGRADE: real = 0.03;
+
+const CRIT_DAMAGE_UPGRADE: real = 0.25;
+
+// 玩家升级能加到的最大暴击率
+const PLAYER_MAX_CRIT_CHANCE: real = 0.3;
+
+const CRIT_MESSAGE_SCALE: real = 1.4;
+
+// damage number
+// 同一个目标在这段时间内的命中合并成一个数字
+const DAMAGE_NUMBER_MERGE_TIME: real = 0.3;
+
+// 屏幕上同时显示的伤害数字上限，超出时复用最早的
+const DAMAGE_NUMBER_MAX: usize = 32;
+
+const DAMAGE_NUMBER_RISE: real = 50.0;
+
+const DAMAGE_NUMBER_RISE_TIME: real = 0.5;
+
+const DAMAGE_NUMBER_FALL: real = 15.0;
+
+const DAMAGE_NUMBER_FADE_TIME: real = 0.3;
+
+// fire mode
+const BURST_COUNT: u32 = 3;
+
+const BURST_INTERVAL: real = 0.06;
+
+const CHARGE_TIME: real = 0.8;
+
+// 蓄满力时的伤害倍率
+const CHARGE_MAX_DAMAGE_RATE: real = 2.0;
+
+// spin and heat
+// 转管机枪从全速停下的默认时间
+const SPIN_DOWN_TIME: real = 1.0;
+
+// 机枪每秒散去的默认热量，满热量为1
+const HEAT_COOLING: real = 0.35;
+
+// 过热后锁定的默认时间
+const OVERHEAT_LOCKOUT: real = 2.5;
+
+// recoil
+// 每秒恢复的后坐力，按弹道里的发数计
+const RECOIL_RECOVERY: real = 8.0;
+
+// 武器固有的散布，占抖动系数的比例
+const BASE_SPREAD_RATE: real = 0.2;
+
+// 完全恢复后第一发的固有散布比例
+const FIRST_SHOT_SPREAD_RATE: real = 0.25;
+
+const ACCURACY_MOVE_RATE: real = 1.6;
+
+const ACCURACY_SNEAK_RATE: real = 0.7;
+
+// 停止奔跑后一段时间内射击更不准
+const ACCURACY_SPRINT_RATE: real = 2.5;
+
+const ACCURACY_SPRINT_TIME: f64 = 0.5;
+
+// 带倍镜的武器开镜前腰射
+const ACCURACY_HIP_RATE: real = 2.0;
+
+const CROSSHAIR_SCALE: real = 2.0;
+
+// 散布每增加这么多像素，准星放大一倍
+const CROSSHAIR_BLOOM_PIXELS: real = 24.0;
+
+const CROSSHAIR_MAX_BLOOM: real = 3.0;
+
+// ammo
+const AMMO_START_RESERVE: u32 = 30;
+
+// 每到新关卡补充的特殊弹药
+const AMMO_LEVEL_RESERVE: u32 = 30;
+
+const AMMO_MAX_RESERVE: u32 = 150;
+
+const HOLLOW_POINT_DAMAGE_RATE: real = 1.4;
+
+// 爆炸弹药溅射伤害占子弹伤害的比例
+const EXPLOSIVE_AMMO_DAMAGE_RATE: real = 0.5;
+
+const RICOCHET_MAX_BOUNCES: u32 = 2;
+
+const RICOCHET_DISTANCE: real = 300.0;
+
+// 每次弹跳后的伤害比例
+const RICOCHET_DAMAGE_RATE: real = 0.7;
+
+// hit zone
+// 头部、躯干、四肢、弱点的默认伤害倍率，僵尸场景可以覆盖
+const HIT_ZONE_DAMAGE_RATES: [real; HIT_ZONES.len()] = [3.0, 1.0, 0.75, 2.0];
+
+const HIT_ZONE_REPEL_RATES: [real; HIT_ZONES.len()] = [1.5, 1.0, 0.5, 1.0];
+
+// 弹道偏离身体中心超过半径的这个比例就算打中四肢
+const HIT_ZONE_LIMB_OFFSET: real = 0.6;
+
+const HIT_ZONE_WEAK_POINT_RADIUS: real = 14.0;
+
+// hitscan
+// 一次射线检测最多穿过的碰撞体，避免死循环
+const HITSCAN_MAX_HITS: usize = 32;
+
+// 和子弹伤害区域的碰撞遮罩一致
+const HITSCAN_COLLISION_MASK: u32 = 1;
+
+const TRACER_TIME: real = 0.06;
+
+const TRACER_WIDTH: real = 1.5;
+
+// attachment
+const WEAPON_MAX_ATTACHMENTS: usize = 3;
+
+const SUPPRESSOR_DAMAGE_RATE: real = 0.85;
+
+const SUPPRESSOR_DISTANCE_RATE: real = 0.85;
+
+const EXTENDED_MAG_CLIP_RATE: real = 1.5;
+
+// 弹匣变大后换弹也更慢
+const EXTENDED_MAG_RELOAD_RATE: real = 1.2;
+
+const COMPENSATOR_JITTER_RATE: real = 0.5;
+
+const LASER_JITTER_RATE: real = 0.8;
+
+// 没有自带倍镜的武器装上瞄准镜后的开镜倍率
+const SCOPE_ZOOM: real = 0.75;
+
+const HEAVY_BARREL_PENETRATE: real = 1.0;
+
+// 重枪管让持枪移动更慢
+const HEAVY_BARREL_WEIGHT: real = 0.1;
+
 // level
 const LEVEL_GROW_RATE: real = 1.1;
 
@@ -200,6 +572,38 @@ const BOSS_MAX_SCREEN_COUNT: u32 = 6;
 
 const BOSS_REFRESH_BARRIER: u32 = 6;
 
+const BOSS_VULNERABLE_CHANCE: real = 0.5;
+
+// 连击最后一下打出的BOSS硬直
+const BOSS_COMBO_STAGGER_TIME: f64 = 0.4;
+
+// 重击打出的BOSS硬直
+const BOSS_HEAVY_STAGGER_TIME: f64 = 1.0;
+
+// melee
+// 按住近战键超过该时间松开为重击
+const MELEE_HEAVY_CHARGE_TIME: f64 = 0.6;
+
+// 两次挥砍间隔不超过冷却加上该时间才能续上连击
+const MELEE_COMBO_WINDOW: f64 = 0.4;
+
+const MELEE_PARRY_TIME: f64 = 0.3;
+
+const MELEE_PARRY_COOLDOWN: f64 = 1.0;
+
+// 格挡能弹开的僵尸手雷距离
+const MELEE_PARRY_DISTANCE: real = 80.0;
+
+// 弹回的僵尸手雷伤害
+const MELEE_PARRY_DAMAGE: i64 = 200;
+
+const KNIFE_THROW_SPEED: real = 900.0;
+
+const KNIFE_THROW_DISTANCE: real = 450.0;
+
+// 回收的刀飞到该距离内即被接住
+const KNIFE_CATCH_DISTANCE: real = 30.0;
+
 #[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
 #[godot(via = GString)]
 pub enum PlayerState {
@@ -207,15 +611,37 @@ pub enum PlayerState {
     Born,
     Guard,
     Run,
+    Sneak,
     Chop,
     Shoot,
     Reload,
     Reloading,
     Hit,
     Impact,
+    Dodge,
+    Downed,
     Dead,
 }
 
+#[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+#[godot(via = GString)]
+pub enum AimSource {
+    #[default]
+    Mouse,
+    Stick,
+}
+
+#[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+#[godot(via = GString)]
+pub enum NetworkRole {
+    #[default]
+    Offline,
+    // 主机负责所有逻辑运算
+    Host,
+    // 客户端只发送输入，接收同步状态
+    Client,
+}
+
 #[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
 #[godot(via = GString)]
 pub enum WeaponState {
@@ -235,6 +661,384 @@ pub enum PlayerUpgrade {
     Repel,
     Lives,
     Distance,
+    Stamina,
+    Recover,
+    CritChance,
+    CritDamage,
+    Class,
+}
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+pub enum PlayerClass {
+    // 无职业加成，技能恢复体力
+    #[default]
+    Survivor,
+    // 持续回血，携带治疗雷，技能治疗附近队友
+    Medic,
+    // 手雷伤害更高，擅长榴弹和火箭筒，技能刷新手雷冷却
+    Demolitions,
+    // 移动更快，暗杀范围更大，技能短暂隐身
+    Scout,
+    // 击退更强，使用机枪不减速，技能短暂减伤
+    Heavy,
+}
+
+/// 入口界面可选的职业
+pub const PLAYER_CLASSES: [PlayerClass; 5] = [
+    PlayerClass::Survivor,
+    PlayerClass::Medic,
+    PlayerClass::Demolitions,
+    PlayerClass::Scout,
+    PlayerClass::Heavy,
+];
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub enum Achievement {
+    // 击杀普通僵尸
+    ZombieSlayer,
+    // 击杀自爆僵尸
+    BoomerBuster,
+    // 击杀投掷僵尸
+    PitcherHunter,
+    // 击杀BOSS
+    BossHunter,
+    // BOSS出现后到被击杀前没有玩家受伤
+    Untouchable,
+    // 暗杀
+    Assassin,
+    // 地狱模式达到指定关卡
+    HellWalker,
+    // 整关只用刀
+    Blademaster,
+    // 一发AWP子弹穿透多个僵尸
+    Skewer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub enum Perk {
+    // 击杀回血
+    Vampire,
+    // 换弹时震退附近的僵尸
+    Shockwave,
+    // 爆头返还一发子弹
+    Recycler,
+    // 受伤时获得一枚免费手雷
+    Retaliation,
+}
+
+pub const PERKS: [Perk; 4] = [
+    Perk::Vampire,
+    Perk::Shockwave,
+    Perk::Recycler,
+    Perk::Retaliation,
+];
+
+/// 成就界面的显示顺序
+pub const ACHIEVEMENTS: [Achievement; 9] = [
+    Achievement::ZombieSlayer,
+    Achievement::BoomerBuster,
+    Achievement::PitcherHunter,
+    Achievement::BossHunter,
+    Achievement::Untouchable,
+    Achievement::Assassin,
+    Achievement::HellWalker,
+    Achievement::Blademaster,
+    Achievement::Skewer,
+];
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+pub enum StatusEffect {
+    #[default]
+    None,
+    Poison,
+    Burning,
+    Slow,
+    Stun,
+    Bleed,
+    Vulnerable,
+    Blind,
+}
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+pub enum MeleeWeapon {
+    // 均衡，可投掷
+    #[default]
+    Katana,
+    // 出手快，伤害低
+    Machete,
+    // 出手慢，伤害和击退高，几率眩晕
+    Axe,
+    // 极快的连续切割，不能投掷
+    Chainsaw,
+}
+
+/// 切换近战武器的顺序
+pub const MELEE_WEAPONS: [MeleeWeapon; 4] = [
+    MeleeWeapon::Katana,
+    MeleeWeapon::Machete,
+    MeleeWeapon::Axe,
+    MeleeWeapon::Chainsaw,
+];
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+pub enum Attachment {
+    // 消音，牺牲伤害和射程
+    #[default]
+    Suppressor,
+    // 扩容弹匣
+    ExtendedMag,
+    // 枪口补偿器，减少散射
+    Compensator,
+    // 激光指示器
+    Laser,
+    // 瞄准镜
+    Scope,
+    // 重枪管，提高穿透
+    HeavyBarrel,
+}
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+#[serde(rename_all = "snake_case")]
+pub enum FireMode {
+    // 每次扣扳机只打一发
+    Semi,
+    // 每次扣扳机打一串
+    Burst,
+    // 按住连发
+    #[default]
+    Auto,
+    // 按住蓄力，松开时打出
+    Charge,
+}
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+pub enum AmmoType {
+    // 普通弹药，不限量
+    #[default]
+    Standard,
+    // 燃烧弹，命中时点燃
+    Incendiary,
+    // 穿甲弹，穿透BOSS不消耗穿透
+    ArmorPiercing,
+    // 空尖弹，伤害更高但不能穿透
+    HollowPoint,
+    // 爆炸弹，命中时小范围爆炸
+    Explosive,
+    // 跳弹，命中后弹向附近的僵尸
+    Ricochet,
+}
+
+/// 切换弹药的顺序，与枚举顺序一致
+pub const AMMO_TYPES: [AmmoType; 6] = [
+    AmmoType::Standard,
+    AmmoType::Incendiary,
+    AmmoType::ArmorPiercing,
+    AmmoType::HollowPoint,
+    AmmoType::Explosive,
+    AmmoType::Ricochet,
+];
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+#[serde(rename_all = "snake_case")]
+pub enum HitZone {
+    Head,
+    #[default]
+    Torso,
+    // 打中四肢会减速
+    Limb,
+    // BOSS背后的弱点
+    WeakPoint,
+}
+
+/// 与枚举顺序一致，也是僵尸场景里倍率数组的顺序
+pub const HIT_ZONES: [HitZone; 4] = [
+    HitZone::Head,
+    HitZone::Torso,
+    HitZone::Limb,
+    HitZone::WeakPoint,
+];
+
+/// 配件按解锁顺序排列
+pub const ATTACHMENTS: [Attachment; 6] = [
+    Attachment::Suppressor,
+    Attachment::Compensator,
+    Attachment::ExtendedMag,
+    Attachment::Laser,
+    Attachment::HeavyBarrel,
+    Attachment::Scope,
+];
+
+#[derive(
+    GodotConvert, Var, Export, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone,
+)]
+#[godot(via = GString)]
+pub enum CoopLives {
+    // 共用1P的生命数
+    #[default]
+    Shared,
+    // 各自独立计算
+    Split,
+}
+
+#[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+#[godot(via = GString)]
+pub enum Team {
+    #[default]
+    Survivor,
+    Zombie,
+}
+
+#[derive(
+    GodotConvert,
+    Var,
+    Export,
+    Serialize,
+    Deserialize,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Copy,
+    Clone,
+)]
+#[godot(via = GString)]
+#[serde(rename_all = "snake_case")]
+pub enum FriendlyFire {
+    Off,
+    // 伤害减半
+    #[default]
+    Reduced,
+    Full,
+}
+
+pub const FRIENDLY_FIRE_OPTIONS: [FriendlyFire; 3] =
+    [FriendlyFire::Off, FriendlyFire::Reduced, FriendlyFire::Full];
+
+#[derive(
+    GodotConvert, Var, Export, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone,
+)]
+#[godot(via = GString)]
+pub enum GrenadeType {
+    #[default]
+    Frag,
+    Flash,
+    Smoke,
+    Heal,
 }
 
 #[derive(GodotConvert, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
@@ -269,6 +1073,10 @@ pub fn random_bool() -> bool {
     rand::thread_rng().gen_range(-1.0..=1.0) >= 0.0
 }
 
+pub fn random_chance(chance: real) -> bool {
+    chance > 0.0 && rand::thread_rng().gen_range(0.0..1.0) < chance
+}
+
 pub fn random_degree() -> real {
     rand::thread_rng().gen_range(0.0..360.0)
 }
@@ -321,6 +1129,11 @@ pub fn is_zombie<T: Deref<Target = Object>>(gd: &T) -> bool {
     not_boss(gd) || is_boss(gd)
 }
 
+pub fn is_alive<T: Deref<Target = Node>>(gd: &T) -> bool {
+    // 死亡时会释放碰撞体
+    gd.has_node("CollisionShape2D")
+}
+
 pub fn normal_zombie<T: Deref<Target = Object>>(gd: &T) -> bool {
     gd.is_class("RustZombie")
 }
@@ -0,0 +1,81 @@
+use crate::LOADOUT_PATH;
+use crate::weapon::definition::WeaponDefinition;
+use dashmap::DashMap;
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// 玩家调整过的武器顺序和收藏，第一次使用时从文件读取
+static SLOTS: LazyLock<DashMap<String, WeaponSlot>> = LazyLock::new(|| {
+    let slots = DashMap::new();
+    if let Some(file) = FileAccess::open(LOADOUT_PATH, ModeFlags::READ) {
+        let data = file.get_as_text().to_string();
+        if let Ok(saved) = serde_json::from_str::<HashMap<String, WeaponSlot>>(&data) {
+            for (weapon_name, slot) in saved {
+                slots.insert(weapon_name, slot);
+            }
+        }
+    }
+    slots
+});
+
+/// 武器在切换顺序里的位置，没调整过的武器按定义顺序排列
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+#[serde(default)]
+pub struct WeaponSlot {
+    pub order: u32,
+    // 收藏的武器组成切换循环
+    pub favorite: bool,
+}
+
+impl WeaponSlot {
+    pub fn get(weapon_name: &str) -> Self {
+        if let Some(slot) = SLOTS.get(weapon_name) {
+            return *slot;
+        }
+        let order = WeaponDefinition::all()
+            .iter()
+            .position(|definition| definition.name.to_uppercase() == weapon_name)
+            .map_or(u32::MAX, |position| position as u32);
+        Self {
+            order,
+            favorite: false,
+        }
+    }
+
+    /// 交换两把武器的顺序，前一把武器原本排在后一把前面
+    pub fn swap(weapon_name: &str, other_name: &str) {
+        let mut slot = Self::get(weapon_name);
+        let mut other = Self::get(other_name);
+        if slot.order == other.order {
+            // 自制武器没有定义顺序，先拉开再交换
+            slot.order = slot.order.saturating_sub(1);
+        }
+        std::mem::swap(&mut slot.order, &mut other.order);
+        SLOTS.insert(weapon_name.to_string(), slot);
+        SLOTS.insert(other_name.to_string(), other);
+        Self::save();
+    }
+
+    /// 切换收藏，返回切换后是否收藏
+    pub fn toggle_favorite(weapon_name: &str) -> bool {
+        let mut slot = Self::get(weapon_name);
+        slot.favorite = !slot.favorite;
+        SLOTS.insert(weapon_name.to_string(), slot);
+        Self::save();
+        slot.favorite
+    }
+
+    fn save() {
+        let slots: HashMap<String, WeaponSlot> = SLOTS
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        DirAccess::make_dir_recursive_absolute("user://data");
+        if let Some(mut file) = FileAccess::open(LOADOUT_PATH, ModeFlags::WRITE) {
+            file.store_string(&serde_json::to_string_pretty(&slots).unwrap());
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::entrance::RustEntrance;
+use crate::network::RustNetwork;
+use crate::{NETWORK_DEFAULT_ADDRESS, NETWORK_PORT};
+use godot::classes::{Button, Control, IControl, Label, LineEdit, Os};
+use godot::obj::{Base, Gd, OnReady, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// 命令行参数只在第一次打开入口时生效，避免断线回到入口后反复重连
+static CMDLINE_HANDLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct RustLobby {
+    address: OnReady<Gd<LineEdit>>,
+    message: OnReady<Gd<Label>>,
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for RustLobby {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            address: OnReady::from_node("VBoxContainer/Address"),
+            message: OnReady::from_node("VBoxContainer/Message"),
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let gd = self.to_gd();
+        let container = self.base().get_node_as::<Control>("VBoxContainer");
+        container
+            .get_node_as::<Button>("HBoxContainer/Host")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_host_pressed);
+        container
+            .get_node_as::<Button>("HBoxContainer/Join")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_join_pressed);
+        container
+            .get_node_as::<Button>("HBoxContainer/Back")
+            .signals()
+            .pressed()
+            .connect_obj(&gd, Self::on_back_pressed);
+        self.address.set_text(NETWORK_DEFAULT_ADDRESS);
+        if let Some(mut multiplayer) = self.base().get_multiplayer() {
+            multiplayer.connect("connected_to_server", &self.base().callable("on_connected"));
+            multiplayer.connect(
+                "connection_failed",
+                &self.base().callable("on_connection_failed"),
+            );
+        }
+        if CMDLINE_HANDLED.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // 支持命令行直接联机，方便本机测试：-- --host 或 -- --join=127.0.0.1
+        for arg in Os::singleton().get_cmdline_user_args().as_slice() {
+            let arg = arg.to_string();
+            if "--host" == arg {
+                self.base_mut().call_deferred("on_host_pressed", &[]);
+            } else if let Some(address) = arg.strip_prefix("--join") {
+                let address = address.trim_start_matches('=');
+                if !address.is_empty() {
+                    self.address.set_text(address);
+                }
+                self.base_mut().call_deferred("on_join_pressed", &[]);
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl RustLobby {
+    #[func]
+    pub fn on_host_pressed(&mut self) {
+        if !RustNetwork::host(NETWORK_PORT) {
+            self.show_message(&format!("FAILED TO HOST ON PORT {}", NETWORK_PORT));
+            return;
+        }
+        self.start();
+    }
+
+    #[func]
+    pub fn on_join_pressed(&mut self) {
+        let address = self.address.get_text().to_string();
+        let address = if address.trim().is_empty() {
+            NETWORK_DEFAULT_ADDRESS.to_string()
+        } else {
+            address.trim().to_string()
+        };
+        if !RustNetwork::join(&address, NETWORK_PORT) {
+            self.show_message(&format!("FAILED TO JOIN {}", address));
+            return;
+        }
+        self.show_message(&format!("CONNECTING TO {}", address));
+    }
+
+    #[func]
+    pub fn on_connected(&mut self) {
+        self.start();
+    }
+
+    #[func]
+    pub fn on_connection_failed(&mut self) {
+        RustNetwork::close();
+        self.show_message("CONNECTION FAILED");
+    }
+
+    #[func]
+    pub fn on_back_pressed(&mut self) {
+        RustNetwork::close();
+        self.show_message("");
+        self.base_mut().set_visible(false);
+        self.base()
+            .get_parent()
+            .expect("RustLobby parent not found")
+            .get_node_as::<Control>("VBoxContainer")
+            .set_visible(true);
+    }
+
+    // 主机建好或客户端连上后进入游戏世界
+    fn start(&mut self) {
+        self.show_message("");
+        self.base_mut().set_visible(false);
+        self.base()
+            .get_parent()
+            .expect("RustLobby parent not found")
+            .cast::<RustEntrance>()
+            .call_deferred("start_online", &[]);
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.message.set_text(message);
+        self.message.show();
+    }
+}
@@ -0,0 +1,182 @@
+use crate::weapon::definition::WeaponStats;
+use crate::{
+    HIT_ZONES, HitZone, MASTERY_JITTER_RATE, MASTERY_PATH, MASTERY_PENETRATE, MASTERY_RELOAD_RATE,
+    MASTERY_TIER_KILLS,
+};
+use dashmap::{DashMap, DashSet};
+use godot::builtin::{Color, real};
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 各武器的熟练度，第一次使用时从档案文件读取
+static PROGRESS: LazyLock<DashMap<String, WeaponMastery>> = LazyLock::new(|| {
+    let progress = DashMap::new();
+    if let Some(file) = FileAccess::open(MASTERY_PATH, ModeFlags::READ) {
+        let data = file.get_as_text().to_string();
+        if let Ok(saved) = serde_json::from_str::<HashMap<String, WeaponMastery>>(&data) {
+            for (weapon_name, mastery) in saved {
+                progress.insert(weapon_name, mastery);
+            }
+        }
+    }
+    progress
+});
+
+// 刚升级、还没提示过的武器
+static TIER_UPS: LazyLock<DashSet<String>> = LazyLock::new(DashSet::new);
+
+// 有武器升级时加一，玩家据此重新计算属性
+static VERSION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct WeaponMastery {
+    pub kills: u32,
+    pub damage: u64,
+    // 按部位统计的命中次数
+    pub zone_hits: [u32; HIT_ZONES.len()],
+}
+
+impl WeaponMastery {
+    pub fn get(weapon_name: &str) -> Self {
+        PROGRESS
+            .get(weapon_name)
+            .map(|mastery| *mastery)
+            .unwrap_or_default()
+    }
+
+    pub fn get_tier(&self) -> u32 {
+        MASTERY_TIER_KILLS
+            .iter()
+            .filter(|kills| self.kills >= **kills)
+            .count() as u32
+    }
+
+    pub fn is_max_tier(&self) -> bool {
+        self.get_tier() as usize >= MASTERY_TIER_KILLS.len()
+    }
+
+    /// 升到下一级需要的击杀数，满级时为None
+    pub fn get_next_kills(&self) -> Option<u32> {
+        MASTERY_TIER_KILLS.get(self.get_tier() as usize).copied()
+    }
+
+    /// 按等级叠加属性加成
+    pub fn apply(&self, stats: &mut WeaponStats) {
+        let tier = self.get_tier();
+        if tier >= 1 {
+            stats.reload_time *= MASTERY_RELOAD_RATE;
+        }
+        if tier >= 2 {
+            stats.jitter *= MASTERY_JITTER_RATE;
+        }
+        if tier >= 3 {
+            stats.penetrate += MASTERY_PENETRATE;
+        }
+    }
+
+    /// 每个等级对应的外观
+    pub fn get_variant(tier: u32) -> (&'static str, Color) {
+        match tier {
+            0 => ("", Color::WHITE),
+            1 => ("BRONZE", Color::from_rgb(0.85, 0.55, 0.3)),
+            2 => ("SILVER", Color::from_rgb(0.8, 0.85, 0.95)),
+            3 => ("GOLD", Color::from_rgb(1.0, 0.84, 0.2)),
+            _ => ("CRIMSON", Color::from_rgb(1.0, 0.25, 0.3)),
+        }
+    }
+
+    pub fn get_tier_bonus(tier: u32) -> String {
+        match tier {
+            1 => format!("RELOAD -{:.0}%", (1.0 - MASTERY_RELOAD_RATE) * 100.0),
+            2 => format!("JITTER -{:.0}%", (1.0 - MASTERY_JITTER_RATE) * 100.0),
+            3 => format!("PENETRATE +{:.1}", MASTERY_PENETRATE),
+            _ => String::from("CRIMSON FINISH"),
+        }
+    }
+
+    /// 爆头占命中次数的比例
+    pub fn get_headshot_rate(&self) -> real {
+        let hits: u32 = self.zone_hits.iter().sum();
+        if 0 == hits {
+            return 0.0;
+        }
+        self.zone_hits[HitZone::Head as usize] as real / hits as real
+    }
+
+    /// HUD上显示的熟练度进度
+    pub fn get_progress_text(&self) -> String {
+        let progress = match self.get_next_kills() {
+            Some(next_kills) => {
+                format!("MASTERY {} {}/{}", self.get_tier(), self.kills, next_kills)
+            }
+            None => format!("MASTERY {} MAX", self.get_tier()),
+        };
+        if self.zone_hits.iter().all(|hits| 0 == *hits) {
+            return progress;
+        }
+        format!("{} HS {:.0}%", progress, self.get_headshot_rate() * 100.0)
+    }
+
+    /// 武器命中僵尸时记录伤害
+    pub fn record_damage(weapon_name: &str, damage: i64) {
+        if weapon_name.is_empty() || damage <= 0 {
+            return;
+        }
+        PROGRESS.entry(weapon_name.to_string()).or_default().damage += damage as u64;
+    }
+
+    /// 子弹命中时按部位计数
+    pub fn record_zone_hit(weapon_name: &str, zone: HitZone) {
+        if weapon_name.is_empty() {
+            return;
+        }
+        let mut mastery = PROGRESS.entry(weapon_name.to_string()).or_default();
+        mastery.zone_hits[zone as usize] = mastery.zone_hits[zone as usize].saturating_add(1);
+    }
+
+    /// 僵尸死亡时记录击杀，最后一击不是枪械时不记
+    pub fn on_kill(weapon_name: &str) {
+        if weapon_name.is_empty() {
+            return;
+        }
+        let mut mastery = PROGRESS.entry(weapon_name.to_string()).or_default();
+        let tier = mastery.get_tier();
+        mastery.kills = mastery.kills.saturating_add(1);
+        let tier_up = mastery.get_tier() > tier;
+        drop(mastery);
+        if tier_up {
+            TIER_UPS.insert(weapon_name.to_string());
+            Self::save_profile();
+            VERSION.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    /// 取出刚升级的武器，由玩家弹出提示
+    pub fn take_tier_ups() -> Vec<String> {
+        let weapon_names: Vec<String> = TIER_UPS.iter().map(|name| name.clone()).collect();
+        for weapon_name in &weapon_names {
+            TIER_UPS.remove(weapon_name);
+        }
+        weapon_names
+    }
+
+    pub fn get_version() -> u64 {
+        VERSION.load(Ordering::Acquire)
+    }
+
+    pub fn save_profile() {
+        let progress: HashMap<String, WeaponMastery> = PROGRESS
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect();
+        DirAccess::make_dir_recursive_absolute("user://data");
+        if let Some(mut file) = FileAccess::open(MASTERY_PATH, ModeFlags::WRITE) {
+            file.store_string(&serde_json::to_string_pretty(&progress).unwrap());
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use crate::world::RustWorld;
+use crate::{MAX_PLAYERS, NetworkRole, PlayerClass};
+use crossbeam_utils::atomic::AtomicCell;
+use dashmap::DashSet;
+use godot::builtin::NodePath;
+use godot::classes::scene_replication_config::ReplicationMode;
+use godot::classes::{
+    ENetMultiplayerPeer, Engine, INode, MultiplayerApi, MultiplayerSpawner,
+    MultiplayerSynchronizer, Node, SceneReplicationConfig, SceneTree,
+};
+use godot::global::{Error, godot_warn};
+use godot::meta::ToGodot;
+use godot::obj::{Base, Gd, NewAlloc, NewGd, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ROLE: AtomicCell<NetworkRole> = AtomicCell::new(NetworkRole::Offline);
+
+// 已经进入游戏世界的客户端，只向它们同步状态
+static READY_PEERS: LazyLock<DashSet<i32>> = LazyLock::new(DashSet::new);
+
+// 联机时生成的节点需要唯一且可读的名字
+static SPAWN_COUNT: AtomicU64 = AtomicU64::new(0);
+
+const SYNC_GROUP: &str = "replicated";
+
+/// 主机添加到RustWorld下、需要同步到客户端的场景
+const SPAWNABLE_SCENES: [&str; 18] = [
+    "res://scenes/rust_player.tscn",
+    "res://scenes/zombies/rust_zombie.tscn",
+    "res://scenes/zombies/rust_rusher.tscn",
+    "res://scenes/zombies/rust_boomer.tscn",
+    "res://scenes/zombies/rust_pitcher.tscn",
+    "res://scenes/zombies/rust_boss.tscn",
+    "res://scenes/bullets/rust_bullet.tscn",
+    "res://scenes/bullets/rust_big_bullet.tscn",
+    "res://scenes/bullets/rust_grenade_bullet.tscn",
+    "res://scenes/bullets/rust_hegrenade_bullet.tscn",
+    "res://scenes/bullets/rust_rocket_bullet.tscn",
+    "res://scenes/bullets/rust_explosive_round.tscn",
+    "res://scenes/grenades/fgrenade.tscn",
+    "res://scenes/grenades/flashbang.tscn",
+    "res://scenes/grenades/smoke_grenade.tscn",
+    "res://scenes/grenades/heal_grenade.tscn",
+    "res://scenes/grenades/zombie_grenade.tscn",
+    "res://scenes/rust_perk_drop.tscn",
+];
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct RustNetwork {
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for RustNetwork {
+    fn init(base: Base<Node>) -> Self {
+        Self { base }
+    }
+
+    fn ready(&mut self) {
+        if !Self::is_online() {
+            return;
+        }
+        let mut spawner = MultiplayerSpawner::new_alloc();
+        spawner.set_name("MultiplayerSpawner");
+        // 生成到RustWorld下
+        spawner.set_spawn_path(&NodePath::from("../.."));
+        for scene in SPAWNABLE_SCENES {
+            spawner.add_spawnable_scene(scene);
+        }
+        self.base_mut().add_child(&spawner);
+        let Some(mut multiplayer) = Self::get_multiplayer() else {
+            return;
+        };
+        if Self::is_client() {
+            multiplayer.connect(
+                "server_disconnected",
+                &self.base().callable("on_server_disconnected"),
+            );
+            // 客户端的世界加载完成，通知主机开始同步
+            self.base_mut().rpc_id(
+                1,
+                "client_ready",
+                &[RustWorld::get_player_class(0).to_variant()],
+            );
+        } else {
+            multiplayer.connect(
+                "peer_disconnected",
+                &self.base().callable("on_peer_disconnected"),
+            );
+        }
+    }
+
+    fn exit_tree(&mut self) {
+        // 离开游戏世界时断开连接
+        Self::close();
+    }
+}
+
+#[godot_api]
+impl RustNetwork {
+    #[rpc(any_peer, call_remote, reliable)]
+    pub fn client_ready(&mut self, player_class: PlayerClass) {
+        if !Self::is_host() {
+            return;
+        }
+        let Some(multiplayer) = Self::get_multiplayer() else {
+            return;
+        };
+        let peer_id = multiplayer.get_remote_sender_id();
+        READY_PEERS.insert(peer_id);
+        if let Some(tree) = self.base().get_tree() {
+            for node in tree.get_nodes_in_group(SYNC_GROUP).iter_shared() {
+                node.cast::<MultiplayerSynchronizer>()
+                    .set_visibility_for(peer_id, true);
+            }
+        }
+        self.get_world()
+            .bind_mut()
+            .join_remote_player(peer_id, player_class);
+    }
+
+    #[func]
+    pub fn on_peer_disconnected(&mut self, peer_id: i64) {
+        let peer_id = peer_id as i32;
+        READY_PEERS.remove(&peer_id);
+        self.get_world().bind_mut().leave_remote_player(peer_id);
+    }
+
+    #[func]
+    pub fn on_server_disconnected(&mut self) {
+        godot_warn!("Disconnected from host");
+        self.get_world().call_deferred("on_exit_pressed", &[]);
+    }
+
+    fn get_world(&self) -> Gd<RustWorld> {
+        self.base()
+            .get_parent()
+            .expect("RustWorld not found")
+            .cast::<RustWorld>()
+    }
+
+    pub fn host(port: i32) -> bool {
+        let mut peer = ENetMultiplayerPeer::new_gd();
+        let error = peer
+            .create_server_ex(port)
+            .max_clients(MAX_PLAYERS as i32 - 1)
+            .done();
+        if Error::OK != error {
+            godot_warn!("Failed to host on port {}: {:?}", port, error);
+            return false;
+        }
+        Self::start(peer, NetworkRole::Host)
+    }
+
+    pub fn join(address: &str, port: i32) -> bool {
+        let mut peer = ENetMultiplayerPeer::new_gd();
+        let error = peer.create_client(address, port);
+        if Error::OK != error {
+            godot_warn!("Failed to join {}:{}: {:?}", address, port, error);
+            return false;
+        }
+        Self::start(peer, NetworkRole::Client)
+    }
+
+    fn start(peer: Gd<ENetMultiplayerPeer>, role: NetworkRole) -> bool {
+        let Some(mut multiplayer) = Self::get_multiplayer() else {
+            return false;
+        };
+        multiplayer.set_multiplayer_peer(&peer);
+        READY_PEERS.clear();
+        ROLE.store(role);
+        true
+    }
+
+    pub fn close() {
+        if !Self::is_online() {
+            return;
+        }
+        if let Some(mut multiplayer) = Self::get_multiplayer() {
+            if let Some(mut peer) = multiplayer.get_multiplayer_peer() {
+                peer.close();
+            }
+            multiplayer.set_multiplayer_peer(Gd::null_arg());
+        }
+        READY_PEERS.clear();
+        ROLE.store(NetworkRole::Offline);
+    }
+
+    /// 主机上限满员时踢出多余的客户端
+    pub fn kick(peer_id: i32) {
+        if let Some(multiplayer) = Self::get_multiplayer() {
+            if let Some(peer) = multiplayer.get_multiplayer_peer() {
+                peer.cast::<ENetMultiplayerPeer>().disconnect_peer(peer_id);
+            }
+        }
+    }
+
+    /// 给节点添加同步器，以`.:`开头的变换属性每帧同步，其他属性变化时同步
+    pub fn replicate(node: &mut Gd<Node>, properties: &[&str]) {
+        if !Self::is_online() {
+            return;
+        }
+        let mut config = SceneReplicationConfig::new_gd();
+        for property in properties {
+            let path = NodePath::from(*property);
+            config.add_property(&path);
+            config.property_set_spawn(&path, true);
+            config.property_set_replication_mode(
+                &path,
+                if property.starts_with(".:") {
+                    ReplicationMode::ALWAYS
+                } else {
+                    ReplicationMode::ON_CHANGE
+                },
+            );
+        }
+        let mut synchronizer = MultiplayerSynchronizer::new_alloc();
+        synchronizer.set_name("MultiplayerSynchronizer");
+        synchronizer.set_replication_config(&config);
+        // 客户端加载好世界后才对其可见
+        synchronizer.set_visibility_public(false);
+        for peer_id in READY_PEERS.iter() {
+            synchronizer.set_visibility_for(*peer_id, true);
+        }
+        synchronizer.add_to_group(SYNC_GROUP);
+        node.add_child(&synchronizer);
+    }
+
+    /// 同步生成的节点不能使用自动生成的名字
+    pub fn name_spawned(node: &mut Gd<Node>) {
+        if !Self::is_host() {
+            return;
+        }
+        let count = SPAWN_COUNT.fetch_add(1, Ordering::AcqRel);
+        let name = format!("{}{}", node.get_name(), count);
+        node.set_name(&name);
+    }
+
+    pub fn get_role() -> NetworkRole {
+        ROLE.load()
+    }
+
+    pub fn is_online() -> bool {
+        NetworkRole::Offline != Self::get_role()
+    }
+
+    pub fn is_host() -> bool {
+        NetworkRole::Host == Self::get_role()
+    }
+
+    pub fn is_client() -> bool {
+        NetworkRole::Client == Self::get_role()
+    }
+
+    /// 单机和主机负责游戏逻辑，客户端只展示同步结果
+    pub fn is_authority() -> bool {
+        !Self::is_client()
+    }
+
+    pub fn get_local_peer_id() -> i32 {
+        if !Self::is_online() {
+            return 1;
+        }
+        Self::get_multiplayer()
+            .map(|multiplayer| multiplayer.get_unique_id())
+            .unwrap_or(1)
+    }
+
+    pub fn get_sender_id() -> i32 {
+        Self::get_multiplayer()
+            .map(|multiplayer| multiplayer.get_remote_sender_id())
+            .unwrap_or(0)
+    }
+
+    fn get_multiplayer() -> Option<Gd<MultiplayerApi>> {
+        Engine::singleton()
+            .get_main_loop()?
+            .cast::<SceneTree>()
+            .get_multiplayer()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::network::RustNetwork;
+use crate::player::RustPlayer;
+use crate::world::RustWorld;
+use crate::{PERK_DROP_LIFETIME, PERK_KILL_HEAL, Perk, is_survivor, random_chance};
+use godot::builtin::{Vector2, real};
+use godot::classes::{Area2D, IArea2D, Node, Node2D, PackedScene};
+use godot::meta::ToGodot;
+use godot::obj::{Base, Gd, WithBaseField};
+use godot::register::{GodotClass, godot_api};
+use godot::tools::load;
+use std::sync::LazyLock;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const PERK_DROP: LazyLock<Gd<PackedScene>> =
+    LazyLock::new(|| load("res://scenes/rust_perk_drop.tscn"));
+
+impl Perk {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            Perk::Vampire => "VAMPIRE",
+            Perk::Shockwave => "SHOCKWAVE",
+            Perk::Recycler => "RECYCLER",
+            Perk::Retaliation => "RETALIATION",
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            Perk::Vampire => format!("KILLS HEAL {} HP", PERK_KILL_HEAL),
+            Perk::Shockwave => String::from("RELOADING PUSHES NEARBY ZOMBIES BACK"),
+            Perk::Recycler => String::from("HEADSHOTS REFUND ONE BULLET"),
+            Perk::Retaliation => String::from("TAKING DAMAGE GIVES A FREE GRENADE"),
+        }
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Area2D)]
+pub struct RustPerkDrop {
+    base: Base<Area2D>,
+}
+
+#[godot_api]
+impl IArea2D for RustPerkDrop {
+    fn init(base: Base<Area2D>) -> Self {
+        Self { base }
+    }
+
+    fn ready(&mut self) {
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &[".:position"]);
+        let gd = self.to_gd();
+        gd.clone()
+            .upcast::<Area2D>()
+            .signals()
+            .body_entered()
+            .connect_obj(&gd, Self::on_body_entered);
+        if let Some(mut tree) = self.base().get_tree() {
+            if let Some(mut timer) = tree.create_timer(PERK_DROP_LIFETIME) {
+                timer.connect("timeout", &self.base().callable("queue_free"));
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl RustPerkDrop {
+    #[func]
+    pub fn on_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() || !is_survivor(&***body) {
+            return;
+        }
+        // 已经集齐所有天赋的玩家不会捡走掉落
+        if body.cast::<RustPlayer>().bind_mut().gain_random_perk() {
+            self.base_mut().queue_free();
+        }
+    }
+
+    /// 僵尸死亡时按概率掉落天赋
+    pub fn try_drop(position: Vector2, chance: real) {
+        if !RustNetwork::is_authority() || !random_chance(chance) {
+            return;
+        }
+        #[allow(clippy::borrow_interior_mutable_const)]
+        if let Some(mut perk_drop) = PERK_DROP.try_instantiate_as::<Self>() {
+            perk_drop.set_global_position(position);
+            RustNetwork::name_spawned(&mut perk_drop.clone().upcast());
+            RustWorld::get().call_deferred("add_child", &[perk_drop.to_variant()]);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+use crate::{
+    ACCURACY_HIP_RATE, ACCURACY_MOVE_RATE, ACCURACY_SNEAK_RATE, ACCURACY_SPRINT_RATE,
+    AIM_ASSIST_ANGLE, AIM_ASSIST_STRENGTH, AIM_MAX_DISTANCE, AIM_MIN_DISTANCE, AIM_STICK_DEADZONE,
+    AimSource, MAX_PLAYERS, is_alive,
+};
+
+static AIM_SOURCES: [AtomicCell<AimSource>; MAX_PLAYERS] =
+    [const { AtomicCell::new(AimSource::Mouse) }; MAX_PLAYERS];
+
+static AIM_POSITIONS: [AtomicCell<Vector2>; MAX_PLAYERS] =
+    [const { AtomicCell::new(Vector2::ZERO) }; MAX_PLAYERS];
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn update_aim(&mut self) {
+        if self.is_remote() {
+            // 客户端玩家的瞄准点由客户端上报
+            AIM_POSITIONS[self.player_index].store(self.remote_aim);
+            return;
+        }
+        let stick = self.get_action_vector("aim_left", "aim_right", "aim_up", "aim_down");
+        let index = self.player_index;
+        if stick.length() > AIM_STICK_DEADZONE {
+            AIM_SOURCES[index].store(AimSource::Stick);
+            self.stick_aim = stick.limit_length(Some(1.0));
+        } else if self.is_local_teammate() {
+            // 2P没有鼠标，用键盘时朝移动方向瞄准
+            AIM_SOURCES[index].store(AimSource::Stick);
+            let move_direction =
+                self.get_action_vector("move_left", "move_right", "move_up", "move_down");
+            if Vector2::ZERO != move_direction {
+                self.stick_aim = move_direction.normalized() * AIM_STICK_DEADZONE;
+            }
+        }
+        let aim_position = match AIM_SOURCES[index].load() {
+            AimSource::Mouse => self.get_mouse_position(),
+            AimSource::Stick => self.get_stick_position(),
+        };
+        AIM_POSITIONS[index].store(aim_position);
+    }
+
+    // 摇杆推得越远，瞄准点离玩家越远
+    fn get_stick_position(&self) -> Vector2 {
+        let player_position = self.base().get_global_position();
+        if Vector2::ZERO == self.stick_aim {
+            return player_position + self.get_current_direction() * AIM_MIN_DISTANCE;
+        }
+        let strength = ((self.stick_aim.length() - AIM_STICK_DEADZONE)
+            / (1.0 - AIM_STICK_DEADZONE))
+            .clamp(0.0, 1.0);
+        let distance = AIM_MIN_DISTANCE + (AIM_MAX_DISTANCE - AIM_MIN_DISTANCE) * strength;
+        let mut direction = self.stick_aim.normalized();
+        if self.aim_assist {
+            if let Some(target) = self.find_assist_target(direction) {
+                direction = direction
+                    .lerp(player_position.direction_to(target), AIM_ASSIST_STRENGTH)
+                    .normalized();
+            }
+        }
+        player_position + direction * distance
+    }
+
+    // 辅助瞄准，吸附到锥形范围内最近的僵尸
+    fn find_assist_target(&self, direction: Vector2) -> Option<Vector2> {
+        let player_position = self.base().get_global_position();
+        let mut nearest: Option<(real, Vector2)> = None;
+        for node in self
+            .base()
+            .get_tree()?
+            .get_nodes_in_group("zombie")
+            .iter_shared()
+        {
+            if !node.is_instance_valid() || !is_alive(&node) {
+                continue;
+            }
+            let zombie_position = node.cast::<Node2D>().get_global_position();
+            let distance = player_position.distance_to(zombie_position);
+            if distance > AIM_MAX_DISTANCE {
+                continue;
+            }
+            let angle = direction
+                .angle_to(player_position.direction_to(zombie_position))
+                .to_degrees()
+                .abs();
+            if angle > AIM_ASSIST_ANGLE {
+                continue;
+            }
+            if nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance) {
+                nearest = Some((distance, zombie_position));
+            }
+        }
+        nearest.map(|(_, position)| position)
+    }
+
+    /// 移动、刚停止奔跑和带倍镜武器开镜前射击更不准，结果交给当前武器
+    pub fn update_accuracy(&mut self, delta: f64) {
+        self.left_sprint_time = (self.left_sprint_time - delta).max(0.0);
+        let mut accuracy_rate = if self.sneaking {
+            ACCURACY_SNEAK_RATE
+        } else if self.is_moving() {
+            ACCURACY_MOVE_RATE
+        } else {
+            1.0
+        };
+        if self.left_sprint_time > 0.0 {
+            accuracy_rate *= ACCURACY_SPRINT_RATE;
+        }
+        let mut weapon = self.get_current_weapon();
+        if weapon.bind().get_zoom() < 1.0 && self.scope_zoom.x >= 1.0 {
+            accuracy_rate *= ACCURACY_HIP_RATE;
+        }
+        weapon.bind_mut().set_accuracy_rate(accuracy_rate);
+    }
+
+    // 本地2P没有鼠标，联机时每台机器的玩家各自使用鼠标
+    pub fn use_mouse_aim(&self) {
+        AIM_SOURCES[self.player_index].store(AimSource::Mouse);
+    }
+
+    pub fn get_aim_source(index: usize) -> AimSource {
+        AIM_SOURCES[index].load()
+    }
+
+    pub fn get_aim_position(index: usize) -> Vector2 {
+        AIM_POSITIONS[index].load()
+    }
+}
@@ -0,0 +1,37 @@
+use super::*;
+use crate::AMMO_LEVEL_RESERVE;
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    /// 每到新关卡给所有玩家补充特殊弹药，读档时不补充
+    pub fn unlock_level_ammo(&mut self, level: u32) {
+        let previous_level = self.ammo_level;
+        if level <= previous_level {
+            return;
+        }
+        self.ammo_level = level;
+        if 0 == previous_level {
+            return;
+        }
+        AmmoType::resupply(AMMO_LEVEL_RESERVE);
+        self.get_current_weapon().bind().update_ammo_type_hud();
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(&format!(
+                "SPECIAL AMMO +{}, PRESS N TO SWITCH",
+                AMMO_LEVEL_RESERVE
+            ));
+        }
+    }
+
+    pub fn next_ammo_type(&mut self) {
+        let mut weapon = self.get_current_weapon();
+        let result = weapon.bind_mut().next_ammo_type();
+        let message = match result {
+            Ok(ammo_type) => format!("{}: {}", ammo_type.get_title(), ammo_type.get_description()),
+            Err(message) => message,
+        };
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(&message);
+        }
+    }
+}
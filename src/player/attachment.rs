@@ -0,0 +1,162 @@
+use super::*;
+use crate::loadout::WeaponSlot;
+use crate::{ATTACHMENTS, Attachment};
+use godot::builtin::Color;
+
+// 激光指示器配件让瞄准线更亮更粗
+const LASER_COLOR: Color = Color::from_rgba(0.705882, 0.0, 0.0, 1.0);
+
+const LASER_WIDTH: real = 1.0;
+
+const BRIGHT_LASER_COLOR: Color = Color::from_rgba(1.0, 0.15, 0.15, 1.0);
+
+const BRIGHT_LASER_WIDTH: real = 2.5;
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    /// 解锁不高于该关卡的配件，读档时不提示
+    pub fn unlock_level_attachments(&mut self, level: u32) {
+        let previous_level = self.attachment_level;
+        if level <= previous_level {
+            return;
+        }
+        self.attachment_level = level;
+        if 0 == previous_level {
+            return;
+        }
+        for attachment in ATTACHMENTS {
+            let unlock_level = attachment.get_unlock_level();
+            if unlock_level <= previous_level || unlock_level > level {
+                continue;
+            }
+            if let Some(mut message_label) = self.create_message() {
+                message_label.bind_mut().show_message(&format!(
+                    "ATTACHMENT {} UNLOCKED, PRESS I TO EQUIP IT",
+                    attachment.get_title()
+                ));
+            }
+        }
+    }
+
+    pub fn is_attachment_unlocked(&self, attachment: Attachment) -> bool {
+        attachment.get_unlock_level() <= self.attachment_level
+    }
+
+    pub fn toggle_loadout(&mut self) {
+        if self.loadout_open {
+            self.close_loadout();
+        } else if !RustWorld::is_paused() && PlayerState::Dead != self.state {
+            self.loadout_open = true;
+            RustWorld::pause();
+            self.refresh_loadout();
+            self.hud.bind_mut().set_loadout_visible(true);
+        }
+    }
+
+    #[func]
+    pub fn close_loadout(&mut self) {
+        if !self.loadout_open {
+            return;
+        }
+        self.loadout_open = false;
+        self.hud.bind_mut().set_loadout_visible(false);
+        RustWorld::resume();
+    }
+
+    #[func]
+    pub fn toggle_attachment(&mut self, attachment: Attachment) {
+        if !self.is_attachment_unlocked(attachment) {
+            return;
+        }
+        let mut weapon = self.get_current_weapon();
+        let result = weapon.bind_mut().toggle_attachment(attachment);
+        if let Err(message) = result {
+            if let Some(mut message_label) = self.create_message() {
+                message_label.bind_mut().show_message(&message);
+            }
+            return;
+        }
+        // 持枪移动速度和开镜倍率都可能变化
+        self.set_scope_zoom(Vector2::new(1.0, 1.0));
+        self.current_speed = self.speed * self.get_weapon_weight();
+        self.update_weapon_hud(&weapon);
+        self.refresh_loadout();
+    }
+
+    #[func]
+    pub fn move_weapon_up(&mut self) {
+        self.move_weapon(-1);
+    }
+
+    #[func]
+    pub fn move_weapon_down(&mut self) {
+        self.move_weapon(1);
+    }
+
+    // 和相邻的已拥有武器交换顺序，数字键和轮盘都跟着变
+    fn move_weapon(&mut self, step: i32) {
+        let slots = self.get_weapon_slots();
+        let Some(slot) = slots.iter().position(|i| *i == self.current_weapon_index) else {
+            return;
+        };
+        let Some(other_index) = slot
+            .checked_add_signed(step as isize)
+            .and_then(|other| slots.get(other))
+        else {
+            return;
+        };
+        let weapon_name = self.get_current_weapon().get_name().to_string();
+        let Some(other) = self.weapons.get_child(*other_index) else {
+            return;
+        };
+        let other_name = other.get_name().to_string();
+        if step < 0 {
+            WeaponSlot::swap(&other_name, &weapon_name);
+        } else {
+            WeaponSlot::swap(&weapon_name, &other_name);
+        }
+        self.refresh_loadout();
+    }
+
+    #[func]
+    pub fn toggle_favorite_weapon(&mut self) {
+        let weapon_name = self.get_current_weapon().get_name().to_string();
+        WeaponSlot::toggle_favorite(&weapon_name);
+        self.refresh_loadout();
+    }
+
+    fn refresh_loadout(&mut self) {
+        let weapon = self.get_current_weapon();
+        let entries: Vec<(Attachment, bool, bool)> = ATTACHMENTS
+            .into_iter()
+            .map(|attachment| {
+                (
+                    attachment,
+                    weapon.bind().has_attachment(attachment),
+                    self.is_attachment_unlocked(attachment),
+                )
+            })
+            .collect();
+        let weapon_name = weapon.get_name().to_string();
+        let slots = self.get_weapon_slots();
+        let slot = slots
+            .iter()
+            .position(|i| *i == self.current_weapon_index)
+            .unwrap_or_default();
+        let favorite = WeaponSlot::get(&weapon_name).favorite;
+        let gd = self.to_gd();
+        let mut hud = self.hud.bind_mut();
+        hud.update_loadout(&weapon_name, &entries, &gd);
+        hud.update_loadout_slot(slot, slots.len(), favorite);
+    }
+
+    pub fn update_laser_style(&mut self, bright: bool) {
+        if bright {
+            self.line2d.set_default_color(BRIGHT_LASER_COLOR);
+            self.line2d.set_width(BRIGHT_LASER_WIDTH);
+        } else {
+            self.line2d.set_default_color(LASER_COLOR);
+            self.line2d.set_width(LASER_WIDTH);
+        }
+    }
+}
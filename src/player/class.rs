@@ -0,0 +1,258 @@
+use super::*;
+use crate::weapon::definition::{WeaponDefinition, WeaponUnlock};
+use crate::{
+    ABILITY_COOLDOWN, DEMOLITIONS_AFFINITY_RATE, DEMOLITIONS_GRENADE_RATE, HEAVY_FORTIFY_RATE,
+    HEAVY_FORTIFY_TIME, HEAVY_REPEL, MEDIC_HEAL, MEDIC_HEAL_DISTANCE, MEDIC_REGEN, PlayerClass,
+    SCOUT_ASSASSINATE_ANGLE, SCOUT_CLOAK_TIME, SCOUT_CLOAK_VISIBILITY_RATE, SCOUT_SPEED_RATE,
+};
+
+// 各职业开局额外携带的武器
+fn get_starting_weapons(player_class: PlayerClass) -> &'static [&'static str] {
+    match player_class {
+        PlayerClass::Survivor => &[],
+        PlayerClass::Medic => &["deagle"],
+        PlayerClass::Demolitions => &["m79"],
+        PlayerClass::Scout => &["awp"],
+        PlayerClass::Heavy => &["m1887"],
+    }
+}
+
+// 职业擅长的武器
+fn get_affinity_weapons(player_class: PlayerClass) -> &'static [&'static str] {
+    match player_class {
+        PlayerClass::Demolitions => &["M79", "RPG-7", "M32"],
+        PlayerClass::Heavy => &["M249", "MG3", "M134"],
+        _ => &[],
+    }
+}
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    /// 新开局时按生成玩家时给定的职业调整基础属性，读档时由存档覆盖
+    pub fn init_class(&mut self) {
+        if !self.class_applied {
+            self.class_applied = true;
+            self.apply_class_bonus();
+        }
+        // 等读档完成后再发放起始武器
+        self.base_mut().call_deferred("equip_class", &[]);
+        let upgrade = Self::get_class_upgrade_text(self.player_class);
+        self.hud.bind_mut().update_class_upgrade(upgrade);
+        self.update_ability_hud();
+    }
+
+    /// 职业加成，重新开始时属性会被重置，需要再次加上
+    pub fn apply_class_bonus(&mut self) {
+        match self.player_class {
+            PlayerClass::Survivor => {}
+            PlayerClass::Medic => {
+                self.health = self.health.saturating_add(20);
+                self.regen = MEDIC_REGEN;
+            }
+            PlayerClass::Demolitions => {
+                self.grenade_damage_rate = DEMOLITIONS_GRENADE_RATE;
+                self.grenade_cooldown *= 0.7;
+            }
+            PlayerClass::Scout => {
+                self.health = self.health.saturating_sub(20);
+                self.speed *= SCOUT_SPEED_RATE;
+                self.assassinate_angle = SCOUT_ASSASSINATE_ANGLE;
+            }
+            PlayerClass::Heavy => {
+                self.health = self.health.saturating_add(50);
+                self.repel += HEAVY_REPEL;
+                self.speed *= 0.9;
+            }
+        }
+        self.current_health = self.health;
+        self.current_speed = self.speed;
+    }
+
+    #[func]
+    pub fn equip_class(&mut self) {
+        for definition in WeaponDefinition::all() {
+            if WeaponUnlock::Start == definition.unlock {
+                self.add_weapon(&definition.name);
+            }
+        }
+        for weapon_name in get_starting_weapons(self.player_class) {
+            self.add_weapon(weapon_name);
+        }
+    }
+
+    pub fn get_player_class(&self) -> PlayerClass {
+        self.player_class
+    }
+
+    /// 生成玩家时在加入场景树之前设置
+    pub fn set_player_class(&mut self, player_class: PlayerClass) {
+        self.player_class = player_class;
+    }
+
+    #[allow(clippy::borrow_interior_mutable_const)]
+    pub fn get_class_grenades(&self) -> Vec<Gd<PackedScene>> {
+        match self.player_class {
+            PlayerClass::Medic => {
+                vec![GRENADE.clone(), HEAL_GRENADE.clone(), SMOKE_GRENADE.clone()]
+            }
+            _ => vec![
+                GRENADE.clone(),
+                FLASH_GRENADE.clone(),
+                SMOKE_GRENADE.clone(),
+            ],
+        }
+    }
+
+    pub fn use_ability(&mut self) {
+        if PlayerState::Dead == self.state {
+            return;
+        }
+        if self.left_ability_cooldown > 0.0 {
+            let cooldown = self.left_ability_cooldown;
+            if let Some(mut message_label) = self.create_message() {
+                message_label
+                    .bind_mut()
+                    .show_message(&format!("ABILITY READY IN {:.1}S", cooldown));
+            }
+            return;
+        }
+        let message = match self.player_class {
+            PlayerClass::Survivor => {
+                self.current_stamina = self.stamina;
+                self.exhausted = false;
+                self.hud
+                    .bind_mut()
+                    .update_stamina_hud(self.current_stamina, self.stamina);
+                "SECOND WIND"
+            }
+            PlayerClass::Medic => {
+                self.heal_nearby();
+                "FIRST AID"
+            }
+            PlayerClass::Demolitions => {
+                self.current_grenade_cooldowns.fill(0.0);
+                self.update_grenade_hud();
+                "RESUPPLY"
+            }
+            PlayerClass::Scout => {
+                self.left_ability_time = SCOUT_CLOAK_TIME;
+                "CAMOUFLAGE"
+            }
+            PlayerClass::Heavy => {
+                self.left_ability_time = HEAVY_FORTIFY_TIME;
+                "FORTIFY"
+            }
+        };
+        self.left_ability_cooldown = ABILITY_COOLDOWN;
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(message);
+        }
+        self.update_ability_hud();
+    }
+
+    // 治疗自己和附近的队友
+    fn heal_nearby(&mut self) {
+        self.heal(MEDIC_HEAL);
+        let position = self.base().get_global_position();
+        for index in 0..MAX_PLAYERS {
+            if index == self.player_index
+                || Self::get_position_of(index).distance_to(position) > MEDIC_HEAL_DISTANCE
+            {
+                continue;
+            }
+            if let Some(mut teammate) = Self::get_by_index(index) {
+                teammate.bind_mut().heal(MEDIC_HEAL);
+            }
+        }
+    }
+
+    pub fn heal(&mut self, amount: u32) {
+        if matches!(self.state, PlayerState::Downed | PlayerState::Dead) {
+            return;
+        }
+        self.current_health = self.current_health.saturating_add(amount).min(self.health);
+        self.hud
+            .bind_mut()
+            .update_hp_hud(self.current_health, self.health);
+    }
+
+    pub fn update_class(&mut self, delta: f64) {
+        self.left_ability_cooldown = (self.left_ability_cooldown - delta).max(0.0);
+        self.left_ability_time = (self.left_ability_time - delta).max(0.0);
+        if self.regen > 0.0 && self.current_health < self.health {
+            // 不足1点的回复量累计到下一帧
+            self.regen_progress += self.regen * delta as real;
+            if self.regen_progress >= 1.0 {
+                let amount = self.regen_progress as u32;
+                self.regen_progress -= amount as real;
+                self.heal(amount);
+            }
+        }
+        self.update_ability_hud();
+    }
+
+    fn update_ability_hud(&mut self) {
+        let player_class = self.player_class;
+        let cooldown = self.left_ability_cooldown;
+        let active = self.left_ability_time;
+        self.hud
+            .bind_mut()
+            .update_ability_hud(player_class, cooldown, active);
+    }
+
+    /// 侦察兵隐身时几乎不会被发现
+    pub fn get_class_visibility_rate(&self) -> real {
+        if PlayerClass::Scout == self.player_class && self.left_ability_time > 0.0 {
+            SCOUT_CLOAK_VISIBILITY_RATE
+        } else {
+            1.0
+        }
+    }
+
+    /// 重装兵坚守时受到的伤害减少
+    pub fn get_class_damage_rate(&self) -> real {
+        if PlayerClass::Heavy == self.player_class && self.left_ability_time > 0.0 {
+            HEAVY_FORTIFY_RATE
+        } else {
+            1.0
+        }
+    }
+
+    fn has_weapon_affinity(&self) -> bool {
+        let weapon_name = self.get_current_weapon().get_name().to_string();
+        get_affinity_weapons(self.player_class).contains(&weapon_name.as_str())
+    }
+
+    /// 爆破兵使用榴弹和火箭筒时伤害更高
+    pub fn get_class_damage(&self) -> i64 {
+        if PlayerClass::Demolitions == self.player_class && self.has_weapon_affinity() {
+            let weapon_damage = self.get_current_weapon().bind().get_damage();
+            return self.damage.saturating_add(
+                (weapon_damage as real * (DEMOLITIONS_AFFINITY_RATE - 1.0)) as i64,
+            );
+        }
+        self.damage
+    }
+
+    /// 重装兵使用机枪时不减速，转管转起来时仍然变慢
+    pub fn get_weapon_weight(&self) -> real {
+        let weapon = self.get_current_weapon();
+        let weight = weapon.bind().get_weight();
+        let spin_rate = weapon.bind().get_spin_weight_rate();
+        if PlayerClass::Heavy == self.player_class && self.has_weapon_affinity() {
+            weight.max(1.0) * spin_rate
+        } else {
+            weight * spin_rate
+        }
+    }
+
+    pub fn get_class_upgrade_text(player_class: PlayerClass) -> &'static str {
+        match player_class {
+            PlayerClass::Survivor => "",
+            PlayerClass::Medic => "REGEN+0.5",
+            PlayerClass::Demolitions => "GRENADE DAMAGE+20%",
+            PlayerClass::Scout => "ASSASSINATE ANGLE+10",
+            PlayerClass::Heavy => "REPEL+3",
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use super::*;
+use crate::{
+    COOP_CAMERA_MARGIN, COOP_MAX_DISTANCE, COOP_MIN_ZOOM, CoopLives, MAX_PLAYERS, REVIVE_DISTANCE,
+    REVIVE_TIME,
+};
+use godot::builtin::StringName;
+use godot::classes::{InputMap, Node};
+use godot::obj::InstanceId;
+
+// 已加入游戏的玩家，0表示该位置没有玩家
+static PLAYER_IDS: [AtomicCell<i64>; MAX_PLAYERS] = [const { AtomicCell::new(0) }; MAX_PLAYERS];
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn set_player_index(&mut self, index: usize) {
+        self.player_index = index.min(MAX_PLAYERS - 1);
+    }
+
+    pub fn get_player_index(&self) -> usize {
+        self.player_index
+    }
+
+    pub fn join(&mut self) {
+        PLAYER_IDS[self.player_index].store(self.base().instance_id().to_i64());
+        if self.is_local_teammate() {
+            // 2P共用1P的镜头
+            self.camera.set_enabled(false);
+            self.hud.bind_mut().use_teammate_layout();
+        }
+    }
+
+    pub fn leave(&mut self) {
+        let id = self.base().instance_id().to_i64();
+        _ = PLAYER_IDS[self.player_index].compare_exchange(id, 0);
+    }
+
+    pub fn get_by_index(index: usize) -> Option<Gd<Self>> {
+        let id = PLAYER_IDS.get(index)?.load();
+        if 0 == id {
+            return None;
+        }
+        Gd::try_from_instance_id(InstanceId::from_i64(id)).ok()
+    }
+
+    pub fn is_joined(index: usize) -> bool {
+        0 != PLAYER_IDS[index].load()
+    }
+
+    /// 同一台机器上的2P，联机时每名玩家都有自己的镜头
+    pub fn is_local_teammate(&self) -> bool {
+        self.player_index > 0 && !RustNetwork::is_online()
+    }
+
+    pub fn is_coop() -> bool {
+        (1..MAX_PLAYERS).any(Self::is_joined)
+    }
+
+    pub fn get_teammate(&self) -> Option<Gd<Self>> {
+        (0..MAX_PLAYERS)
+            .filter(|index| *index != self.player_index)
+            .find_map(Self::get_by_index)
+    }
+
+    /// 通过子节点找到所属玩家的编号，供武器、刀、HUD使用
+    pub fn find_owner_index(node: Gd<Node>) -> usize {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            let id = node.instance_id().to_i64();
+            if let Some(index) = PLAYER_IDS
+                .iter()
+                .position(|player_id| player_id.load() == id)
+            {
+                return index;
+            }
+            current = node.get_parent();
+        }
+        0
+    }
+
+    // 倒地或死亡的玩家不会被僵尸追击
+    fn is_available(index: usize) -> bool {
+        Self::is_joined(index)
+            && !matches!(
+                Self::get_state_of(index),
+                PlayerState::Downed | PlayerState::Dead
+            )
+    }
+
+    /// 僵尸选择目标，越近、越显眼的玩家威胁越大
+    pub fn get_target_index(from: Vector2) -> usize {
+        (0..MAX_PLAYERS)
+            .filter(|index| Self::is_available(*index))
+            .min_by(|a, b| {
+                Self::get_threat_distance(from, *a).total_cmp(&Self::get_threat_distance(from, *b))
+            })
+            .unwrap_or(0)
+    }
+
+    fn get_threat_distance(from: Vector2, index: usize) -> real {
+        from.distance_to(Self::get_position_of(index)) / Self::get_visibility(index).max(0.1)
+    }
+
+    pub fn get_nearest_index(from: Vector2) -> usize {
+        (0..MAX_PLAYERS)
+            .filter(|index| Self::is_joined(*index))
+            .min_by(|a, b| {
+                from.distance_to(Self::get_position_of(*a))
+                    .total_cmp(&from.distance_to(Self::get_position_of(*b)))
+            })
+            .unwrap_or(0)
+    }
+
+    // 共用生命时从1P扣除
+    pub fn take_life(&mut self) -> bool {
+        if self.player_index > 0 && CoopLives::Shared == RustWorld::get_coop_lives() {
+            if let Some(mut leader) = Self::get_by_index(0) {
+                return leader.bind_mut().take_life();
+            }
+        }
+        if 0 == self.current_lives {
+            return false;
+        }
+        self.current_lives -= 1;
+        true
+    }
+
+    pub fn has_lives(&self) -> bool {
+        if self.player_index > 0 && CoopLives::Shared == RustWorld::get_coop_lives() {
+            if let Some(leader) = Self::get_by_index(0) {
+                return leader.bind().has_lives();
+            }
+        }
+        self.current_lives > 0
+    }
+
+    /// 所有玩家都已死亡且没有剩余生命
+    pub fn is_team_out(&self) -> bool {
+        if self.has_lives() {
+            return false;
+        }
+        let shared = CoopLives::Shared == RustWorld::get_coop_lives();
+        self.get_teammate().is_none_or(|teammate| {
+            let teammate = teammate.bind();
+            PlayerState::Dead == teammate.state && (shared || 0 == teammate.current_lives)
+        })
+    }
+
+    // 队友还能行动时才会倒地，否则直接死亡
+    pub fn can_be_downed(&self) -> bool {
+        PlayerState::Downed != self.state
+            && (0..MAX_PLAYERS).any(|index| index != self.player_index && Self::is_available(index))
+    }
+
+    pub fn update_downed(&mut self, delta: f64) {
+        self.left_bleed_time -= delta;
+        let position = self.base().get_global_position();
+        let rescuing = (0..MAX_PLAYERS).any(|index| {
+            index != self.player_index
+                && Self::is_available(index)
+                && Self::get_position_of(index).distance_to(position) <= REVIVE_DISTANCE
+        });
+        if rescuing {
+            self.revive_progress += delta;
+        } else {
+            self.revive_progress = (self.revive_progress - delta).max(0.0);
+        }
+        self.hud
+            .bind_mut()
+            .update_downed_hud(self.left_bleed_time, self.revive_progress / REVIVE_TIME);
+        if self.revive_progress >= REVIVE_TIME {
+            self.revive();
+        } else if self.left_bleed_time <= 0.0 {
+            self.die(position);
+        }
+    }
+
+    // 1P的镜头同时框住两名玩家
+    pub fn update_coop_camera(&mut self) {
+        if self.player_index > 0 || RustNetwork::is_online() {
+            return;
+        }
+        let Some(teammate) = self.get_teammate() else {
+            if Vector2::ZERO != self.camera.get_position() {
+                // 2P离开后镜头回到1P身上
+                self.camera.set_position(Vector2::ZERO);
+                self.camera.set_zoom(self.scope_zoom);
+            }
+            return;
+        };
+        let position = self.base().get_global_position();
+        let teammate_position = teammate.get_global_position();
+        self.camera
+            .set_global_position(position.lerp(teammate_position, 0.5));
+        let viewport_size = self.base().get_viewport_rect().size;
+        let span = (teammate_position - position).abs() + Vector2::splat(COOP_CAMERA_MARGIN);
+        let zoom = (viewport_size.x / span.x)
+            .min(viewport_size.y / span.y)
+            .clamp(COOP_MIN_ZOOM, 1.0)
+            .min(self.scope_zoom.x);
+        self.camera.set_zoom(Vector2::splat(zoom));
+    }
+
+    // 限制两名玩家的距离，保证都在镜头内
+    pub fn keep_near_teammate(&mut self) {
+        if RustNetwork::is_online() {
+            return;
+        }
+        let Some(teammate) = self.get_teammate() else {
+            return;
+        };
+        let teammate_position = teammate.get_global_position();
+        let position = self.base().get_global_position();
+        if position.distance_to(teammate_position) > COOP_MAX_DISTANCE {
+            self.base_mut().set_global_position(
+                teammate_position + teammate_position.direction_to(position) * COOP_MAX_DISTANCE,
+            );
+        }
+    }
+
+    // 本地2P的操作统一使用p2_前缀的按键映射
+    fn get_action(&self, action: &str) -> Option<StringName> {
+        let action = if !self.is_local_teammate() {
+            StringName::from(action)
+        } else {
+            StringName::from(format!("p2_{}", action).as_str())
+        };
+        InputMap::singleton().has_action(&action).then_some(action)
+    }
+
+    pub fn is_action_pressed(&self, action: &str) -> bool {
+        if self.is_remote() {
+            return self.get_remote_strength(action) > 0.0;
+        }
+        self.get_action(action)
+            .is_some_and(|action| Input::singleton().is_action_pressed(&action))
+    }
+
+    pub fn is_event_pressed(&self, event: &Gd<InputEvent>, action: &str) -> bool {
+        self.get_action(action)
+            .is_some_and(|action| event.is_action_pressed(&action))
+    }
+
+    pub fn is_event_released(&self, event: &Gd<InputEvent>, action: &str) -> bool {
+        self.get_action(action)
+            .is_some_and(|action| event.is_action_released(&action))
+    }
+
+    pub fn get_action_vector(
+        &self,
+        negative_x: &str,
+        positive_x: &str,
+        negative_y: &str,
+        positive_y: &str,
+    ) -> Vector2 {
+        if self.is_remote() {
+            return Vector2::new(
+                self.get_remote_strength(positive_x) - self.get_remote_strength(negative_x),
+                self.get_remote_strength(positive_y) - self.get_remote_strength(negative_y),
+            )
+            .limit_length(Some(1.0));
+        }
+        match (
+            self.get_action(negative_x),
+            self.get_action(positive_x),
+            self.get_action(negative_y),
+            self.get_action(positive_y),
+        ) {
+            (Some(negative_x), Some(positive_x), Some(negative_y), Some(positive_y)) => {
+                Input::singleton().get_vector(&negative_x, &positive_x, &negative_y, &positive_y)
+            }
+            _ => Vector2::ZERO,
+        }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.is_action_pressed("move_left")
+            || self.is_action_pressed("move_right")
+            || self.is_action_pressed("move_up")
+            || self.is_action_pressed("move_down")
+    }
+}
@@ -0,0 +1,63 @@
+use super::*;
+use crate::StatusEffect;
+use crate::damage_number::RustDamageNumbers;
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    #[func]
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        if matches!(self.state, PlayerState::Dead | PlayerState::Downed)
+            || self.invincible
+            || self.is_dodge_invincible()
+        {
+            return;
+        }
+        if self.effects.apply(effect) {
+            self.update_effects_hud();
+        }
+    }
+
+    pub fn update_effects(&mut self, delta: f64) {
+        let damage = self.effects.process(delta);
+        if damage > 0 {
+            self.on_effect_hit(damage);
+        }
+        self.update_effects_hud();
+    }
+
+    pub fn clear_effects(&mut self) {
+        self.effects.clear();
+        self.update_effects_hud();
+    }
+
+    // 持续伤害不打断玩家动作
+    fn on_effect_hit(&mut self, hit_val: i64) {
+        RustDamageNumbers::push(
+            self.base().instance_id(),
+            self.base().get_global_position(),
+            hit_val,
+            None,
+            false,
+        );
+        if !self.invincible {
+            self.current_health = self.current_health.saturating_sub(hit_val as u32);
+            self.hud
+                .bind_mut()
+                .update_hp_hud(self.current_health, self.health);
+        }
+        if 0 == self.current_health {
+            let position = self.base().get_global_position();
+            self.die(position);
+        }
+    }
+
+    fn update_effects_hud(&mut self) {
+        let color = self.effects.color();
+        self.animated_sprite2d.set_modulate(color);
+        let description = self.effects.describe();
+        let poisoned = self.effects.has(StatusEffect::Poison);
+        self.hud
+            .bind_mut()
+            .update_effects_hud(&description, poisoned);
+    }
+}
@@ -0,0 +1,127 @@
+use super::*;
+use crate::{AimSource, GrenadeType};
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn init_grenades(&mut self) {
+        if self.grenade_scenes.is_empty() {
+            for scene in self.get_class_grenades() {
+                self.grenade_scenes.push(&scene);
+            }
+        }
+        // 实例化一次读取手雷类型，供切换和HUD使用
+        self.grenade_types.clear();
+        for grenade_scene in self.grenade_scenes.iter_shared() {
+            if let Some(grenade) = grenade_scene.try_instantiate_as::<RustGrenade>() {
+                let grenade_type = grenade.bind().get_grenade_type();
+                let cooldown_rate = grenade.bind().get_cooldown_rate();
+                self.grenade_types.push((grenade_type, cooldown_rate));
+                grenade.free();
+            }
+        }
+        self.current_grenade_cooldowns
+            .resize(self.grenade_types.len(), 0.0);
+        if self.current_grenade_index >= self.grenade_types.len() {
+            self.current_grenade_index = 0;
+        }
+        self.update_grenade_hud();
+    }
+
+    pub fn update_grenade_cooldowns(&mut self, delta: f64) {
+        for cooldown in self.current_grenade_cooldowns.iter_mut() {
+            *cooldown = (*cooldown - delta as real).max(0.0);
+        }
+        self.update_grenade_hud();
+    }
+
+    pub fn switch_grenade(&mut self) {
+        if self.grenade_types.is_empty() {
+            return;
+        }
+        self.current_grenade_index = (self.current_grenade_index + 1) % self.grenade_types.len();
+        let (grenade_type, _) = self.grenade_types[self.current_grenade_index];
+        if let Some(mut message_label) = self.create_message() {
+            message_label
+                .bind_mut()
+                .show_message(&format!("{:?} GRENADE", grenade_type).to_uppercase());
+        }
+        self.update_grenade_hud();
+    }
+
+    pub fn throw_grenade(&mut self) {
+        let index = self.current_grenade_index;
+        let (Some(grenade_scene), Some(&(grenade_type, _))) = (
+            self.grenade_scenes.get(index),
+            self.grenade_types.get(index),
+        ) else {
+            return;
+        };
+        let current_cooldown = self.current_grenade_cooldowns[index];
+        if current_cooldown > 0.0 {
+            if let Some(mut grenade_cooldown_label) = self.create_message() {
+                grenade_cooldown_label.bind_mut().show_message(
+                    &format!("{:?} READY IN {:.1}S", grenade_type, current_cooldown).to_uppercase(),
+                );
+            }
+            return;
+        }
+        let aim_position = Self::get_aim_position(self.player_index);
+        let direction = self
+            .base()
+            .get_global_position()
+            .direction_to(aim_position)
+            .normalized();
+        let grenade_point = self.grenade_point.get_global_position();
+        // 摇杆瞄准时投掷距离不超过瞄准点，鼠标照旧扔满射程
+        let final_distance = match Self::get_aim_source(self.player_index) {
+            AimSource::Mouse => GRENADE_DISTANCE + self.distance,
+            AimSource::Stick => {
+                (GRENADE_DISTANCE + self.distance).min(grenade_point.distance_to(aim_position))
+            }
+        };
+        if let Some(mut grenade) = grenade_scene.try_instantiate_as::<RustGrenade>() {
+            grenade.set_global_position(grenade_point);
+            let mut gd_mut = grenade.bind_mut();
+            gd_mut.set_bullet_point(grenade_point);
+            gd_mut.set_final_distance(final_distance);
+            gd_mut.set_final_damage(
+                ((GRENADE_DAMAGE + self.damage) as real * self.grenade_damage_rate) as i64,
+            );
+            gd_mut.set_final_repel(GRENADE_REPEL + self.repel);
+            gd_mut.set_direction(direction);
+            gd_mut.set_player_index(self.player_index);
+            let cooldown_rate = gd_mut.get_cooldown_rate();
+            drop(gd_mut);
+            if let Some(mut parent) = self.base().get_parent() {
+                RustNetwork::name_spawned(&mut grenade.clone().upcast());
+                parent.add_child(&grenade);
+                self.current_grenade_cooldowns[index] = self.grenade_cooldown * cooldown_rate;
+                RustAchievements::on_ranged_used();
+            }
+        }
+    }
+
+    pub fn update_grenade_hud(&mut self) {
+        let text = self
+            .grenade_types
+            .iter()
+            .zip(self.current_grenade_cooldowns.iter())
+            .enumerate()
+            .map(|(i, ((grenade_type, _), cooldown))| {
+                let status = if *cooldown > 0.0 {
+                    format!("{:?} {:.1}S", grenade_type, cooldown)
+                } else {
+                    format!("{:?} READY", grenade_type)
+                };
+                if i == self.current_grenade_index {
+                    format!("[{}]", status)
+                } else {
+                    status
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+            .to_uppercase();
+        self.hud.bind_mut().update_grenade_hud(&text);
+    }
+}
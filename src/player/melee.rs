@@ -0,0 +1,153 @@
+use super::*;
+use crate::knife::COMBO_STEPS;
+use crate::{
+    MELEE_COMBO_WINDOW, MELEE_HEAVY_CHARGE_TIME, MELEE_PARRY_COOLDOWN, MELEE_PARRY_DISTANCE,
+    MELEE_PARRY_TIME, MELEE_WEAPONS,
+};
+use std::sync::atomic::AtomicBool;
+
+// 正在格挡的玩家
+static PARRYING: [AtomicBool; MAX_PLAYERS] = [const { AtomicBool::new(false) }; MAX_PLAYERS];
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn update_melee(&mut self, delta: f64) {
+        self.left_combo_time = (self.left_combo_time - delta).max(0.0);
+        self.current_parry_cooldown = (self.current_parry_cooldown - delta).max(0.0);
+        if self.left_parry_time > 0.0 {
+            self.left_parry_time -= delta;
+            if self.left_parry_time <= 0.0 {
+                PARRYING[self.player_index].store(false, Ordering::Release);
+            }
+        }
+    }
+
+    /// 按下近战键开始蓄力
+    pub fn start_charge(&mut self) {
+        self.charging = true;
+        self.melee_charge = 0.0;
+    }
+
+    /// 按住近战键时持续蓄力
+    pub fn charge_melee(&mut self, delta: f64) {
+        if !self.charging {
+            return;
+        }
+        let charge = self.melee_charge;
+        self.melee_charge += delta;
+        if charge < MELEE_HEAVY_CHARGE_TIME && self.melee_charge >= MELEE_HEAVY_CHARGE_TIME {
+            if let Some(mut message_label) = self.create_message() {
+                message_label.bind_mut().show_message("HEAVY READY");
+            }
+        }
+    }
+
+    /// 松开近战键，蓄力足够时重击，否则轻击
+    pub fn release_charge(&mut self) {
+        if !self.charging {
+            return;
+        }
+        self.charging = false;
+        let heavy = self.melee_charge >= MELEE_HEAVY_CHARGE_TIME;
+        self.melee_charge = 0.0;
+        self.chop(heavy);
+    }
+
+    // 在连击窗口内挥砍进入下一段连击，重击会打断连击
+    pub fn next_combo_step(&mut self, heavy: bool) -> usize {
+        if heavy || self.left_combo_time <= 0.0 {
+            self.combo_step = 0;
+        } else {
+            self.combo_step = (self.combo_step + 1) % COMBO_STEPS.len();
+        }
+        if heavy {
+            self.left_combo_time = 0.0;
+        } else {
+            let data = self.melee_weapon.get_data();
+            self.left_combo_time =
+                (self.chop_cooldown * data.cooldown_rate) as f64 + MELEE_COMBO_WINDOW;
+        }
+        self.combo_step
+    }
+
+    pub fn parry(&mut self) {
+        if PlayerState::Dead == self.state
+            || PlayerState::Impact == self.state
+            || PlayerState::Dodge == self.state
+            || self.current_parry_cooldown > 0.0
+            || !self.knife.bind().is_ready()
+        {
+            return;
+        }
+        self.charging = false;
+        self.current_parry_cooldown = MELEE_PARRY_COOLDOWN;
+        self.left_parry_time = MELEE_PARRY_TIME;
+        PARRYING[self.player_index].store(true, Ordering::Release);
+        self.start_melee_state();
+        self.knife.bind_mut().parry(MELEE_PARRY_TIME);
+    }
+
+    /// 刀在手上时投掷，已投掷时召回
+    pub fn throw_knife(&mut self) {
+        if PlayerState::Dead == self.state {
+            return;
+        }
+        if self.knife.bind().is_thrown() {
+            self.knife.bind_mut().recall();
+            return;
+        }
+        if PlayerState::Impact == self.state
+            || PlayerState::Dodge == self.state
+            || PlayerState::Chop == self.state
+        {
+            return;
+        }
+        let data = self.melee_weapon.get_data();
+        if !data.throwable {
+            if let Some(mut message_label) = self.create_message() {
+                message_label
+                    .bind_mut()
+                    .show_message(&format!("CAN'T THROW {}", data.name));
+            }
+            return;
+        }
+        self.charging = false;
+        let damage = self.damage;
+        let repel = self.repel;
+        let direction = self.get_current_direction();
+        self.knife.bind_mut().throw(damage, repel, direction);
+    }
+
+    pub fn next_melee(&mut self) {
+        if !self.knife.bind().is_ready() {
+            return;
+        }
+        let index = MELEE_WEAPONS
+            .iter()
+            .position(|melee| *melee == self.melee_weapon)
+            .unwrap_or(0);
+        self.melee_weapon = MELEE_WEAPONS[(index + 1) % MELEE_WEAPONS.len()];
+        self.apply_melee();
+        let name = self.melee_weapon.get_data().name;
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(&name);
+        }
+    }
+
+    pub fn apply_melee(&mut self) {
+        let melee = self.melee_weapon;
+        self.combo_step = 0;
+        self.left_combo_time = 0.0;
+        self.knife.bind_mut().set_melee(melee);
+        self.hud.bind_mut().update_melee_hud(&melee.get_data().name);
+    }
+
+    /// 附近正在格挡的玩家
+    pub fn get_parrying_index(position: Vector2) -> Option<usize> {
+        (0..MAX_PLAYERS).find(|index| {
+            PARRYING[*index].load(Ordering::Acquire)
+                && PlayerState::Chop == Self::get_state_of(*index)
+                && Self::get_position_of(*index).distance_to(position) <= MELEE_PARRY_DISTANCE
+        })
+    }
+}
@@ -1,14 +1,23 @@
+use crate::achievement::RustAchievements;
+use crate::combat::{CombatEvent, CombatEventBus};
 use crate::common::RustMessage;
+use crate::effect::StatusEffects;
 use crate::grenade::RustGrenade;
 use crate::hud::RustHUD;
 use crate::knife::RustKnife;
+use crate::mastery::WeaponMastery;
+use crate::network::RustNetwork;
 use crate::world::RustWorld;
 use crate::{
-    GRENADE_DAMAGE, GRENADE_DISTANCE, GRENADE_REPEL, MESSAGE, PLAYER_LEVEL_UP_BARRIER,
-    PLAYER_MAX_HEALTH, PLAYER_MAX_LIVES, PLAYER_MOVE_SPEED, PlayerState, scale_rate,
+    ASSASSINATE_ANGLE, AmmoType, DODGE_TIME, GRENADE_COOLDOWN, GRENADE_DAMAGE, GRENADE_DISTANCE,
+    GRENADE_REPEL, GrenadeType, MAX_PLAYERS, MESSAGE, MeleeWeapon, PLAYER_LEVEL_UP_BARRIER,
+    PLAYER_MAX_HEALTH, PLAYER_MAX_LIVES, PLAYER_MAX_STAMINA, PLAYER_MOVE_SPEED, Perk, PlayerClass,
+    PlayerState, STAMINA_RECOVER_RATE, scale_rate,
 };
 use crossbeam_utils::atomic::AtomicCell;
-use godot::builtin::{Array, GString, Vector2, real};
+use godot::builtin::{
+    Array, Dictionary, GString, PackedInt32Array, PackedStringArray, StringName, Vector2, real,
+};
 use godot::classes::node::PhysicsInterpolationMode;
 use godot::classes::{
     AnimatedSprite2D, AudioStreamPlayer2D, Camera2D, CharacterBody2D, GpuParticles2D,
@@ -18,6 +27,7 @@ use godot::classes::{
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
 use godot::tools::load;
+use std::collections::HashMap;
 use std::sync::LazyLock;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -30,7 +40,32 @@ pub mod upgrade;
 
 pub mod save;
 
-static POSITION: AtomicCell<Vector2> = AtomicCell::new(Vector2::ZERO);
+pub mod aim;
+
+pub mod effect;
+
+pub mod grenade;
+
+pub mod stealth;
+
+pub mod coop;
+
+pub mod network;
+
+pub mod class;
+
+pub mod perk;
+
+pub mod melee;
+
+pub mod attachment;
+
+pub mod ammo;
+
+pub mod wheel;
+
+static POSITIONS: [AtomicCell<Vector2>; MAX_PLAYERS] =
+    [const { AtomicCell::new(Vector2::ZERO) }; MAX_PLAYERS];
 
 static LAST_SCORE_UPDATE: AtomicU64 = AtomicU64::new(0);
 
@@ -38,6 +73,18 @@ static LAST_SCORE_UPDATE: AtomicU64 = AtomicU64::new(0);
 const GRENADE: LazyLock<Gd<PackedScene>> =
     LazyLock::new(|| load("res://scenes/grenades/fgrenade.tscn"));
 
+#[allow(clippy::declare_interior_mutable_const)]
+const FLASH_GRENADE: LazyLock<Gd<PackedScene>> =
+    LazyLock::new(|| load("res://scenes/grenades/flashbang.tscn"));
+
+#[allow(clippy::declare_interior_mutable_const)]
+const SMOKE_GRENADE: LazyLock<Gd<PackedScene>> =
+    LazyLock::new(|| load("res://scenes/grenades/smoke_grenade.tscn"));
+
+#[allow(clippy::declare_interior_mutable_const)]
+const HEAL_GRENADE: LazyLock<Gd<PackedScene>> =
+    LazyLock::new(|| load("res://scenes/grenades/heal_grenade.tscn"));
+
 #[derive(GodotClass)]
 #[class(base=CharacterBody2D)]
 pub struct RustPlayer {
@@ -65,12 +112,24 @@ pub struct RustPlayer {
     #[doc = "玩家击退系数"]
     #[export]
     repel: real,
+    #[doc = "玩家额外暴击率"]
+    #[export]
+    crit_chance: real,
+    #[doc = "玩家额外暴击伤害倍率"]
+    #[export]
+    crit_damage: real,
     #[doc = "玩家最大生命值"]
     #[export]
     health: u32,
     #[doc = "玩家移动速度"]
     #[export]
     speed: real,
+    #[doc = "玩家最大体力"]
+    #[export]
+    stamina: real,
+    #[doc = "玩家每秒恢复的体力"]
+    #[export]
+    stamina_recover: real,
     #[doc = "玩家升级所需的分数"]
     #[export]
     level_up_barrier: u32,
@@ -83,15 +142,82 @@ pub struct RustPlayer {
     #[doc = "斩击冷却时间"]
     #[export]
     chop_cooldown: real,
+    #[doc = "手柄瞄准时是否开启辅助瞄准"]
+    #[export]
+    aim_assist: bool,
+    #[doc = "玩家每秒回复的生命值"]
+    #[export]
+    regen: real,
+    #[doc = "手雷伤害倍率"]
+    #[export]
+    grenade_damage_rate: real,
+    #[doc = "刺杀允许的背后夹角"]
+    #[export]
+    assassinate_angle: real,
+    #[doc = "当前近战武器"]
+    #[export]
+    melee_weapon: MeleeWeapon,
+    player_class: PlayerClass,
+    // 职业属性只在新开局时加成一次
+    class_applied: bool,
+    regen_progress: real,
+    left_ability_cooldown: f64,
+    left_ability_time: f64,
+    // 已获得的天赋，按获得顺序
+    perks: Vec<Perk>,
+    // 0为1P，1为2P
+    player_index: usize,
+    // 联机时操作该玩家的连接号，1为主机
+    peer_id: i32,
+    // 主机上记录的客户端按键力度和瞄准点
+    remote_strengths: HashMap<String, real>,
+    remote_aim: Vector2,
+    left_input_time: f64,
+    scope_zoom: Vector2,
+    // 停止奔跑后射击仍不准的剩余时间
+    left_sprint_time: f64,
     current_chop_cooldown: f64,
-    current_grenade_cooldown: real,
+    // 刚完成的连击段数，以及还能续上连击的时间
+    combo_step: usize,
+    left_combo_time: f64,
+    charging: bool,
+    melee_charge: f64,
+    left_parry_time: f64,
+    current_parry_cooldown: f64,
+    // 已解锁配件对应的关卡
+    attachment_level: u32,
+    loadout_open: bool,
+    // 打开武器轮盘时按顺序排列的武器下标，以及准星指着的那把
+    wheel_slots: Vec<i32>,
+    wheel_selected: Option<i32>,
+    // 已补充过特殊弹药的关卡
+    ammo_level: u32,
+    // 已刷新到HUD的熟练度版本，以及HUD上显示的手上武器熟练度
+    mastery_version: u64,
+    shown_mastery: WeaponMastery,
+    current_grenade_index: usize,
+    // 每种手雷单独冷却
+    current_grenade_cooldowns: Vec<real>,
+    grenade_types: Vec<(GrenadeType, real)>,
     current_level_up_barrier: u32,
     current_lives: u32,
     current_health: u32,
+    current_stamina: real,
+    // 体力耗尽后需要恢复一部分才能再次奔跑
+    exhausted: bool,
+    sneaking: bool,
     state: PlayerState,
     current_speed: real,
     impact_position: Vector2,
     left_impact_time: f64,
+    dodge_direction: Vector2,
+    left_dodge_time: f64,
+    // 倒地后剩余的求救时间
+    left_bleed_time: f64,
+    revive_progress: f64,
+    // 右摇杆最后一次有效输入
+    stick_aim: Vector2,
+    effects: StatusEffects,
     // 玩家获得的分数
     score: u32,
     // 玩家死亡的次数
@@ -132,21 +258,67 @@ impl ICharacterBody2D for RustPlayer {
             distance: 0.0,
             penetrate: 0.0,
             repel: 0.0,
+            crit_chance: 0.0,
+            crit_damage: 0.0,
             health: PLAYER_MAX_HEALTH,
             current_health: PLAYER_MAX_HEALTH,
             state: PlayerState::Born,
             speed: PLAYER_MOVE_SPEED,
+            stamina: PLAYER_MAX_STAMINA,
+            stamina_recover: STAMINA_RECOVER_RATE,
+            current_stamina: PLAYER_MAX_STAMINA,
+            exhausted: false,
+            sneaking: false,
             level_up_barrier: PLAYER_LEVEL_UP_BARRIER,
-            grenade_cooldown: 10.0,
-            current_grenade_cooldown: 0.0,
+            grenade_cooldown: GRENADE_COOLDOWN,
+            current_grenade_index: 0,
+            current_grenade_cooldowns: Vec::new(),
+            grenade_types: Vec::new(),
             grenade_scenes: Array::new(),
             chop_cooldown: 0.5,
+            aim_assist: true,
+            regen: 0.0,
+            grenade_damage_rate: 1.0,
+            assassinate_angle: ASSASSINATE_ANGLE,
+            melee_weapon: MeleeWeapon::Katana,
+            player_class: PlayerClass::Survivor,
+            class_applied: false,
+            regen_progress: 0.0,
+            left_ability_cooldown: 0.0,
+            left_ability_time: 0.0,
+            perks: Vec::new(),
+            player_index: 0,
+            peer_id: 1,
+            remote_strengths: HashMap::new(),
+            remote_aim: Vector2::ZERO,
+            left_input_time: 0.0,
+            scope_zoom: Vector2::ONE,
+            left_sprint_time: 0.0,
             current_chop_cooldown: 0.0,
+            combo_step: 0,
+            left_combo_time: 0.0,
+            charging: false,
+            melee_charge: 0.0,
+            left_parry_time: 0.0,
+            current_parry_cooldown: 0.0,
+            attachment_level: 0,
+            loadout_open: false,
+            wheel_slots: Vec::new(),
+            wheel_selected: None,
+            ammo_level: 0,
+            mastery_version: 0,
+            shown_mastery: WeaponMastery::default(),
             current_level_up_barrier: PLAYER_LEVEL_UP_BARRIER,
             current_lives: PLAYER_MAX_LIVES,
             current_speed: PLAYER_MOVE_SPEED,
             impact_position: Vector2::ZERO,
             left_impact_time: 0.0,
+            dodge_direction: Vector2::ZERO,
+            left_dodge_time: 0.0,
+            left_bleed_time: 0.0,
+            revive_progress: 0.0,
+            stick_aim: Vector2::ZERO,
+            effects: StatusEffects::default(),
             score: 0,
             died: 0,
             kill_count: 0,
@@ -178,11 +350,32 @@ impl ICharacterBody2D for RustPlayer {
         if self.remote_transform2d.is_instance_valid() {
             self.remote_transform2d.set_global_rotation_degrees(0.0);
         }
+        if !RustNetwork::is_authority() {
+            self.update_client(delta);
+            return;
+        }
+        self.update_aim();
+        self.update_weapon_wheel();
+        self.update_coop_camera();
+        self.show_achievements();
+        self.update_perks();
+        self.update_mastery();
         if PlayerState::Dead == self.state || RustWorld::is_paused() {
             return;
         }
-        self.current_grenade_cooldown -= delta as real;
+        if PlayerState::Downed == self.state {
+            self.update_downed(delta);
+            return;
+        }
+        self.update_effects(delta);
+        if PlayerState::Dead == self.state {
+            return;
+        }
+        self.update_grenade_cooldowns(delta);
         self.current_chop_cooldown -= delta;
+        self.update_melee(delta);
+        self.update_stamina(delta);
+        self.update_class(delta);
         self.level_up();
         let mut hud = self.hud.bind_mut();
         hud.update_killed_hud(self.kill_count);
@@ -196,28 +389,34 @@ impl ICharacterBody2D for RustPlayer {
             } else {
                 self.impacted();
             }
+        } else if PlayerState::Dodge == self.state {
+            self.left_dodge_time += delta;
+            if self.left_dodge_time >= DODGE_TIME {
+                self.dodged();
+            }
         }
         let player_position = self.base().get_global_position();
-        POSITION.store(player_position);
-        let mouse_position = self.get_mouse_position();
-        self.base_mut().look_at(mouse_position);
-        let input = Input::singleton();
-        if input.is_action_pressed("mouse_left") {
+        POSITIONS[self.player_index].store(player_position);
+        let aim_position = Self::get_aim_position(self.player_index);
+        self.base_mut().look_at(aim_position);
+        self.update_accuracy(delta);
+        if self.effects.is_stunned() {
+            // 眩晕时无法行动
+        } else if self.is_weapon_wheel_open() {
+            // 选武器时不开火
+        } else if self.is_action_pressed("mouse_left") {
             self.shoot();
-        } else if input.is_action_pressed("e") {
-            self.chop();
-        } else if (input.is_action_pressed("shift") || input.is_action_pressed("mouse_right"))
-            && (input.is_action_pressed("move_left")
-                || input.is_action_pressed("move_right")
-                || input.is_action_pressed("move_up")
-                || input.is_action_pressed("move_down"))
+        } else if self.is_action_pressed("e") {
+            self.charge_melee(delta);
+        } else if (self.is_action_pressed("shift") || self.is_action_pressed("mouse_right"))
+            && self.is_moving()
         {
             self.run();
+        } else if self.sneaking && self.is_moving() {
+            self.sneak();
         }
-        let mut move_direction = Vector2::new(
-            input.get_axis("move_left", "move_right"),
-            input.get_axis("move_up", "move_down"),
-        );
+        let mut move_direction =
+            self.get_action_vector("move_left", "move_right", "move_up", "move_down");
         match self.state {
             PlayerState::Run => self
                 .animated_sprite2d
@@ -229,16 +428,25 @@ impl ICharacterBody2D for RustPlayer {
                     .normalized();
                 self.animated_sprite2d.look_at(self.impact_position);
             }
-            _ => self.animated_sprite2d.look_at(mouse_position),
+            PlayerState::Dodge => {
+                move_direction = self.dodge_direction;
+                self.animated_sprite2d
+                    .look_at(player_position + move_direction);
+            }
+            _ => self.animated_sprite2d.look_at(aim_position),
         }
         let mut character_body2d = self.base.to_gd();
         if move_direction != Vector2::ZERO {
-            character_body2d.set_velocity(move_direction.normalized() * self.current_speed);
+            character_body2d.set_velocity(
+                move_direction.normalized() * self.current_speed * self.effects.speed_rate(),
+            );
         } else {
             character_body2d.set_velocity(Vector2::ZERO);
             self.guard();
         }
         character_body2d.move_and_slide();
+        self.keep_near_teammate();
+        self.update_visibility();
     }
 
     fn enter_tree(&mut self) {
@@ -247,11 +455,14 @@ impl ICharacterBody2D for RustPlayer {
 
     fn exit_tree(&mut self) {
         self.grenade_scenes.clear();
+        CombatEventBus::unsubscribe(self.player_index);
+        self.leave();
     }
 
     fn ready(&mut self) {
         self.current_level_up_barrier = self.level_up_barrier;
         self.knife.set_visible(false);
+        self.apply_melee();
         self.change_weapon(self.current_weapon_index);
         self.base_mut()
             .set_physics_interpolation_mode(PhysicsInterpolationMode::ON);
@@ -260,6 +471,7 @@ impl ICharacterBody2D for RustPlayer {
         hud.update_lives_hud(self.current_lives, self.lives);
         hud.update_hp_hud(self.current_health, self.health);
         hud.update_speed_hud(self.current_speed);
+        hud.update_stamina_hud(self.current_stamina, self.stamina);
         hud.update_damage_hud(rust_weapon.bind().get_damage(), self.damage);
         hud.update_distance_hud(rust_weapon.bind().get_distance(), self.distance);
         hud.update_repel_hud(rust_weapon.bind().get_repel(), self.repel);
@@ -267,10 +479,13 @@ impl ICharacterBody2D for RustPlayer {
         hud.update_killed_hud(self.kill_count);
         hud.update_score_hud(self.score);
         hud.update_died_hud(self.died);
-        if self.grenade_scenes.is_empty() {
-            #[allow(clippy::borrow_interior_mutable_const)]
-            self.grenade_scenes.push(&*GRENADE);
-        }
+        drop(hud);
+        self.init_class();
+        self.init_perks();
+        self.init_grenades();
+        AmmoType::reset_reserves(self.player_index);
+        self.init_network();
+        self.join();
         if !self.player_name.is_empty() {
             let name = self.player_name.clone();
             let mut name_label = self.remote_transform2d.get_node_as::<Label>("Name");
@@ -281,60 +496,132 @@ impl ICharacterBody2D for RustPlayer {
     }
 
     fn input(&mut self, event: Gd<InputEvent>) {
-        if RustWorld::is_paused() {
+        if !self.is_local() {
+            return;
+        }
+        if !self.is_local_teammate() && event.is_class("InputEventMouseMotion") {
+            // 鼠标移动时切回鼠标瞄准
+            self.use_mouse_aim();
+        }
+        if RustNetwork::is_client() {
+            if self.is_event_released(&event, "weapon_wheel") {
+                // 轮盘在客户端上选，先把选中的武器发给主机
+                self.close_weapon_wheel();
+            }
+            // 客户端的操作交给主机处理
+            self.send_action(&event);
+            return;
+        }
+        self.handle_input(event);
+    }
+}
+
+#[godot_api]
+impl RustPlayer {
+    #[rpc(any_peer, call_remote, unreliable_ordered)]
+    pub fn receive_input(&mut self, strengths: Dictionary, aim_position: Vector2) {
+        self.apply_remote_input(strengths, aim_position);
+    }
+
+    #[rpc(any_peer, call_remote, reliable)]
+    pub fn receive_action(&mut self, action: StringName, pressed: bool) {
+        self.apply_remote_action(action, pressed);
+    }
+
+    #[rpc(any_peer, call_remote, reliable)]
+    pub fn receive_weapon_choice(&mut self, weapon_index: i32) {
+        self.apply_weapon_choice(weapon_index);
+    }
+
+    #[rpc(authority, call_remote, reliable)]
+    pub fn receive_weapon_wheel(
+        &mut self,
+        weapon_indices: PackedInt32Array,
+        weapon_names: PackedStringArray,
+        current_weapon_index: i32,
+    ) {
+        self.apply_weapon_wheel(weapon_indices, weapon_names, current_weapon_index);
+    }
+
+    pub fn handle_input(&mut self, event: Gd<InputEvent>) {
+        if self.is_event_pressed(&event, "loadout") {
+            self.toggle_loadout();
+            return;
+        }
+        if self.is_event_released(&event, "weapon_wheel") {
+            self.close_weapon_wheel();
+            return;
+        }
+        if RustWorld::is_paused() || self.effects.is_stunned() || PlayerState::Downed == self.state
+        {
             return;
         }
-        if event.is_action_pressed("e") {
-            self.chop();
-        } else if event.is_action_pressed("r") {
+        if self.is_event_pressed(&event, "dodge") {
+            self.dodge();
+        } else if self.is_event_pressed(&event, "sneak") {
+            self.toggle_sneak();
+        } else if self.is_event_pressed(&event, "ability") {
+            self.use_ability();
+        } else if self.is_event_pressed(&event, "e") {
+            self.start_charge();
+        } else if self.is_event_released(&event, "e") {
+            self.release_charge();
+        } else if self.is_event_pressed(&event, "parry") {
+            self.parry();
+        } else if self.is_event_pressed(&event, "throw_knife") {
+            self.throw_knife();
+        } else if self.is_event_pressed(&event, "next_melee") {
+            self.next_melee();
+        } else if self.is_event_pressed(&event, "fire_mode") {
+            self.next_fire_mode();
+        } else if self.is_event_pressed(&event, "ammo_type") {
+            self.next_ammo_type();
+        } else if self.is_event_pressed(&event, "r") {
             self.reload();
-        } else if event.is_action_released("shift")
-            || event.is_action_released("mouse_left")
-            || event.is_action_released("mouse_right")
+        } else if self.is_event_released(&event, "shift")
+            || self.is_event_released(&event, "mouse_left")
+            || self.is_event_released(&event, "mouse_right")
         {
             self.guard();
-        } else if event.is_action_pressed("q") || event.is_action_pressed("mouse_middle") {
+        } else if self.is_event_pressed(&event, "q")
+            || self.is_event_pressed(&event, "mouse_middle")
+        {
             self.throw_grenade();
-        } else if event.is_action_pressed("1") {
-            self.change_weapon(0);
-        } else if event.is_action_pressed("2") {
-            self.change_weapon(1);
-        } else if event.is_action_pressed("3") {
-            self.change_weapon(2);
-        } else if event.is_action_pressed("4") {
-            self.change_weapon(3);
-        } else if event.is_action_pressed("5") {
-            self.change_weapon(4);
-        } else if event.is_action_pressed("6") {
-            self.change_weapon(5);
-        } else if event.is_action_pressed("7") {
-            self.change_weapon(6);
-        } else if event.is_action_pressed("8") {
-            self.change_weapon(7);
-        } else if event.is_action_pressed("9") {
-            self.change_weapon(8);
-        } else if event.is_action_pressed("next_weapon") || event.is_action_pressed("mouse_side2") {
-            let mut new_weapon_index = self.current_weapon_index;
-            new_weapon_index += 1;
-            if new_weapon_index >= self.weapons.get_child_count() {
-                new_weapon_index = 0;
-            }
-            self.change_weapon(new_weapon_index);
-        } else if event.is_action_pressed("last_weapon") || event.is_action_pressed("mouse_side1") {
-            let mut new_weapon_index = self.current_weapon_index;
-            new_weapon_index -= 1;
-            if new_weapon_index < 0 {
-                new_weapon_index = self.weapons.get_child_count() - 1;
-            }
-            self.change_weapon(new_weapon_index);
-        } else if cfg!(feature = "develop") && event.is_action_pressed("p") {
+        } else if self.is_event_pressed(&event, "next_grenade") {
+            self.switch_grenade();
+        } else if self.is_event_pressed(&event, "1") {
+            self.change_weapon_slot(0);
+        } else if self.is_event_pressed(&event, "2") {
+            self.change_weapon_slot(1);
+        } else if self.is_event_pressed(&event, "3") {
+            self.change_weapon_slot(2);
+        } else if self.is_event_pressed(&event, "4") {
+            self.change_weapon_slot(3);
+        } else if self.is_event_pressed(&event, "5") {
+            self.change_weapon_slot(4);
+        } else if self.is_event_pressed(&event, "6") {
+            self.change_weapon_slot(5);
+        } else if self.is_event_pressed(&event, "7") {
+            self.change_weapon_slot(6);
+        } else if self.is_event_pressed(&event, "8") {
+            self.change_weapon_slot(7);
+        } else if self.is_event_pressed(&event, "9") {
+            self.change_weapon_slot(8);
+        } else if self.is_event_pressed(&event, "weapon_wheel") {
+            self.open_weapon_wheel();
+        } else if self.is_event_pressed(&event, "next_weapon")
+            || self.is_event_pressed(&event, "mouse_side2")
+        {
+            self.cycle_weapon(1);
+        } else if self.is_event_pressed(&event, "last_weapon")
+            || self.is_event_pressed(&event, "mouse_side1")
+        {
+            self.cycle_weapon(-1);
+        } else if cfg!(feature = "develop") && self.is_event_pressed(&event, "p") {
             self.die(Vector2::ZERO);
         }
     }
-}
 
-#[godot_api]
-impl RustPlayer {
     pub fn scale(&self) {
         self.base()
             .get_window()
@@ -343,7 +630,18 @@ impl RustPlayer {
     }
 
     pub fn on_hit(&mut self, hit_val: i64, hit_position: Vector2) {
+        if PlayerState::Downed == self.state || self.is_dodge_invincible() {
+            return;
+        }
+        let mut hit_val = self.effects.scale_damage(hit_val);
+        if hit_val > 0 {
+            hit_val = (hit_val as real * self.get_class_damage_rate()) as i64;
+        }
         if !self.invincible {
+            if hit_val > 0 {
+                RustAchievements::on_player_hurt(self.player_index);
+                CombatEventBus::emit(self.player_index, CombatEvent::Damaged(hit_val));
+            }
             let health = self.current_health;
             self.current_health = if hit_val > 0 {
                 health.saturating_sub(hit_val as u32)
@@ -366,17 +664,40 @@ impl RustPlayer {
         self.distance = 0.0;
         self.penetrate = 0.0;
         self.repel = 0.0;
+        self.crit_chance = 0.0;
+        self.crit_damage = 0.0;
         self.health = PLAYER_MAX_HEALTH;
         self.current_health = self.health;
+        self.stamina = PLAYER_MAX_STAMINA;
+        self.stamina_recover = STAMINA_RECOVER_RATE;
+        self.current_stamina = self.stamina;
+        self.exhausted = false;
         self.level_up_barrier = PLAYER_LEVEL_UP_BARRIER;
         self.current_level_up_barrier = self.level_up_barrier;
         self.score = 0;
         self.died = 0;
         self.kill_count = 0;
-        self.current_lives = self.lives.saturating_add(1);
-        self.born();
+        self.current_lives = self.lives;
+        self.perks.clear();
+        self.update_perk_hud();
+        // 职业加成和职业升级都从基础属性重新算
+        self.speed = PLAYER_MOVE_SPEED;
+        self.grenade_cooldown = GRENADE_COOLDOWN;
+        self.regen = 0.0;
+        self.regen_progress = 0.0;
+        self.grenade_damage_rate = 1.0;
+        self.assassinate_angle = ASSASSINATE_ANGLE;
+        self.left_ability_cooldown = 0.0;
+        self.left_ability_time = 0.0;
+        self.apply_class_bonus();
+        if PlayerState::Dead == self.state {
+            self.respawn();
+        }
         let rust_weapon = self.get_current_weapon();
         let mut hud = self.hud.bind_mut();
+        hud.update_hp_hud(self.current_health, self.health);
+        hud.update_speed_hud(self.current_speed);
+        hud.update_stamina_hud(self.current_stamina, self.stamina);
         hud.update_damage_hud(rust_weapon.bind().get_damage(), self.damage);
         hud.update_distance_hud(rust_weapon.bind().get_distance(), self.distance);
         hud.update_repel_hud(rust_weapon.bind().get_repel(), self.repel);
@@ -386,36 +707,17 @@ impl RustPlayer {
         hud.update_died_hud(self.died);
     }
 
-    pub fn throw_grenade(&mut self) {
-        if self.current_grenade_cooldown > 0.0 {
-            if let Some(mut grenade_cooldown_label) = self.create_message() {
-                grenade_cooldown_label.bind_mut().show_message(&format!(
-                    "GRENADE READY IN {:.1}S",
-                    self.current_grenade_cooldown
-                ));
-            }
+    // 新解锁的成就只在1P头上提示
+    fn show_achievements(&mut self) {
+        if self.player_index > 0 {
             return;
         }
-        let direction = self
-            .base()
-            .get_global_position()
-            .direction_to(self.get_mouse_position())
-            .normalized();
-        let grenade_point = self.grenade_point.get_global_position();
-        for grenade_scene in self.grenade_scenes.iter_shared() {
-            if let Some(mut grenade) = grenade_scene.try_instantiate_as::<RustGrenade>() {
-                grenade.set_global_position(grenade_point);
-                let mut gd_mut = grenade.bind_mut();
-                gd_mut.set_bullet_point(grenade_point);
-                gd_mut.set_final_distance(GRENADE_DISTANCE + self.distance);
-                gd_mut.set_final_damage(GRENADE_DAMAGE + self.damage);
-                gd_mut.set_final_repel(GRENADE_REPEL + self.repel);
-                gd_mut.set_direction(direction);
-                drop(gd_mut);
-                if let Some(mut parent) = self.base().get_parent() {
-                    parent.add_child(&grenade);
-                    self.current_grenade_cooldown = self.grenade_cooldown;
-                }
+        for achievement in RustAchievements::take_unlocked() {
+            if let Some(mut message_label) = self.create_message() {
+                message_label.bind_mut().show_message(&format!(
+                    "ACHIEVEMENT UNLOCKED: {}",
+                    achievement.get_title()
+                ));
             }
         }
     }
@@ -423,7 +725,7 @@ impl RustPlayer {
     pub fn create_message(&mut self) -> Option<Gd<RustMessage>> {
         #[allow(clippy::borrow_interior_mutable_const)]
         if let Some(mut message_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
-            message_label.set_global_position(Self::get_position());
+            message_label.set_global_position(self.base().get_global_position());
             if let Some(tree) = self.base().get_tree() {
                 if let Some(mut root) = tree.get_root() {
                     root.add_child(&message_label);
@@ -475,7 +777,11 @@ impl RustPlayer {
     }
 
     pub fn get_position() -> Vector2 {
-        POSITION.load()
+        POSITIONS[0].load()
+    }
+
+    pub fn get_position_of(index: usize) -> Vector2 {
+        POSITIONS[index].load()
     }
 
     pub fn get() -> Gd<Self> {
@@ -0,0 +1,213 @@
+use super::*;
+use crate::NETWORK_INPUT_RATE;
+use godot::classes::{InputEventAction, Node};
+use godot::meta::ToGodot;
+use godot::obj::NewGd;
+
+// 主机同步给客户端的玩家属性，包括HUD上的文字
+const SYNC_PROPERTIES: [&str; 26] = [
+    ".:position",
+    ".:rotation",
+    "AnimatedSprite2D:rotation",
+    "AnimatedSprite2D:animation",
+    "Weapon:visible",
+    "Line2D:visible",
+    "RustHUD/Control/VBoxTopLeft/Lives:text",
+    "RustHUD/Control/VBoxTopLeft/HP:text",
+    "RustHUD/Control/VBoxTopLeft/Speed:text",
+    "RustHUD/Control/VBoxTopLeft/Stamina:text",
+    "RustHUD/Control/VBoxTopLeft/Ability:text",
+    "RustHUD/Control/VBoxTopLeft/Perks:text",
+    "RustHUD/Control/VBoxTopLeft/Stealth:text",
+    "RustHUD/Control/VBoxTopLeft/Damage:text",
+    "RustHUD/Control/VBoxTopLeft/Distance:text",
+    "RustHUD/Control/VBoxTopLeft/Repel:text",
+    "RustHUD/Control/VBoxTopLeft/Penetrate:text",
+    "RustHUD/Control/VBoxTopLeft/Jitter:text",
+    "RustHUD/Control/VBoxTopLeft/Effects/Label:text",
+    "RustHUD/Control/VBoxBottomRight/Melee:text",
+    "RustHUD/Control/VBoxBottomRight/Grenade:text",
+    "RustHUD/Control/VBoxBottomRight/WeaponName:text",
+    "RustHUD/Control/VBoxBottomRight/Mastery:text",
+    "RustHUD/Control/VBoxBottomRight/FireMode:text",
+    "RustHUD/Control/VBoxBottomRight/AmmoType:text",
+    "RustHUD/Control/VBoxBottomRight/WeaponAmmo:text",
+];
+
+// 需要持续按住的动作，按固定频率发送给主机
+const HELD_ACTIONS: [&str; 8] = [
+    "move_left",
+    "move_right",
+    "move_up",
+    "move_down",
+    "mouse_left",
+    "mouse_right",
+    "shift",
+    "e",
+];
+
+// 单次触发的动作，按下和松开时立即发送给主机
+const EVENT_ACTIONS: [&str; 31] = [
+    "dodge",
+    "sneak",
+    "ability",
+    "e",
+    "parry",
+    "throw_knife",
+    "next_melee",
+    "fire_mode",
+    "ammo_type",
+    "r",
+    "shift",
+    "mouse_left",
+    "mouse_right",
+    "q",
+    "mouse_middle",
+    "next_grenade",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "next_weapon",
+    "mouse_side2",
+    "last_weapon",
+    "mouse_side1",
+    "weapon_wheel",
+    "p",
+];
+
+const NETWORK_NAME_PREFIX: &str = "RustPlayer_";
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    /// 联机玩家的节点名形如RustPlayer_编号_连接号
+    pub fn get_network_name(index: usize, peer_id: i32) -> String {
+        format!("{}{}_{}", NETWORK_NAME_PREFIX, index, peer_id)
+    }
+
+    pub fn get_peer_id(&self) -> i32 {
+        self.peer_id
+    }
+
+    pub fn init_network(&mut self) {
+        if !RustNetwork::is_online() {
+            return;
+        }
+        let name = self.base().get_name().to_string();
+        if let Some((index, peer_id)) = name
+            .strip_prefix(NETWORK_NAME_PREFIX)
+            .and_then(|suffix| suffix.split_once('_'))
+        {
+            if let (Ok(index), Ok(peer_id)) = (index.parse::<usize>(), peer_id.parse::<i32>()) {
+                self.set_player_index(index);
+                self.peer_id = peer_id;
+            }
+        }
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &SYNC_PROPERTIES);
+        if self.is_local() {
+            self.camera.make_current();
+        } else {
+            // 其他机器上的玩家不占用本机的镜头和HUD
+            self.camera.set_enabled(false);
+            self.hud.set_visible(false);
+        }
+    }
+
+    /// 由本机操作的玩家
+    pub fn is_local(&self) -> bool {
+        !RustNetwork::is_online() || self.peer_id == RustNetwork::get_local_peer_id()
+    }
+
+    /// 主机上由客户端操作的玩家
+    pub fn is_remote(&self) -> bool {
+        RustNetwork::is_authority() && !self.is_local()
+    }
+
+    // 客户端不运行玩家逻辑，只上报本地玩家的输入
+    pub fn update_client(&mut self, delta: f64) {
+        let player_position = self.base().get_global_position();
+        POSITIONS[self.player_index].store(player_position);
+        if !self.is_local() {
+            return;
+        }
+        self.update_aim();
+        self.update_weapon_wheel();
+        self.left_input_time -= delta;
+        if self.left_input_time > 0.0 {
+            return;
+        }
+        self.left_input_time = 1.0 / NETWORK_INPUT_RATE;
+        let input = Input::singleton();
+        let mut strengths = Dictionary::new();
+        for action in HELD_ACTIONS {
+            strengths.set(action, input.get_action_strength(action));
+        }
+        let aim_position = Self::get_aim_position(self.player_index);
+        self.base_mut().rpc_id(
+            1,
+            "receive_input",
+            &[strengths.to_variant(), aim_position.to_variant()],
+        );
+    }
+
+    pub fn send_action(&mut self, event: &Gd<InputEvent>) {
+        for action in EVENT_ACTIONS {
+            let pressed = if event.is_action_pressed(action) {
+                true
+            } else if event.is_action_released(action) {
+                false
+            } else {
+                continue;
+            };
+            self.base_mut().rpc_id(
+                1,
+                "receive_action",
+                &[StringName::from(action).to_variant(), pressed.to_variant()],
+            );
+        }
+    }
+
+    // 只接受该玩家所属客户端发来的输入
+    fn is_from_owner(&self) -> bool {
+        RustNetwork::is_host() && RustNetwork::get_sender_id() == self.peer_id
+    }
+
+    pub fn apply_remote_input(&mut self, strengths: Dictionary, aim_position: Vector2) {
+        if !self.is_from_owner() {
+            return;
+        }
+        self.remote_strengths.clear();
+        for (action, strength) in strengths.iter_shared() {
+            self.remote_strengths
+                .insert(action.to_string(), strength.try_to::<real>().unwrap_or(0.0));
+        }
+        self.remote_aim = aim_position;
+    }
+
+    pub fn apply_remote_action(&mut self, action: StringName, pressed: bool) {
+        if !self.is_from_owner() {
+            return;
+        }
+        let mut event = InputEventAction::new_gd();
+        event.set_action(&action);
+        event.set_pressed(pressed);
+        self.handle_input(event.upcast());
+    }
+
+    pub fn apply_weapon_choice(&mut self, weapon_index: i32) {
+        if !self.is_from_owner() || weapon_index < 0 {
+            return;
+        }
+        self.choose_weapon(Some(weapon_index));
+    }
+
+    pub fn get_remote_strength(&self, action: &str) -> real {
+        self.remote_strengths.get(action).copied().unwrap_or(0.0)
+    }
+}
@@ -0,0 +1,106 @@
+use super::*;
+use crate::combat::{CombatEvent, CombatEventBus};
+use crate::{PERK_KILL_HEAL, PERK_SHOCKWAVE_DISTANCE, PERK_SHOCKWAVE_REPEL, PERKS, Perk, is_alive};
+use rand::seq::SliceRandom;
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn init_perks(&mut self) {
+        CombatEventBus::subscribe(self.player_index);
+        self.update_perk_hud();
+    }
+
+    pub fn has_perk(&self, perk: Perk) -> bool {
+        self.perks.contains(&perk)
+    }
+
+    /// 随机获得一个还没有的天赋，已集齐时返回false
+    pub fn gain_random_perk(&mut self) -> bool {
+        let missing: Vec<Perk> = PERKS
+            .into_iter()
+            .filter(|perk| !self.has_perk(*perk))
+            .collect();
+        let Some(perk) = missing.choose(&mut rand::thread_rng()).copied() else {
+            return false;
+        };
+        self.perks.push(perk);
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(&format!(
+                "PERK {}: {}",
+                perk.get_title(),
+                perk.get_description()
+            ));
+        }
+        self.update_perk_hud();
+        true
+    }
+
+    pub fn update_perk_hud(&mut self) {
+        let titles: Vec<&str> = self.perks.iter().map(Perk::get_title).collect();
+        let all_owned = self.perks.len() >= PERKS.len();
+        let mut hud = self.hud.bind_mut();
+        hud.update_perks_hud(&titles.join(" "));
+        hud.update_perk_upgrade(!all_owned);
+    }
+
+    // 处理这一帧之前收到的战斗事件
+    pub fn update_perks(&mut self) {
+        let events = CombatEventBus::drain(self.player_index);
+        if self.perks.is_empty() || matches!(self.state, PlayerState::Downed | PlayerState::Dead) {
+            return;
+        }
+        for event in events {
+            for perk in self.perks.clone() {
+                self.trigger_perk(perk, event);
+            }
+        }
+    }
+
+    fn trigger_perk(&mut self, perk: Perk, event: CombatEvent) {
+        match (perk, event) {
+            (Perk::Vampire, CombatEvent::Kill(_)) => self.heal(PERK_KILL_HEAL),
+            (Perk::Shockwave, CombatEvent::Reload) => self.shockwave(),
+            (Perk::Recycler, CombatEvent::Headshot) => {
+                self.get_current_weapon().bind_mut().refund_ammo(1);
+            }
+            (Perk::Retaliation, CombatEvent::Damaged(_)) => self.refund_grenade(),
+            _ => {}
+        }
+    }
+
+    // 把附近的僵尸推开
+    fn shockwave(&mut self) {
+        let player_position = self.base().get_global_position();
+        let Some(tree) = self.base().get_tree() else {
+            return;
+        };
+        for node in tree.get_nodes_in_group("zombie").iter_shared() {
+            if !node.is_instance_valid() || !is_alive(&node) {
+                continue;
+            }
+            let mut zombie = node.cast::<Node2D>();
+            let zombie_position = zombie.get_global_position();
+            if player_position.distance_to(zombie_position) > PERK_SHOCKWAVE_DISTANCE {
+                continue;
+            }
+            let direction = player_position.direction_to(zombie_position);
+            zombie.set_global_position(zombie_position + direction * PERK_SHOCKWAVE_REPEL);
+        }
+    }
+
+    // 当前手雷立即冷却完毕
+    fn refund_grenade(&mut self) {
+        let index = self.current_grenade_index;
+        let Some(cooldown) = self.current_grenade_cooldowns.get_mut(index) else {
+            return;
+        };
+        if *cooldown <= 0.0 {
+            return;
+        }
+        *cooldown = 0.0;
+        self.update_grenade_hud();
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message("FREE GRENADE");
+        }
+    }
+}
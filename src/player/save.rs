@@ -20,11 +20,23 @@ impl Serialize for RustPlayer {
         state.serialize_field("distance", &self.distance)?;
         state.serialize_field("penetrate", &self.penetrate)?;
         state.serialize_field("repel", &self.repel)?;
+        state.serialize_field("crit_chance", &self.crit_chance)?;
+        state.serialize_field("crit_damage", &self.crit_damage)?;
         state.serialize_field("health", &self.health)?;
         state.serialize_field("speed", &self.speed)?;
+        state.serialize_field("stamina", &self.stamina)?;
+        state.serialize_field("stamina_recover", &self.stamina_recover)?;
+        state.serialize_field("current_stamina", &self.current_stamina)?;
         state.serialize_field("level_up_barrier", &self.level_up_barrier)?;
         state.serialize_field("grenade_cooldown", &self.grenade_cooldown)?;
+        state.serialize_field("current_grenade_index", &self.current_grenade_index)?;
         state.serialize_field("chop_cooldown", &self.chop_cooldown)?;
+        state.serialize_field("player_class", &self.player_class)?;
+        state.serialize_field("regen", &self.regen)?;
+        state.serialize_field("grenade_damage_rate", &self.grenade_damage_rate)?;
+        state.serialize_field("assassinate_angle", &self.assassinate_angle)?;
+        state.serialize_field("melee_weapon", &self.melee_weapon)?;
+        state.serialize_field("perks", &self.perks)?;
         state.serialize_field("current_level_up_barrier", &self.current_level_up_barrier)?;
         state.serialize_field("current_lives", &self.current_lives)?;
         state.serialize_field("current_health", &self.current_health)?;
@@ -33,6 +45,7 @@ impl Serialize for RustPlayer {
         state.serialize_field("score", &self.score)?;
         state.serialize_field("died", &self.died)?;
         state.serialize_field("kill_count", &self.kill_count)?;
+        state.serialize_field("ammo_reserves", &AmmoType::get_reserves(self.player_index))?;
         state.end()
     }
 }
@@ -48,11 +61,35 @@ struct PlayerData {
     distance: real,
     penetrate: real,
     repel: real,
+    #[serde(default)]
+    crit_chance: real,
+    #[serde(default)]
+    crit_damage: real,
     health: u32,
     speed: real,
+    #[serde(default = "default_stamina")]
+    stamina: real,
+    #[serde(default = "default_stamina_recover")]
+    stamina_recover: real,
+    #[serde(default = "default_stamina")]
+    current_stamina: real,
     level_up_barrier: u32,
     grenade_cooldown: real,
+    #[serde(default)]
+    current_grenade_index: usize,
     chop_cooldown: real,
+    #[serde(default)]
+    player_class: PlayerClass,
+    #[serde(default)]
+    regen: real,
+    #[serde(default = "default_grenade_damage_rate")]
+    grenade_damage_rate: real,
+    #[serde(default = "default_assassinate_angle")]
+    assassinate_angle: real,
+    #[serde(default)]
+    melee_weapon: MeleeWeapon,
+    #[serde(default)]
+    perks: Vec<Perk>,
     current_level_up_barrier: u32,
     current_lives: u32,
     current_health: u32,
@@ -61,12 +98,36 @@ struct PlayerData {
     score: u32,
     died: u32,
     kill_count: u32,
+    #[serde(default)]
+    ammo_reserves: Vec<(AmmoType, u32)>,
+}
+
+// 兼容没有体力数据的旧存档
+fn default_stamina() -> real {
+    PLAYER_MAX_STAMINA
+}
+
+fn default_stamina_recover() -> real {
+    STAMINA_RECOVER_RATE
+}
+
+// 兼容没有职业数据的旧存档
+fn default_grenade_damage_rate() -> real {
+    1.0
+}
+
+fn default_assassinate_angle() -> real {
+    ASSASSINATE_ANGLE
 }
 
 #[godot_api(secondary)]
 impl RustPlayer {
     #[func]
     pub fn on_save(&self) {
+        if self.player_index > 0 {
+            // 存档只记录1P
+            return;
+        }
         let name = self.base().get_class().to_string();
         let data = serde_json::to_string(&self).unwrap();
         SAVE.insert(name, HashSet::from([data]));
@@ -74,6 +135,9 @@ impl RustPlayer {
 
     #[func]
     pub fn on_load(&mut self) {
+        if self.player_index > 0 {
+            return;
+        }
         let name = self.base().get_class().to_string();
         if let Some((_, vec)) = SAVE.remove(&name) {
             let json = vec.iter().next().unwrap();
@@ -88,11 +152,27 @@ impl RustPlayer {
                 self.distance = save_data.distance;
                 self.penetrate = save_data.penetrate;
                 self.repel = save_data.repel;
+                self.crit_chance = save_data.crit_chance;
+                self.crit_damage = save_data.crit_damage;
                 self.health = save_data.health;
                 self.speed = save_data.speed;
+                self.stamina = save_data.stamina;
+                self.stamina_recover = save_data.stamina_recover;
+                self.current_stamina = save_data.current_stamina;
                 self.level_up_barrier = save_data.level_up_barrier;
                 self.grenade_cooldown = save_data.grenade_cooldown;
+                self.current_grenade_index = save_data.current_grenade_index;
                 self.chop_cooldown = save_data.chop_cooldown;
+                if save_data.player_class != self.player_class {
+                    // 职业不同时按存档的职业重新发放手雷
+                    self.grenade_scenes.clear();
+                }
+                self.player_class = save_data.player_class;
+                self.regen = save_data.regen;
+                self.grenade_damage_rate = save_data.grenade_damage_rate;
+                self.assassinate_angle = save_data.assassinate_angle;
+                self.melee_weapon = save_data.melee_weapon;
+                self.perks = save_data.perks;
                 self.current_level_up_barrier = save_data.current_level_up_barrier;
                 self.current_lives = save_data.current_lives;
                 self.current_health = save_data.current_health;
@@ -102,6 +182,10 @@ impl RustPlayer {
                 self.died = save_data.died;
                 self.kill_count = save_data.kill_count;
                 self.ready();
+                // ready会重置备弹，之后再恢复存档里的备弹
+                for (ammo_type, reserve) in save_data.ammo_reserves {
+                    ammo_type.set_reserve(self.player_index, reserve);
+                }
             }
         }
     }
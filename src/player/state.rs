@@ -1,9 +1,14 @@
 use super::*;
-use crate::{NO_NOISE, PlayerState, random_bool};
+use crate::{
+    ACCURACY_SPRINT_TIME, BLEED_OUT_TIME, DODGE_INVINCIBLE_TIME, DODGE_SPEED_RATE,
+    DODGE_STAMINA_COST, MAX_PLAYERS, NO_NOISE, PlayerState, REVIVE_HEALTH_RATE, SNEAK_SPEED_RATE,
+    STAMINA_RECOVER_BARRIER, STAMINA_RUN_COST, random_bool,
+};
 use godot::builtin::Callable;
 use godot::meta::ToGodot;
 
-static STATE: AtomicCell<PlayerState> = AtomicCell::new(PlayerState::Born);
+static STATES: [AtomicCell<PlayerState>; MAX_PLAYERS] =
+    [const { AtomicCell::new(PlayerState::Born) }; MAX_PLAYERS];
 
 static NOISE_POSITION: AtomicCell<Vector2> = AtomicCell::new(NO_NOISE);
 
@@ -11,17 +16,20 @@ static NOISE_POSITION: AtomicCell<Vector2> = AtomicCell::new(NO_NOISE);
 impl RustPlayer {
     #[func]
     pub fn born(&mut self) {
-        if PlayerState::Dead != self.state || 0 == self.current_lives {
+        if PlayerState::Dead != self.state || !self.take_life() {
             return;
         }
-        self.current_lives -= 1;
+        self.respawn();
+    }
+
+    pub fn respawn(&mut self) {
         self.weapons.set_visible(true);
         self.line2d.set_visible(true);
         self.animated_sprite2d.play_ex().name("guard").done();
-        self.current_speed = self.speed * self.get_current_weapon().bind().get_weight();
+        self.current_speed = self.speed * self.get_weapon_weight();
         self.state = PlayerState::Born;
         self.current_health = self.health;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.get_current_weapon().bind_mut().weapon_ready();
         self.hud
             .bind_mut()
@@ -35,19 +43,21 @@ impl RustPlayer {
     #[func]
     pub fn guard(&mut self) {
         if PlayerState::Dead == self.state
+            || PlayerState::Downed == self.state
             || PlayerState::Impact == self.state
             || PlayerState::Reload == self.state
             || PlayerState::Reloading == self.state
             || PlayerState::Chop == self.state
+            || PlayerState::Dodge == self.state
         {
             return;
         }
         self.weapons.set_visible(true);
         self.line2d.set_visible(true);
         self.animated_sprite2d.play_ex().name("guard").done();
-        self.current_speed = self.speed * self.get_current_weapon().bind().get_weight();
+        self.current_speed = self.speed * self.get_weapon_weight();
         self.state = PlayerState::Guard;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.get_current_weapon().bind_mut().weapon_ready();
         self.hud.bind_mut().update_speed_hud(self.current_speed);
     }
@@ -56,15 +66,20 @@ impl RustPlayer {
         if PlayerState::Dead == self.state
             || PlayerState::Impact == self.state
             || PlayerState::Chop == self.state
+            || PlayerState::Dodge == self.state
+            || self.exhausted
         {
             return;
         }
+        //奔跑会退出潜行
+        self.sneaking = false;
+        self.left_sprint_time = ACCURACY_SPRINT_TIME;
         self.weapons.set_visible(false);
         self.line2d.set_visible(false);
         self.animated_sprite2d.play_ex().name("run").done();
-        self.current_speed = self.speed * 1.5 * self.get_current_weapon().bind().get_weight();
+        self.current_speed = self.speed * 1.5 * self.get_weapon_weight();
         self.state = PlayerState::Run;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.get_current_weapon().bind_mut().weapon_ready();
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         //打断换弹
@@ -87,10 +102,35 @@ impl RustPlayer {
         }
     }
 
+    // 潜行移动不会发出脚步声
+    pub fn sneak(&mut self) {
+        if PlayerState::Dead == self.state
+            || PlayerState::Impact == self.state
+            || PlayerState::Reload == self.state
+            || PlayerState::Reloading == self.state
+            || PlayerState::Chop == self.state
+            || PlayerState::Dodge == self.state
+        {
+            return;
+        }
+        self.weapons.set_visible(true);
+        self.line2d.set_visible(true);
+        self.animated_sprite2d.play_ex().name("guard").done();
+        self.current_speed = self.speed * SNEAK_SPEED_RATE * self.get_weapon_weight();
+        self.state = PlayerState::Sneak;
+        STATES[self.player_index].store(self.state);
+        self.get_current_weapon().bind_mut().weapon_ready();
+        self.hud.bind_mut().update_speed_hud(self.current_speed);
+        if self.run_audio.is_playing() {
+            self.run_audio.stop();
+        }
+    }
+
     pub fn shoot(&mut self) {
         if PlayerState::Dead == self.state
             || PlayerState::Impact == self.state
             || PlayerState::Chop == self.state
+            || PlayerState::Dodge == self.state
         {
             return;
         }
@@ -103,42 +143,57 @@ impl RustPlayer {
         rust_weapon.set_visible(true);
         self.line2d.set_visible(true);
         self.animated_sprite2d.play_ex().name("guard").done();
-        self.current_speed = self.speed * 0.5 * rust_weapon.bind().get_weight();
+        self.current_speed = self.speed * 0.5 * self.get_weapon_weight();
         self.state = PlayerState::Shoot;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         //打断正在持续的换弹
         rust_weapon.bind_mut().stop_reload();
-        rust_weapon
-            .bind_mut()
-            .fire(self.damage, self.distance, self.penetrate, self.repel);
+        let damage = self.get_class_damage();
+        RustAchievements::on_ranged_used();
+        let mut gd_mut = rust_weapon.bind_mut();
+        gd_mut.set_crit_bonus(self.crit_chance, self.crit_damage);
+        gd_mut.pull_trigger(damage, self.distance, self.penetrate, self.repel);
     }
 
     pub fn headshot(&mut self) {
         self.headshot_audio.play();
+        CombatEventBus::emit(self.player_index, CombatEvent::Headshot);
     }
 
-    pub fn chop(&mut self) {
+    pub fn chop(&mut self, heavy: bool) {
         if PlayerState::Dead == self.state
             || PlayerState::Impact == self.state
+            || PlayerState::Dodge == self.state
             || self.current_chop_cooldown > 0.0
+            || !self.knife.bind().is_ready()
         {
             return;
         }
-        self.current_chop_cooldown = self.chop_cooldown as f64;
+        let cooldown_rate = self.melee_weapon.get_data().cooldown_rate;
+        self.current_chop_cooldown = (self.chop_cooldown * cooldown_rate) as f64;
+        let combo_step = self.next_combo_step(heavy);
+        self.start_melee_state();
+        let damage = self.damage;
+        let repel = self.repel;
+        let assassinate_angle = self.assassinate_angle;
+        self.knife
+            .bind_mut()
+            .chop(damage, repel, assassinate_angle, combo_step, heavy);
+    }
+
+    // 挥砍和格挡时收起枪
+    pub fn start_melee_state(&mut self) {
         self.weapons.set_visible(false);
         self.line2d.set_visible(false);
         self.animated_sprite2d.play_ex().name("chop").done();
         self.current_speed = self.speed * 0.75;
         self.state = PlayerState::Chop;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.get_current_weapon().bind_mut().weapon_ready();
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         //打断换弹
         self.get_current_weapon().bind_mut().stop_reload();
-        let damage = 80 + self.damage;
-        let repel = 30.0 + self.repel;
-        self.knife.bind_mut().chop(damage, repel);
     }
 
     #[func]
@@ -156,6 +211,7 @@ impl RustPlayer {
             || PlayerState::Impact == self.state
             || PlayerState::Reload == self.state
             || PlayerState::Chop == self.state
+            || PlayerState::Dodge == self.state
             || !rust_weapon.bind_mut().reload()
         {
             return;
@@ -163,9 +219,9 @@ impl RustPlayer {
         self.weapons.set_visible(true);
         self.line2d.set_visible(false);
         self.animated_sprite2d.play_ex().name("reload").done();
-        self.current_speed = self.speed * 0.75 * rust_weapon.bind().get_weight();
+        self.current_speed = self.speed * 0.75 * self.get_weapon_weight();
         self.state = PlayerState::Reload;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.hud.bind_mut().update_speed_hud(self.current_speed);
     }
 
@@ -175,9 +231,9 @@ impl RustPlayer {
             return;
         }
         self.animated_sprite2d.play_ex().name("reload").done();
-        self.current_speed = self.speed * 0.75 * self.get_current_weapon().bind().get_weight();
+        self.current_speed = self.speed * 0.75 * self.get_weapon_weight();
         self.state = PlayerState::Reloading;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.hud.bind_mut().update_speed_hud(self.current_speed);
     }
 
@@ -197,7 +253,7 @@ impl RustPlayer {
         self.weapons.set_visible(false);
         self.line2d.set_visible(false);
         self.animated_sprite2d.play_ex().name("hit").done();
-        self.current_speed = self.speed * 0.5 * self.get_current_weapon().bind().get_weight();
+        self.current_speed = self.speed * 0.5 * self.get_weapon_weight();
         self.state = PlayerState::Hit;
         let player_position = self.base().get_global_position();
         self.blood_flash.set_global_position(
@@ -205,7 +261,7 @@ impl RustPlayer {
         );
         self.blood_flash.look_at(hit_position);
         self.blood_flash.restart();
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         if random_bool() {
             self.body_hurt.play();
@@ -218,11 +274,14 @@ impl RustPlayer {
     }
 
     pub fn on_impact(&mut self, hit_val: i64, impact_position: Vector2) {
-        if PlayerState::Dead == self.state {
+        if PlayerState::Dead == self.state
+            || PlayerState::Downed == self.state
+            || self.is_dodge_invincible()
+        {
             return;
         }
         self.on_hit(hit_val, impact_position);
-        if PlayerState::Dead == self.state {
+        if matches!(self.state, PlayerState::Dead | PlayerState::Downed) {
             return;
         }
         self.weapons.set_visible(false);
@@ -238,7 +297,7 @@ impl RustPlayer {
         self.blood_flash.set_one_shot(false);
         self.blood_flash.set_emitting(true);
         self.blood_flash.restart();
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.get_current_weapon().bind_mut().weapon_ready();
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         //打断正在持续的换弹
@@ -253,7 +312,7 @@ impl RustPlayer {
         self.animated_sprite2d.play_ex().name("bump").done();
         self.current_speed = self.speed * 1.25;
         self.state = PlayerState::Impact;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         let hit_position = self.impact_position;
         self.base_mut().look_at(hit_position);
@@ -267,11 +326,124 @@ impl RustPlayer {
             return;
         }
         self.state = PlayerState::Guard;
+        self.clear_impact();
+        self.guard();
+    }
+
+    fn clear_impact(&mut self) {
         self.blood_flash.set_emitting(false);
         self.blood_flash.set_one_shot(true);
         self.blood_flash.restart();
         self.impact_position = Vector2::ZERO;
         self.left_impact_time = 0.0;
+    }
+
+    pub fn dodge(&mut self) {
+        if PlayerState::Dead == self.state || PlayerState::Dodge == self.state {
+            return;
+        }
+        if self.current_stamina < DODGE_STAMINA_COST {
+            if let Some(mut stamina_label) = self.create_message() {
+                stamina_label.bind_mut().show_message("NOT ENOUGH STAMINA");
+            }
+            return;
+        }
+        if PlayerState::Impact == self.state {
+            //翻滚可以挣脱撞击
+            self.clear_impact();
+        }
+        self.current_stamina -= DODGE_STAMINA_COST;
+        let move_direction =
+            self.get_action_vector("move_left", "move_right", "move_up", "move_down");
+        // 没有移动时朝瞄准方向翻滚
+        self.dodge_direction = if Vector2::ZERO != move_direction {
+            move_direction.normalized()
+        } else {
+            self.get_current_direction()
+        };
+        self.left_dodge_time = 0.0;
+        self.weapons.set_visible(false);
+        self.line2d.set_visible(false);
+        self.animated_sprite2d.play_ex().name("run").done();
+        self.current_speed = self.speed * DODGE_SPEED_RATE;
+        self.state = PlayerState::Dodge;
+        STATES[self.player_index].store(self.state);
+        self.get_current_weapon().bind_mut().weapon_ready();
+        self.hud.bind_mut().update_speed_hud(self.current_speed);
+        //打断换弹
+        self.get_current_weapon().bind_mut().stop_reload();
+        if !self.run_audio.is_playing() {
+            self.run_audio.play();
+        }
+    }
+
+    pub fn dodged(&mut self) {
+        if PlayerState::Dodge != self.state {
+            return;
+        }
+        self.state = PlayerState::Guard;
+        self.dodge_direction = Vector2::ZERO;
+        self.left_dodge_time = 0.0;
+        self.guard();
+    }
+
+    pub fn is_dodge_invincible(&self) -> bool {
+        PlayerState::Dodge == self.state && self.left_dodge_time < DODGE_INVINCIBLE_TIME
+    }
+
+    pub fn update_stamina(&mut self, delta: f64) {
+        match self.state {
+            PlayerState::Run => {
+                self.current_stamina -= STAMINA_RUN_COST * delta as real;
+                if self.current_stamina <= 0.0 {
+                    self.current_stamina = 0.0;
+                    self.exhausted = true;
+                    self.guard();
+                }
+            }
+            PlayerState::Guard | PlayerState::Sneak => {
+                self.current_stamina =
+                    (self.current_stamina + self.stamina_recover * delta as real).min(self.stamina);
+            }
+            _ => {}
+        }
+        if self.exhausted && self.current_stamina >= self.stamina * STAMINA_RECOVER_BARRIER {
+            self.exhausted = false;
+        }
+        self.hud
+            .bind_mut()
+            .update_stamina_hud(self.current_stamina, self.stamina);
+    }
+
+    // 双人时生命值归零先倒地，等待队友救援
+    fn down(&mut self, hit_position: Vector2) {
+        self.weapons.set_visible(false);
+        self.line2d.set_visible(false);
+        self.animated_sprite2d.look_at(hit_position);
+        self.animated_sprite2d.play_ex().name("die").done();
+        self.current_speed = 0.0;
+        self.state = PlayerState::Downed;
+        STATES[self.player_index].store(self.state);
+        self.sneaking = false;
+        self.clear_effects();
+        self.hud.bind_mut().update_speed_hud(self.current_speed);
+        //打断换弹
+        self.get_current_weapon().bind_mut().stop_reload();
+        self.scream_audio.play();
+        self.left_bleed_time = BLEED_OUT_TIME;
+        self.revive_progress = 0.0;
+    }
+
+    pub fn revive(&mut self) {
+        if PlayerState::Downed != self.state {
+            return;
+        }
+        self.current_health = ((self.health as real * REVIVE_HEALTH_RATE) as u32).max(1);
+        self.hud
+            .bind_mut()
+            .update_hp_hud(self.current_health, self.health);
+        self.revive_progress = 0.0;
+        self.state = PlayerState::Guard;
         self.guard();
     }
 
@@ -279,19 +451,29 @@ impl RustPlayer {
         if PlayerState::Dead == self.state {
             return;
         }
+        if self.can_be_downed() {
+            self.down(hit_position);
+            return;
+        }
         self.weapons.set_visible(false);
         self.line2d.set_visible(false);
         self.animated_sprite2d.look_at(hit_position);
         self.animated_sprite2d.play_ex().name("die").done();
         self.current_speed = 0.0;
         self.state = PlayerState::Dead;
-        STATE.store(self.state);
+        STATES[self.player_index].store(self.state);
+        self.sneaking = false;
+        self.clear_effects();
         self.hud.bind_mut().update_speed_hud(self.current_speed);
         //打断换弹
         self.get_current_weapon().bind_mut().stop_reload();
         self.die_audio.play();
         self.died += 1;
-        if 0 == self.current_lives {
+        if !self.has_lives() {
+            if !self.is_team_out() {
+                // 还有队友存活，等待继续游戏时复活
+                return;
+            }
             if let Some(tree) = self.base().get_tree() {
                 if let Some(root) = tree.get_root() {
                     root.get_node_as::<RustWorld>("RustWorld")
@@ -310,7 +492,11 @@ impl RustPlayer {
     }
 
     pub fn get_state() -> PlayerState {
-        STATE.load()
+        STATES[0].load()
+    }
+
+    pub fn get_state_of(index: usize) -> PlayerState {
+        STATES[index].load()
     }
 
     pub fn get_noise_position() -> Option<Vector2> {
@@ -0,0 +1,52 @@
+use super::*;
+use crate::world::ground::RustGround;
+use crate::{BUSH_VISIBILITY_RATE, MAX_PLAYERS, RUN_VISIBILITY_RATE, SNEAK_VISIBILITY_RATE};
+
+static VISIBILITIES: [AtomicCell<real>; MAX_PLAYERS] =
+    [const { AtomicCell::new(1.0) }; MAX_PLAYERS];
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn toggle_sneak(&mut self) {
+        if PlayerState::Dead == self.state {
+            return;
+        }
+        self.sneaking = !self.sneaking;
+        if !self.sneaking && PlayerState::Sneak == self.state {
+            self.guard();
+        }
+        if let Some(mut sneak_label) = self.create_message() {
+            sneak_label.bind_mut().show_message(if self.sneaking {
+                "SNEAK ON"
+            } else {
+                "SNEAK OFF"
+            });
+        }
+    }
+
+    // 可见度越低，僵尸发现玩家的距离越近
+    pub fn update_visibility(&mut self) {
+        let player_position = self.base().get_global_position();
+        let in_bush = RustGround::is_in_bush(player_position);
+        let mut visibility = match self.state {
+            PlayerState::Run => RUN_VISIBILITY_RATE,
+            PlayerState::Guard | PlayerState::Sneak if self.sneaking => SNEAK_VISIBILITY_RATE,
+            _ => 1.0,
+        };
+        if in_bush {
+            visibility *= BUSH_VISIBILITY_RATE;
+        }
+        visibility *= self.get_class_visibility_rate();
+        VISIBILITIES[self.player_index].store(visibility);
+        let mut color = self.animated_sprite2d.get_self_modulate();
+        color.a = if in_bush { 0.6 } else { 1.0 };
+        self.animated_sprite2d.set_self_modulate(color);
+        self.hud
+            .bind_mut()
+            .update_stealth_hud(visibility, self.sneaking, in_bush);
+    }
+
+    pub fn get_visibility(index: usize) -> real {
+        VISIBILITIES[index].load()
+    }
+}
@@ -1,5 +1,8 @@
 use super::*;
-use crate::{PLAYER_LEVEL_UP_GROW_RATE, PlayerUpgrade};
+use crate::{
+    CRIT_CHANCE_UPGRADE, CRIT_DAMAGE_UPGRADE, PLAYER_LEVEL_UP_GROW_RATE, PLAYER_MAX_CRIT_CHANCE,
+    PlayerClass, PlayerUpgrade,
+};
 
 #[godot_api(secondary)]
 impl RustPlayer {
@@ -15,7 +18,9 @@ impl RustPlayer {
         self.level_up_barrier = (self.level_up_barrier as real * PLAYER_LEVEL_UP_GROW_RATE) as u32;
         self.current_level_up_barrier += self.level_up_barrier;
         RustWorld::pause();
-        self.hud.bind_mut().set_upgrade_visible(true);
+        let mut hud = self.hud.bind_mut();
+        hud.update_crit_chance_upgrade(self.crit_chance < PLAYER_MAX_CRIT_CHANCE);
+        hud.set_upgrade_visible(true);
     }
 
     #[func]
@@ -84,6 +89,67 @@ impl RustPlayer {
         self.show_upgrade_label(PlayerUpgrade::Health);
     }
 
+    #[func]
+    pub fn upgrade_stamina(&mut self) {
+        //体力上限升级
+        self.stamina += 10.0;
+        self.current_stamina = self.stamina;
+        self.hud
+            .bind_mut()
+            .update_stamina_hud(self.current_stamina, self.stamina);
+        self.show_upgrade_label(PlayerUpgrade::Stamina);
+    }
+
+    #[func]
+    pub fn upgrade_recover(&mut self) {
+        //体力恢复升级
+        self.stamina_recover += 3.0;
+        self.show_upgrade_label(PlayerUpgrade::Recover);
+    }
+
+    #[func]
+    pub fn upgrade_crit_chance(&mut self) {
+        //暴击率升级
+        self.crit_chance = (self.crit_chance + CRIT_CHANCE_UPGRADE).min(PLAYER_MAX_CRIT_CHANCE);
+        self.show_upgrade_label(PlayerUpgrade::CritChance);
+    }
+
+    #[func]
+    pub fn upgrade_crit_damage(&mut self) {
+        //暴击伤害升级
+        self.crit_damage += CRIT_DAMAGE_UPGRADE;
+        self.show_upgrade_label(PlayerUpgrade::CritDamage);
+    }
+
+    #[func]
+    pub fn upgrade_class(&mut self) {
+        //职业专属升级
+        match self.player_class {
+            PlayerClass::Survivor => {}
+            PlayerClass::Medic => self.regen += 0.5,
+            PlayerClass::Demolitions => self.grenade_damage_rate += 0.2,
+            PlayerClass::Scout => {
+                self.assassinate_angle = (self.assassinate_angle + 10.0).min(150.0);
+            }
+            PlayerClass::Heavy => {
+                self.repel += 3.0;
+                let weapon_repel = self.get_current_weapon().bind().get_repel();
+                self.hud
+                    .bind_mut()
+                    .update_repel_hud(weapon_repel, self.repel);
+            }
+        }
+        self.show_upgrade_label(PlayerUpgrade::Class);
+    }
+
+    #[func]
+    pub fn upgrade_perk(&mut self) {
+        //随机天赋
+        self.gain_random_perk();
+        self.hud.bind_mut().set_upgrade_visible(false);
+        RustWorld::resume();
+    }
+
     fn show_upgrade_label(&mut self, what: PlayerUpgrade) {
         self.hud.bind_mut().set_upgrade_visible(false);
         if let Some(mut level_up_label) = self.create_message() {
@@ -1,7 +1,12 @@
 use super::*;
+use crate::Attachment;
+use crate::loadout::WeaponSlot;
+use crate::mastery::WeaponMastery;
 use crate::weapon::RustWeapon;
+use crate::weapon::definition::{WeaponDefinition, WeaponUnlock};
 use godot::classes::{DirAccess, Texture2D};
 use godot::global::godot_warn;
+use godot::tools::try_load;
 use std::collections::HashMap;
 
 #[allow(clippy::declare_interior_mutable_const)]
@@ -47,39 +52,17 @@ impl RustPlayer {
                 let mut weapon = node.cast::<RustWeapon>();
                 if weapon_index == i {
                     weapon.set_visible(true);
-                    self.ray_cast2d
-                        .set_target_position(Vector2::new(weapon.bind().get_distance(), 0.0));
-                    let mut hud = self.hud.bind_mut();
-                    let weapon_name = weapon.get_name().to_upper();
-                    hud.update_weapon_name_hud(&if weapon.bind().get_silenced() {
-                        format!("SILENCED {}", weapon_name)
-                    } else {
-                        weapon_name.to_string()
-                    });
-                    #[allow(clippy::borrow_interior_mutable_const)]
-                    if let Some(weapon_texture) = WEAPON_TEXTURE.get(&weapon_name) {
-                        hud.update_weapon_sprite_hud(weapon_texture);
-                    } else {
-                        hud.update_weapon_sprite_hud(Gd::null_arg());
-                        godot_warn!("Weapon texture not found for: {}", weapon_name);
-                    }
                     if weapon_index != self.current_weapon_index {
-                        self.camera.set_zoom(Vector2::new(1.0, 1.0));
+                        self.set_scope_zoom(Vector2::new(1.0, 1.0));
                         weapon.bind_mut().deploy();
                     }
                     weapon.bind_mut().weapon_ready();
-                    // 更新HUD
-                    weapon.bind_mut().update_ammo_hud();
-                    hud.update_speed_hud(self.current_speed);
-                    hud.update_damage_hud(weapon.bind().get_damage(), self.damage);
-                    hud.update_distance_hud(weapon.bind().get_distance(), self.distance);
-                    hud.update_repel_hud(weapon.bind().get_repel(), self.repel);
-                    hud.update_penetrate_hud(weapon.bind().get_penetrate(), self.penetrate);
-                    weapon.bind().update_jitter_hud();
+                    self.update_weapon_hud(&weapon);
                 } else {
                     weapon.set_visible(false);
-                    // 打断其他武器的换弹
+                    // 打断其他武器的换弹和点射
                     weapon.bind_mut().stop_reload();
+                    weapon.bind_mut().cease_fire();
                 }
             }
         }
@@ -95,6 +78,167 @@ impl RustPlayer {
         self.change_success_audio.play();
     }
 
+    /// 已拥有武器按自定义顺序排列的下标，未解锁的武器不在其中
+    pub fn get_weapon_slots(&self) -> Vec<i32> {
+        let mut slots: Vec<(i32, u32)> = self
+            .weapons
+            .get_children()
+            .iter_shared()
+            .enumerate()
+            .map(|(i, weapon)| {
+                (
+                    i as i32,
+                    WeaponSlot::get(&weapon.get_name().to_string()).order,
+                )
+            })
+            .collect();
+        slots.sort_by_key(|(_, order)| *order);
+        slots.into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// 数字键按顺序选第几把已拥有的武器
+    pub fn change_weapon_slot(&mut self, slot: usize) {
+        if let Some(weapon_index) = self.get_weapon_slots().get(slot) {
+            self.change_weapon(*weapon_index);
+        } else if let Some(mut locked_label) = self.create_message() {
+            locked_label
+                .bind_mut()
+                .show_message(&format!("WEAPON {} LOCKED", slot + 1));
+        }
+    }
+
+    // 收藏了两把以上时只在收藏里循环，否则在所有已拥有的武器里循环
+    pub fn cycle_weapon(&mut self, step: i32) {
+        let slots = self.get_weapon_slots();
+        let favorites: Vec<i32> = slots
+            .iter()
+            .copied()
+            .filter(|i| self.is_favorite_weapon(*i))
+            .collect();
+        let cycle = if favorites.len() >= 2 {
+            favorites
+        } else {
+            slots
+        };
+        if cycle.is_empty() {
+            return;
+        }
+        let count = cycle.len() as i32;
+        let position = match cycle.iter().position(|i| *i == self.current_weapon_index) {
+            Some(position) => (position as i32 + step).rem_euclid(count),
+            // 当前武器不在收藏里时从头或从尾开始
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        self.change_weapon(cycle[position as usize]);
+    }
+
+    pub fn is_favorite_weapon(&self, weapon_index: i32) -> bool {
+        self.weapons
+            .get_child(weapon_index)
+            .is_some_and(|weapon| WeaponSlot::get(&weapon.get_name().to_string()).favorite)
+    }
+
+    /// 切换武器或更换配件后刷新武器相关的HUD
+    pub fn update_weapon_hud(&mut self, weapon: &Gd<RustWeapon>) {
+        let weapon_distance = weapon.bind().get_distance();
+        self.ray_cast2d
+            .set_target_position(Vector2::new(weapon_distance, 0.0));
+        self.update_laser_style(weapon.bind().has_attachment(Attachment::Laser));
+        let mut hud = self.hud.bind_mut();
+        let weapon_name = weapon.get_name().to_upper();
+        hud.update_weapon_name_hud(&if weapon.bind().get_silenced() {
+            format!("SILENCED {}", weapon_name)
+        } else {
+            weapon_name.to_string()
+        });
+        #[allow(clippy::borrow_interior_mutable_const)]
+        if let Some(weapon_texture) = WEAPON_TEXTURE.get(&weapon_name) {
+            hud.update_weapon_sprite_hud(weapon_texture);
+        } else if let Some(weapon_texture) = WeaponDefinition::get(&weapon_name.to_string())
+            .and_then(|definition| definition.sprite.as_ref())
+            .and_then(|sprite| try_load::<Texture2D>(&sprite.texture).ok())
+        {
+            // 自制武器没有HUD图标时用武器贴图代替
+            hud.update_weapon_sprite_hud(&weapon_texture);
+        } else {
+            hud.update_weapon_sprite_hud(Gd::null_arg());
+            godot_warn!("Weapon texture not found for: {}", weapon_name);
+        }
+        // 更新HUD
+        weapon.bind().update_ammo_hud();
+        weapon.bind().update_fire_mode_hud();
+        weapon.bind().update_ammo_type_hud();
+        weapon.bind().update_mechanic_hud();
+        hud.update_speed_hud(self.current_speed);
+        hud.update_damage_hud(weapon.bind().get_damage(), self.damage);
+        hud.update_distance_hud(weapon_distance, self.distance);
+        hud.update_repel_hud(weapon.bind().get_repel(), self.repel);
+        hud.update_penetrate_hud(weapon.bind().get_penetrate(), self.penetrate);
+        weapon.bind().update_jitter_hud();
+        let mastery = WeaponMastery::get(&weapon_name.to_string());
+        let (_, color) = WeaponMastery::get_variant(mastery.get_tier());
+        hud.update_mastery_hud(&mastery.get_progress_text(), color);
+        self.shown_mastery = mastery;
+    }
+
+    pub fn next_fire_mode(&mut self) {
+        let mut weapon = self.get_current_weapon();
+        let fire_mode = weapon.bind_mut().next_fire_mode();
+        if fire_mode.is_some() {
+            return;
+        }
+        if let Some(mut message_label) = self.create_message() {
+            message_label.bind_mut().show_message(&format!(
+                "{} HAS ONLY ONE FIRE MODE",
+                weapon.get_name().to_upper()
+            ));
+        }
+    }
+
+    /// 熟练度变化后刷新HUD，升级的武器重新计算属性
+    pub fn update_mastery(&mut self) {
+        let version = WeaponMastery::get_version();
+        if version == self.mastery_version {
+            // 没有升级时只刷新手上武器的进度
+            let weapon_name = self.get_current_weapon().get_name().to_upper();
+            let mastery = WeaponMastery::get(&weapon_name.to_string());
+            if mastery != self.shown_mastery {
+                self.shown_mastery = mastery;
+                let (_, color) = WeaponMastery::get_variant(mastery.get_tier());
+                self.hud
+                    .bind_mut()
+                    .update_mastery_hud(&mastery.get_progress_text(), color);
+            }
+            return;
+        }
+        self.mastery_version = version;
+        if 0 == self.player_index {
+            for weapon_name in WeaponMastery::take_tier_ups() {
+                let tier = WeaponMastery::get(&weapon_name).get_tier();
+                let (variant, _) = WeaponMastery::get_variant(tier);
+                if let Some(mut message_label) = self.create_message() {
+                    message_label.bind_mut().show_message(&format!(
+                        "{} MASTERY {} {}: {}",
+                        weapon_name,
+                        tier,
+                        variant,
+                        WeaponMastery::get_tier_bonus(tier)
+                    ));
+                }
+            }
+        }
+        for weapon in self.weapons.get_children().iter_shared() {
+            let mut weapon = weapon.cast::<RustWeapon>();
+            let tier = WeaponMastery::get(&weapon.get_name().to_string()).get_tier();
+            if weapon.bind().get_mastery_tier() != tier {
+                weapon.bind_mut().refresh_stats();
+            }
+        }
+        let weapon = self.get_current_weapon();
+        self.update_weapon_hud(&weapon);
+    }
+
     pub fn update_laser(&mut self) {
         if self.ray_cast2d.is_colliding() {
             let point = self
@@ -110,22 +254,19 @@ impl RustPlayer {
 
     #[func]
     pub fn zoom(&mut self) {
-        let weapon_name = self.get_current_weapon().get_name().to_string();
-        if weapon_name == "M95" || weapon_name == "SKULL-5" {
-            self.zoom_audio.play();
-            self.camera.set_zoom(Vector2::new(0.5, 0.5));
-        } else if weapon_name == "AWP" || weapon_name == "SKULL-6" {
-            self.zoom_audio.play();
-            self.camera.set_zoom(Vector2::new(0.65, 0.65));
-        } else if weapon_name == "AK47-60R" || weapon_name == "M32" {
+        let zoom = self.get_current_weapon().bind().get_zoom();
+        if zoom < 1.0 {
             self.zoom_audio.play();
-            self.camera.set_zoom(Vector2::new(0.8, 0.8));
-        } else if weapon_name == "RPG-7" {
-            self.zoom_audio.play();
-            self.camera.set_zoom(Vector2::new(0.84, 0.84));
+            self.set_scope_zoom(Vector2::new(zoom, zoom));
         }
     }
 
+    // 记录开镜倍率，双人时镜头会在此基础上继续拉远
+    pub fn set_scope_zoom(&mut self, zoom: Vector2) {
+        self.scope_zoom = zoom;
+        self.camera.set_zoom(zoom);
+    }
+
     pub fn get_current_weapon(&self) -> Gd<RustWeapon> {
         self.weapons
             .get_child(self.current_weapon_index)
@@ -133,120 +274,58 @@ impl RustPlayer {
             .cast::<RustWeapon>()
     }
 
-    // 消音武器
-    #[func]
-    pub fn unlock_usp(&mut self) {
-        self.unlock_weapon("usp", 0, "1");
-    }
-
-    #[func]
-    pub fn unlock_deagle(&mut self) {
-        self.unlock_weapon("deagle", 1, "2");
-    }
-
-    #[func]
-    pub fn unlock_m1887(&mut self) {
-        self.unlock_weapon("m1887", 2, "3");
-    }
-
+    /// 解锁所有到达该关卡可用的武器和配件，武器按定义顺序依次解锁
     #[func]
-    pub fn unlock_awp(&mut self) {
-        self.unlock_weapon("awp", 3, "4");
-    }
-
-    // 强力武器
-    #[func]
-    pub fn unlock_m79(&mut self) {
-        self.unlock_weapon("m79", 4, "5");
-    }
-
-    // 消音武器
-    #[func]
-    pub fn unlock_m4a1(&mut self) {
-        self.unlock_weapon("m4a1", 5, "6");
-    }
-
-    #[func]
-    pub fn unlock_ak47(&mut self) {
-        self.unlock_weapon("ak47", 6, "7");
-    }
-
-    #[func]
-    pub fn unlock_xm1014(&mut self) {
-        self.unlock_weapon("xm1014", 7, "8");
-    }
-
-    // 强力武器
-    #[func]
-    pub fn unlock_ak47_60r(&mut self) {
-        self.unlock_weapon("ak47-60r", 8, "9");
-    }
-
-    #[func]
-    pub fn unlock_rpg_7(&mut self) {
-        self.unlock_weapon("rpg-7", 9, "[(MAYBE MANY TIMES)");
-    }
-
-    #[func]
-    pub fn unlock_m249(&mut self) {
-        self.unlock_weapon("m249", 10, "[(MAYBE MANY TIMES)");
-    }
-
-    #[func]
-    pub fn unlock_mg3(&mut self) {
-        self.unlock_weapon("mg3", 11, "[(MAYBE MANY TIMES)");
-    }
-
-    // 强力武器
-    #[func]
-    pub fn unlock_skull_6(&mut self) {
-        self.unlock_weapon("skull-6", 12, "[(MAYBE MANY TIMES)");
-    }
-
-    #[func]
-    pub fn unlock_m95(&mut self) {
-        self.unlock_weapon("m95", 13, "[(MAYBE MANY TIMES)");
-    }
-
-    #[func]
-    pub fn unlock_m134(&mut self) {
-        self.unlock_weapon("m134", 14, "[(MAYBE MANY TIMES)");
-    }
-
-    #[func]
-    pub fn unlock_m32(&mut self) {
-        self.unlock_weapon("m32", 15, "[(MAYBE MANY TIMES)");
-    }
-
-    // 强力武器
-    #[func]
-    pub fn unlock_xm1134(&mut self) {
-        self.unlock_weapon("xm1134", 16, "[(MAYBE MANY TIMES)");
+    pub fn unlock_level_weapons(&mut self, level: u32) {
+        for definition in WeaponDefinition::all() {
+            if let WeaponUnlock::Level(unlock_level) = definition.unlock {
+                if level >= unlock_level {
+                    self.unlock_weapon(&definition.name);
+                }
+            }
+        }
+        self.unlock_level_attachments(level);
+        self.unlock_level_ammo(level);
     }
 
-    // 强力武器
-    #[func]
-    pub fn unlock_skull_5(&mut self) {
-        self.unlock_weapon("skull-5", 17, "[(MAYBE MANY TIMES)");
+    // 按名字判断是否已拥有，职业起始武器不会打乱后续解锁
+    pub fn add_weapon(&mut self, weapon_name: &str) -> Option<i32> {
+        let node_name = weapon_name.to_uppercase();
+        if self
+            .weapons
+            .get_children()
+            .iter_shared()
+            .any(|weapon| weapon.get_name().to_string() == node_name)
+        {
+            return None;
+        }
+        let weapon = WeaponDefinition::get(weapon_name)?.instantiate()?;
+        self.weapons.add_child(&weapon);
+        Some(self.weapons.get_child_count() - 1)
     }
 
-    pub fn unlock_weapon(&mut self, weapon_name: &str, index: i32, key: &str) {
-        if self.weapons.get_child_count() > index {
+    pub fn unlock_weapon(&mut self, weapon_name: &str) {
+        let Some(index) = self.add_weapon(weapon_name) else {
             return;
+        };
+        let slot = self
+            .get_weapon_slots()
+            .iter()
+            .position(|i| *i == index)
+            .unwrap_or_default();
+        // 排在9号以后的武器只能用轮盘选
+        let key = if slot < 9 {
+            format!("PRESS {}", slot + 1)
+        } else {
+            String::from("HOLD TAB")
+        };
+        if let Some(mut unlock_label) = self.create_message() {
+            unlock_label.bind_mut().show_message(&format!(
+                "WEAPON {} UNLOCKED, {} TO USE IT",
+                weapon_name.to_uppercase(),
+                key
+            ));
         }
-        if let Some(weapon) =
-            load::<PackedScene>(&format!("res://scenes/weapons/{}.tscn", weapon_name))
-                .try_instantiate_as::<RustWeapon>()
-        {
-            self.weapons.add_child(&weapon);
-            if let Some(mut unlock_label) = self.create_message() {
-                unlock_label.bind_mut().show_message(&format!(
-                    "WEAPON {} UNLOCKED, PRESS {} TO USE IT",
-                    weapon_name.to_uppercase(),
-                    key
-                ));
-            }
-            self.change_weapon(index);
-        }
+        self.change_weapon(index);
     }
 }
@@ -0,0 +1,158 @@
+use super::*;
+use crate::loadout::WeaponSlot;
+use crate::{WEAPON_WHEEL_DEADZONE, WEAPON_WHEEL_RADIUS};
+use godot::builtin::real_consts::{FRAC_PI_2, TAU};
+use godot::meta::ToGodot;
+
+#[godot_api(secondary)]
+impl RustPlayer {
+    pub fn is_weapon_wheel_open(&self) -> bool {
+        !self.wheel_slots.is_empty()
+    }
+
+    // 按住时打开轮盘，第一把武器在正上方，顺时针排列
+    pub fn open_weapon_wheel(&mut self) {
+        if PlayerState::Dead == self.state || self.is_weapon_wheel_open() {
+            return;
+        }
+        self.wheel_slots = self.get_weapon_slots();
+        self.wheel_selected = None;
+        let weapons: Vec<(i32, String)> = self
+            .wheel_slots
+            .iter()
+            .filter_map(|weapon_index| {
+                let weapon_name = self.weapons.get_child(*weapon_index)?.get_name();
+                Some((*weapon_index, weapon_name.to_string()))
+            })
+            .collect();
+        if self.is_remote() {
+            // 客户端自己画轮盘和选武器，主机只保持打开状态不让开火
+            let weapon_indices: PackedInt32Array = weapons.iter().map(|(i, _)| *i).collect();
+            let weapon_names: PackedStringArray = weapons
+                .iter()
+                .map(|(_, name)| GString::from(name))
+                .collect();
+            let current_weapon_index = self.current_weapon_index;
+            let peer_id = self.peer_id as i64;
+            self.base_mut().rpc_id(
+                peer_id,
+                "receive_weapon_wheel",
+                &[
+                    weapon_indices.to_variant(),
+                    weapon_names.to_variant(),
+                    current_weapon_index.to_variant(),
+                ],
+            );
+            return;
+        }
+        self.draw_weapon_wheel(&weapons, self.current_weapon_index);
+    }
+
+    /// 客户端收到主机上已拥有的武器后按本机的顺序画轮盘
+    pub fn apply_weapon_wheel(
+        &mut self,
+        weapon_indices: PackedInt32Array,
+        weapon_names: PackedStringArray,
+        current_weapon_index: i32,
+    ) {
+        if !self.is_local() || !self.is_action_pressed("weapon_wheel") {
+            // 主机回复前已经松开了
+            return;
+        }
+        let mut weapons: Vec<(i32, String)> = weapon_indices
+            .as_slice()
+            .iter()
+            .copied()
+            .zip(weapon_names.as_slice().iter().map(|name| name.to_string()))
+            .collect();
+        weapons.sort_by_key(|(_, name)| WeaponSlot::get(name).order);
+        self.wheel_slots = weapons.iter().map(|(i, _)| *i).collect();
+        self.wheel_selected = None;
+        self.draw_weapon_wheel(&weapons, current_weapon_index);
+    }
+
+    fn draw_weapon_wheel(&mut self, weapons: &[(i32, String)], current_weapon_index: i32) {
+        let count = weapons.len();
+        let entries: Vec<(String, Vector2, bool)> = weapons
+            .iter()
+            .enumerate()
+            .map(|(slot, (weapon_index, weapon_name))| {
+                let angle = slot as real * TAU / count as real - FRAC_PI_2;
+                let title = if WeaponSlot::get(weapon_name).favorite {
+                    format!("{} *", weapon_name)
+                } else {
+                    weapon_name.clone()
+                };
+                (
+                    title,
+                    Vector2::from_angle(angle) * WEAPON_WHEEL_RADIUS,
+                    *weapon_index == current_weapon_index,
+                )
+            })
+            .collect();
+        let center = self.base().get_global_transform_with_canvas().origin;
+        let mut hud = self.hud.bind_mut();
+        hud.show_weapon_wheel(&entries);
+        hud.move_weapon_wheel(center);
+    }
+
+    /// 松开时换成准星方向上的武器
+    pub fn close_weapon_wheel(&mut self) {
+        if !self.is_weapon_wheel_open() {
+            return;
+        }
+        self.wheel_slots.clear();
+        self.hud.bind_mut().hide_weapon_wheel();
+        let selected = self.wheel_selected.take();
+        if RustNetwork::is_client() {
+            // 客户端把选中的武器交给主机切换
+            if let Some(weapon_index) = selected {
+                self.base_mut()
+                    .rpc_id(1, "receive_weapon_choice", &[weapon_index.to_variant()]);
+            }
+            return;
+        }
+        self.choose_weapon(selected);
+    }
+
+    pub fn choose_weapon(&mut self, selected: Option<i32>) {
+        if RustWorld::is_paused() || self.effects.is_stunned() || PlayerState::Downed == self.state
+        {
+            // 暂停、眩晕和倒地时只收起轮盘
+            return;
+        }
+        if let Some(weapon_index) = selected {
+            if weapon_index != self.current_weapon_index {
+                self.change_weapon(weapon_index);
+            }
+        }
+    }
+
+    // 鼠标和摇杆都用玩家到准星的方向选择
+    pub fn update_weapon_wheel(&mut self) {
+        if !self.is_weapon_wheel_open() || self.is_remote() {
+            return;
+        }
+        if PlayerState::Dead == self.state {
+            self.close_weapon_wheel();
+            return;
+        }
+        // 轮盘跟着玩家在屏幕上的位置，本地双人时各画在自己身上
+        let center = self.base().get_global_transform_with_canvas().origin;
+        self.hud.bind_mut().move_weapon_wheel(center);
+        let player_position = self.base().get_global_position();
+        let aim_position = Self::get_aim_position(self.player_index);
+        let slot = if player_position.distance_to(aim_position) < WEAPON_WHEEL_DEADZONE {
+            None
+        } else {
+            let step = TAU / self.wheel_slots.len() as real;
+            let angle = player_position.angle_to_point(aim_position) + FRAC_PI_2 + step / 2.0;
+            Some((angle.rem_euclid(TAU) / step) as usize % self.wheel_slots.len())
+        };
+        let selected = slot.map(|slot| self.wheel_slots[slot]);
+        if selected != self.wheel_selected {
+            self.wheel_selected = selected;
+            self.hud.bind_mut().select_weapon_wheel(slot);
+        }
+    }
+}
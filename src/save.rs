@@ -1,3 +1,4 @@
+use crate::achievement::RustAchievements;
 use crate::world::RustWorld;
 use crate::{SAVE, SAVE_PATH};
 use dashmap::DashMap;
@@ -71,6 +72,8 @@ impl RustSaveLoader {
         if let Some(mut file) = FileAccess::open(SAVE_PATH, ModeFlags::WRITE) {
             file.store_string(&serde_json::to_string_pretty(&*SAVE).unwrap());
         }
+        // 成就进度跟随存档一起写入
+        RustAchievements::save_profile();
     }
 
     pub fn get() -> Gd<Self> {
@@ -0,0 +1,50 @@
+use crate::SETTINGS_PATH;
+use crate::damage::DamageRules;
+use crossbeam_utils::atomic::AtomicCell;
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+// 游戏设置，第一次使用时从设置文件读取
+static SETTINGS: LazyLock<AtomicCell<GameSettings>> = LazyLock::new(|| {
+    let settings = FileAccess::open(SETTINGS_PATH, ModeFlags::READ)
+        .and_then(|file| serde_json::from_str(&file.get_as_text().to_string()).ok())
+        .unwrap_or_default();
+    AtomicCell::new(settings)
+});
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct GameSettings {
+    // 是否显示伤害数字
+    pub damage_numbers: bool,
+    // 爆炸伤害规则，地狱模式单独一份
+    pub damage_rules: DamageRules,
+    pub hell_damage_rules: DamageRules,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            damage_numbers: true,
+            damage_rules: DamageRules::default(),
+            hell_damage_rules: DamageRules::hell(),
+        }
+    }
+}
+
+impl GameSettings {
+    pub fn get() -> Self {
+        SETTINGS.load()
+    }
+
+    /// 修改设置并立即写入设置文件
+    pub fn set(settings: Self) {
+        SETTINGS.store(settings);
+        DirAccess::make_dir_recursive_absolute("user://data");
+        if let Some(mut file) = FileAccess::open(SETTINGS_PATH, ModeFlags::WRITE) {
+            file.store_string(&serde_json::to_string_pretty(&settings).unwrap());
+        }
+    }
+}
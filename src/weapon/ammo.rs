@@ -0,0 +1,62 @@
+use super::*;
+use crate::AMMO_TYPES;
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    pub fn get_ammo_type(&self) -> AmmoType {
+        self.ammo_type
+    }
+
+    /// 读档时恢复弹药类型，不能使用特殊弹药的武器忽略
+    pub fn set_ammo_type(&mut self, ammo_type: AmmoType) {
+        if self.special_ammo || !ammo_type.is_special() {
+            self.ammo_type = ammo_type;
+        }
+    }
+
+    /// 切换到下一种还有备弹的弹药
+    pub fn next_ammo_type(&mut self) -> Result<AmmoType, String> {
+        if !self.special_ammo {
+            return Err(format!(
+                "{} CAN'T USE SPECIAL AMMO",
+                self.base().get_name().to_upper()
+            ));
+        }
+        let player_index = self.get_player_index();
+        let start = self.ammo_type as usize;
+        let next = (1..AMMO_TYPES.len())
+            .map(|offset| AMMO_TYPES[(start + offset) % AMMO_TYPES.len()])
+            .find(|ammo_type| ammo_type.get_reserve(player_index) > 0);
+        let Some(ammo_type) = next else {
+            return Err(String::from("NO SPECIAL AMMO LEFT"));
+        };
+        self.ammo_type = ammo_type;
+        self.update_ammo_type_hud();
+        Ok(ammo_type)
+    }
+
+    // 从备弹里取出一发，特殊弹药用完后换回普通弹药
+    pub(super) fn load_round(&mut self) -> AmmoType {
+        if !self.ammo_type.is_special() {
+            return AmmoType::Standard;
+        }
+        if !self.ammo_type.take(self.get_player_index()) {
+            self.ammo_type = AmmoType::Standard;
+        }
+        self.update_ammo_type_hud();
+        self.ammo_type
+    }
+
+    pub fn update_ammo_type_hud(&self) {
+        let reserve = self.ammo_type.get_reserve(self.get_player_index());
+        let text = if self.ammo_type.is_special() {
+            format!("{} {}", self.ammo_type.get_title(), reserve)
+        } else {
+            String::from(self.ammo_type.get_title())
+        };
+        self.get_hud().call_deferred(
+            "update_ammo_type_hud",
+            &[text.to_variant(), self.ammo_type.get_color().to_variant()],
+        );
+    }
+}
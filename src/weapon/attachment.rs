@@ -0,0 +1,116 @@
+use super::*;
+use crate::weapon::definition::{WeaponDefinition, WeaponStats};
+use crate::{
+    Attachment, COMPENSATOR_JITTER_RATE, EXTENDED_MAG_CLIP_RATE, EXTENDED_MAG_RELOAD_RATE,
+    HEAVY_BARREL_PENETRATE, HEAVY_BARREL_WEIGHT, LASER_JITTER_RATE, SCOPE_ZOOM,
+    SUPPRESSOR_DAMAGE_RATE, SUPPRESSOR_DISTANCE_RATE, WEAPON_MAX_ATTACHMENTS,
+};
+
+impl Attachment {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            Attachment::Suppressor => "SUPPRESSOR",
+            Attachment::ExtendedMag => "EXTENDED MAG",
+            Attachment::Compensator => "COMPENSATOR",
+            Attachment::Laser => "LASER",
+            Attachment::Scope => "SCOPE",
+            Attachment::HeavyBarrel => "HEAVY BARREL",
+        }
+    }
+
+    pub fn get_description(&self) -> String {
+        match self {
+            Attachment::Suppressor => format!(
+                "SILENCED, DAMAGE -{:.0}%, DISTANCE -{:.0}%",
+                (1.0 - SUPPRESSOR_DAMAGE_RATE) * 100.0,
+                (1.0 - SUPPRESSOR_DISTANCE_RATE) * 100.0
+            ),
+            Attachment::ExtendedMag => format!(
+                "CLIP +{:.0}%, RELOAD +{:.0}%",
+                (EXTENDED_MAG_CLIP_RATE - 1.0) * 100.0,
+                (EXTENDED_MAG_RELOAD_RATE - 1.0) * 100.0
+            ),
+            Attachment::Compensator => {
+                format!("JITTER -{:.0}%", (1.0 - COMPENSATOR_JITTER_RATE) * 100.0)
+            }
+            Attachment::Laser => format!(
+                "BRIGHTER LASER, JITTER -{:.0}%",
+                (1.0 - LASER_JITTER_RATE) * 100.0
+            ),
+            Attachment::Scope => format!("ZOOM x{:.2}", 1.0 / SCOPE_ZOOM),
+            Attachment::HeavyBarrel => format!(
+                "PENETRATE +{:.1}, WEIGHT -{:.1}",
+                HEAVY_BARREL_PENETRATE, HEAVY_BARREL_WEIGHT
+            ),
+        }
+    }
+
+    /// 到达该关卡后解锁
+    pub fn get_unlock_level(&self) -> u32 {
+        match self {
+            Attachment::Suppressor => 4,
+            Attachment::Compensator => 7,
+            Attachment::ExtendedMag => 10,
+            Attachment::Laser => 13,
+            Attachment::HeavyBarrel => 16,
+            Attachment::Scope => 20,
+        }
+    }
+
+    pub fn apply(&self, stats: &mut WeaponStats) {
+        match self {
+            // 自带消音的武器不吃消音器的惩罚
+            Attachment::Suppressor if stats.silenced => {}
+            Attachment::Suppressor => {
+                stats.silenced = true;
+                stats.damage = (stats.damage as real * SUPPRESSOR_DAMAGE_RATE) as i64;
+                stats.distance *= SUPPRESSOR_DISTANCE_RATE;
+            }
+            Attachment::ExtendedMag => {
+                let clip = (stats.clip as real * EXTENDED_MAG_CLIP_RATE).ceil() as i32;
+                stats.clip = clip.max(stats.clip + 1);
+                stats.reload_time *= EXTENDED_MAG_RELOAD_RATE;
+            }
+            Attachment::Compensator => stats.jitter *= COMPENSATOR_JITTER_RATE,
+            Attachment::Laser => stats.jitter *= LASER_JITTER_RATE,
+            // 自带倍镜的武器保留原来的倍率
+            Attachment::Scope => stats.zoom = stats.zoom.min(SCOPE_ZOOM),
+            Attachment::HeavyBarrel => {
+                stats.penetrate += HEAVY_BARREL_PENETRATE;
+                stats.weight = (stats.weight - HEAVY_BARREL_WEIGHT).max(0.1);
+            }
+        }
+    }
+}
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    pub fn get_attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    pub fn has_attachment(&self, attachment: Attachment) -> bool {
+        self.attachments.contains(&attachment)
+    }
+
+    // 武器定义里就带消音
+    fn is_built_in_silenced(&self) -> bool {
+        WeaponDefinition::get(&self.base().get_name().to_string())
+            .is_some_and(|definition| definition.stats.silenced)
+    }
+
+    /// 装上或卸下配件，返回装上后是否生效，装满时返回错误提示
+    pub fn toggle_attachment(&mut self, attachment: Attachment) -> Result<bool, String> {
+        if self.has_attachment(attachment) {
+            self.attachments.retain(|a| *a != attachment);
+        } else if Attachment::Suppressor == attachment && self.is_built_in_silenced() {
+            return Err("ALREADY SILENCED".to_string());
+        } else if self.attachments.len() >= WEAPON_MAX_ATTACHMENTS {
+            return Err(format!("MAX {} ATTACHMENTS", WEAPON_MAX_ATTACHMENTS));
+        } else {
+            self.attachments.push(attachment);
+        }
+        self.refresh_stats();
+        Ok(self.has_attachment(attachment))
+    }
+}
@@ -0,0 +1,578 @@
+use crate::{
+    BULLET_DAMAGE, BULLET_DISTANCE, BULLET_PENETRATE, BULLET_REPEL, BULLET_SPEED, BURST_COUNT,
+    BURST_INTERVAL, CHARGE_TIME, FIRST_SHOT_SPREAD_RATE, FireMode, HEAT_COOLING, MAX_AMMO,
+    OVERHEAT_LOCKOUT, RECOIL_RECOVERY, RELOAD_TIME, SPIN_DOWN_TIME, StatusEffect,
+    WEAPON_CRIT_CHANCE, WEAPON_CRIT_DAMAGE, WEAPON_FIRE_COOLDOWN, WEAPON_MODS_DIR, WEAPONS_PATH,
+};
+use godot::builtin::real;
+use godot::classes::file_access::ModeFlags;
+use godot::classes::{DirAccess, FileAccess, PackedScene, ResourceLoader};
+use godot::global::godot_error;
+use godot::obj::Gd;
+use godot::tools::try_load;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+// 没有自带场景的武器从这个模板生成
+const WEAPON_TEMPLATE: &str = "res://scenes/weapons/rust_weapon.tscn";
+
+// 内置武器和玩家自制武器，内置武器在前
+static DEFINITIONS: LazyLock<Vec<WeaponDefinition>> = LazyLock::new(|| {
+    // 内置定义读取失败时只加载玩家自制武器
+    let built_in: Vec<WeaponDefinition> = match FileAccess::open(WEAPONS_PATH, ModeFlags::READ) {
+        Some(file) => match serde_json::from_str(&file.get_as_text().to_string()) {
+            Ok(definitions) => definitions,
+            Err(e) => {
+                godot_error!("Failed to parse weapon definitions: {}", e);
+                Vec::new()
+            }
+        },
+        None => {
+            godot_error!("Weapon definitions not found: {}", WEAPONS_PATH);
+            Vec::new()
+        }
+    };
+    // 内置武器和自制武器一样校验，不合法的跳过
+    let mut definitions: Vec<WeaponDefinition> = built_in
+        .into_iter()
+        .filter(|definition| match definition.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                godot_error!("Skip weapon {}: {}", definition.name, e);
+                false
+            }
+        })
+        .collect();
+    let mut names: HashSet<String> = definitions
+        .iter()
+        .map(|definition| definition.name.to_uppercase())
+        .collect();
+    let Some(mut mods_dir) = DirAccess::open(WEAPON_MODS_DIR) else {
+        return definitions;
+    };
+    let mut files = mods_dir.get_files().to_vec();
+    files.sort();
+    for file in files {
+        if !file.ends_with(".json") {
+            continue;
+        }
+        let path = format!("{}/{}", WEAPON_MODS_DIR, file);
+        let Some(mod_file) = FileAccess::open(&path, ModeFlags::READ) else {
+            continue;
+        };
+        let result = serde_json::from_str::<WeaponDefinition>(&mod_file.get_as_text().to_string())
+            .map_err(|e| e.to_string())
+            .and_then(|definition| definition.validate().map(|_| definition));
+        match result {
+            Ok(definition) => {
+                if !names.insert(definition.name.to_uppercase()) {
+                    godot_error!(
+                        "Skip weapon mod {}: duplicate name {}",
+                        path,
+                        definition.name
+                    );
+                    continue;
+                }
+                definitions.push(definition);
+            }
+            Err(e) => godot_error!("Skip weapon mod {}: {}", path, e),
+        }
+    }
+    definitions
+});
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeaponUnlock {
+    /// 开局就有
+    Start,
+    /// 到达关卡后解锁
+    Level(u32),
+    /// 只能由职业或其他途径获得
+    #[default]
+    Never,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadMode {
+    /// 整个弹匣一起换
+    #[default]
+    Clip,
+    /// 一发一发地装填
+    Single,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReloadModel {
+    pub mode: ReloadMode,
+    pub bolt_after_deploy: bool,
+    pub bolt_after_reload: bool,
+    pub bolt_after_fire: bool,
+}
+
+/// 可切换的射击模式，第一个为默认模式
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct FireModel {
+    pub modes: Vec<FireMode>,
+    /// 点射每串的发数
+    pub burst_count: u32,
+    /// 点射时每发之间的间隔，打完一串后按开火冷却时间等待
+    pub burst_interval: real,
+    /// 蓄力射击蓄满需要的时间
+    pub charge_time: real,
+}
+
+impl Default for FireModel {
+    fn default() -> Self {
+        Self {
+            modes: vec![FireMode::Auto],
+            burst_count: BURST_COUNT,
+            burst_interval: BURST_INTERVAL,
+            charge_time: CHARGE_TIME,
+        }
+    }
+}
+
+/// 后坐力弹道，连续射击时准星按固定路线偏移
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecoilModel {
+    /// 每一发的横向偏移，以抖动系数为单位，打完后从头循环
+    pub pattern: Vec<real>,
+    /// 停火后每秒恢复的发数
+    pub recovery: real,
+    /// 完全恢复后第一发的固有散布比例
+    pub first_shot_rate: real,
+}
+
+impl Default for RecoilModel {
+    fn default() -> Self {
+        Self {
+            pattern: Vec::new(),
+            recovery: RECOIL_RECOVERY,
+            first_shot_rate: FIRST_SHOT_SPREAD_RATE,
+        }
+    }
+}
+
+/// 伤害随飞行距离衰减，超过起始距离后线性降到最小倍率，默认不衰减
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FalloffModel {
+    pub start: real,
+    pub end: real,
+    pub min_rate: real,
+}
+
+impl Default for FalloffModel {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 0.0,
+            min_rate: 1.0,
+        }
+    }
+}
+
+impl FalloffModel {
+    pub fn get_rate(&self, distance: real) -> real {
+        if distance <= self.start {
+            return 1.0;
+        }
+        if distance >= self.end {
+            return self.min_rate;
+        }
+        let progress = (distance - self.start) / (self.end - self.start);
+        1.0 + (self.min_rate - 1.0) * progress
+    }
+}
+
+/// 转管机枪按住开火键先转起来才能射击，转起来时移动变慢，默认不需要转
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpinModel {
+    /// 从静止转到能开火的时间
+    pub up_time: real,
+    /// 从全速停下的时间
+    pub down_time: real,
+    /// 全速转动时的移动速度比例
+    pub weight: real,
+}
+
+impl Default for SpinModel {
+    fn default() -> Self {
+        Self {
+            up_time: 0.0,
+            down_time: SPIN_DOWN_TIME,
+            weight: 1.0,
+        }
+    }
+}
+
+impl SpinModel {
+    pub fn is_enabled(&self) -> bool {
+        self.up_time > 0.0
+    }
+}
+
+/// 机枪用热量代替弹匣，热量满了过热锁定一段时间，默认不发热
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HeatModel {
+    /// 每一发增加的热量，满热量为1
+    pub per_shot: real,
+    /// 每秒散去的热量
+    pub cooling: real,
+    /// 过热后不能开火的时间
+    pub lockout: real,
+}
+
+impl Default for HeatModel {
+    fn default() -> Self {
+        Self {
+            per_shot: 0.0,
+            cooling: HEAT_COOLING,
+            lockout: OVERHEAT_LOCKOUT,
+        }
+    }
+}
+
+impl HeatModel {
+    pub fn is_enabled(&self) -> bool {
+        self.per_shot > 0.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeaponStats {
+    pub silenced: bool,
+    pub damage: i64,
+    pub weight: real,
+    pub distance: real,
+    pub speed: real,
+    pub clip: i32,
+    pub jitter: real,
+    pub repel: real,
+    pub penetrate: real,
+    pub fire_cooldown: real,
+    pub reload_time: real,
+    pub zoom: real,
+    pub effect: StatusEffect,
+    pub effect_chance: real,
+    pub crit_chance: real,
+    /// 暴击时的伤害倍率
+    pub crit_damage: real,
+}
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        Self {
+            silenced: false,
+            damage: BULLET_DAMAGE,
+            weight: 1.0,
+            distance: BULLET_DISTANCE,
+            speed: BULLET_SPEED,
+            clip: MAX_AMMO,
+            jitter: 0.0,
+            repel: BULLET_REPEL,
+            penetrate: BULLET_PENETRATE,
+            fire_cooldown: WEAPON_FIRE_COOLDOWN,
+            reload_time: RELOAD_TIME,
+            zoom: 1.0,
+            effect: StatusEffect::None,
+            effect_chance: 0.0,
+            crit_chance: WEAPON_CRIT_CHANCE,
+            crit_damage: WEAPON_CRIT_DAMAGE,
+        }
+    }
+}
+
+/// 音效资源路径，不填则沿用场景里的音效
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeaponAudio {
+    pub deploy: Option<String>,
+    pub fire: Option<String>,
+    pub fire_bolt_pull: Option<String>,
+    pub clip_out: Option<String>,
+    pub clip_part_in: Option<String>,
+    pub clip_in: Option<String>,
+    pub reload_bolt_pull: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponSprite {
+    pub texture: String,
+    #[serde(default = "default_sprite_offset")]
+    pub offset: [real; 2],
+    #[serde(default = "default_sprite_scale")]
+    pub scale: real,
+}
+
+fn default_sprite_offset() -> [real; 2] {
+    [16.0, 0.0]
+}
+
+fn default_sprite_scale() -> real {
+    0.5
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponDefinition {
+    /// 节点名，也是HUD上显示的名字
+    pub name: String,
+    /// 自带场景，不填则使用模板场景
+    #[serde(default)]
+    pub scene: Option<String>,
+    #[serde(default)]
+    pub unlock: WeaponUnlock,
+    #[serde(default)]
+    pub stats: WeaponStats,
+    /// 是否每次都从所有子弹点射出子弹
+    #[serde(default)]
+    pub explode: bool,
+    /// 是否用射线即时命中代替子弹实体，只对普通子弹生效
+    #[serde(default)]
+    pub hitscan: bool,
+    /// 子弹场景，不填则使用普通子弹
+    #[serde(default)]
+    pub bullets: Vec<String>,
+    /// 子弹点相对枪口的位置，不填则沿用场景里的子弹点
+    #[serde(default)]
+    pub bullet_points: Vec<[real; 2]>,
+    #[serde(default)]
+    pub falloff: FalloffModel,
+    #[serde(default)]
+    pub reload: ReloadModel,
+    #[serde(default)]
+    pub fire: FireModel,
+    #[serde(default)]
+    pub recoil: RecoilModel,
+    #[serde(default)]
+    pub spin: SpinModel,
+    #[serde(default)]
+    pub heat: HeatModel,
+    #[serde(default)]
+    pub audio: WeaponAudio,
+    #[serde(default)]
+    pub sprite: Option<WeaponSprite>,
+}
+
+impl WeaponDefinition {
+    pub fn all() -> &'static [WeaponDefinition] {
+        &DEFINITIONS
+    }
+
+    pub fn get(name: &str) -> Option<&'static WeaponDefinition> {
+        let name = name.to_uppercase();
+        DEFINITIONS
+            .iter()
+            .find(|definition| definition.name.to_uppercase() == name)
+    }
+
+    /// 检查玩家自制的武器定义，返回第一个错误
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || '-' == c || '_' == c)
+        {
+            return Err(format!("invalid name \"{}\"", self.name));
+        }
+        let stats = &self.stats;
+        let checks = [
+            (stats.damage >= 0, "damage must not be negative"),
+            (stats.clip >= 1, "clip must be at least 1"),
+            (
+                stats.weight > 0.0 && stats.weight <= 2.0,
+                "weight must be in (0, 2]",
+            ),
+            (stats.distance > 0.0, "distance must be positive"),
+            (stats.speed > 0.0, "speed must be positive"),
+            (stats.jitter >= 0.0, "jitter must not be negative"),
+            (
+                stats.fire_cooldown >= 0.0,
+                "fire_cooldown must not be negative",
+            ),
+            (stats.reload_time > 0.0, "reload_time must be positive"),
+            (
+                stats.zoom > 0.0 && stats.zoom <= 1.0,
+                "zoom must be in (0, 1]",
+            ),
+            (
+                (0.0..=1.0).contains(&stats.effect_chance),
+                "effect_chance must be in [0, 1]",
+            ),
+            (
+                (0.0..=1.0).contains(&stats.crit_chance),
+                "crit_chance must be in [0, 1]",
+            ),
+            (stats.crit_damage >= 1.0, "crit_damage must be at least 1"),
+            (
+                self.falloff.start >= 0.0 && self.falloff.end >= self.falloff.start,
+                "falloff.end must not be less than falloff.start",
+            ),
+            (
+                (0.0..=1.0).contains(&self.falloff.min_rate),
+                "falloff.min_rate must be in [0, 1]",
+            ),
+            (
+                !self.hitscan || self.bullets.is_empty(),
+                "hitscan only works with standard bullets",
+            ),
+            (!self.fire.modes.is_empty(), "fire.modes must not be empty"),
+            (
+                self.fire.burst_count >= 1,
+                "fire.burst_count must be at least 1",
+            ),
+            (
+                self.fire.burst_interval >= 0.0,
+                "fire.burst_interval must not be negative",
+            ),
+            (
+                self.fire.charge_time > 0.0,
+                "fire.charge_time must be positive",
+            ),
+            (
+                self.recoil.recovery > 0.0,
+                "recoil.recovery must be positive",
+            ),
+            (
+                self.recoil.first_shot_rate >= 0.0,
+                "recoil.first_shot_rate must not be negative",
+            ),
+            (
+                self.spin.up_time >= 0.0,
+                "spin.up_time must not be negative",
+            ),
+            (self.spin.down_time > 0.0, "spin.down_time must be positive"),
+            (
+                self.spin.weight > 0.0 && self.spin.weight <= 1.0,
+                "spin.weight must be in (0, 1]",
+            ),
+            (
+                (0.0..=1.0).contains(&self.heat.per_shot),
+                "heat.per_shot must be in [0, 1]",
+            ),
+            (self.heat.cooling > 0.0, "heat.cooling must be positive"),
+            (
+                self.heat.lockout >= 0.0,
+                "heat.lockout must not be negative",
+            ),
+        ];
+        if let Some((_, message)) = checks.iter().find(|(ok, _)| !ok) {
+            return Err(String::from(*message));
+        }
+        // 没有自带场景时，换弹流程用到的音效必须提供
+        if self.scene.is_none() {
+            let audio = &self.audio;
+            let required = [
+                (true, &audio.deploy, "audio.deploy"),
+                (true, &audio.fire, "audio.fire"),
+                (true, &audio.clip_out, "audio.clip_out"),
+                (true, &audio.clip_in, "audio.clip_in"),
+                (
+                    ReloadMode::Single == self.reload.mode,
+                    &audio.clip_part_in,
+                    "audio.clip_part_in",
+                ),
+                (
+                    self.reload.bolt_after_deploy || self.reload.bolt_after_fire,
+                    &audio.fire_bolt_pull,
+                    "audio.fire_bolt_pull",
+                ),
+                (
+                    self.reload.bolt_after_reload,
+                    &audio.reload_bolt_pull,
+                    "audio.reload_bolt_pull",
+                ),
+            ];
+            for (needed, path, field) in required {
+                if needed && path.is_none() {
+                    return Err(format!("{} is required without a scene", field));
+                }
+            }
+        }
+        let audio = &self.audio;
+        let paths = self
+            .scene
+            .iter()
+            .chain(self.bullets.iter())
+            .chain(self.sprite.iter().map(|sprite| &sprite.texture))
+            .chain(
+                [
+                    &audio.deploy,
+                    &audio.fire,
+                    &audio.fire_bolt_pull,
+                    &audio.clip_out,
+                    &audio.clip_part_in,
+                    &audio.clip_in,
+                    &audio.reload_bolt_pull,
+                ]
+                .into_iter()
+                .flatten(),
+            );
+        let mut loader = ResourceLoader::singleton();
+        for path in paths {
+            if !loader.exists(path) {
+                return Err(format!("resource not found: {}", path));
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成武器节点，节点名就是武器名
+    pub fn instantiate(&self) -> Option<Gd<super::RustWeapon>> {
+        let path = self.scene.as_deref().unwrap_or(WEAPON_TEMPLATE);
+        let mut weapon = try_load::<PackedScene>(path)
+            .ok()?
+            .try_instantiate_as::<super::RustWeapon>()?;
+        weapon.set_name(&self.name.to_uppercase());
+        Some(weapon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FalloffModel;
+
+    #[test]
+    fn falloff_start_middle_end() {
+        let falloff = FalloffModel {
+            start: 100.0,
+            end: 300.0,
+            min_rate: 0.5,
+        };
+        assert_eq!(1.0, falloff.get_rate(0.0));
+        assert_eq!(1.0, falloff.get_rate(100.0));
+        assert!((falloff.get_rate(200.0) - 0.75).abs() < 1e-5);
+        assert_eq!(0.5, falloff.get_rate(300.0));
+        assert_eq!(0.5, falloff.get_rate(1000.0));
+    }
+
+    #[test]
+    fn falloff_start_equals_end() {
+        let falloff = FalloffModel {
+            start: 200.0,
+            end: 200.0,
+            min_rate: 0.6,
+        };
+        assert_eq!(1.0, falloff.get_rate(200.0));
+        assert_eq!(0.6, falloff.get_rate(200.1));
+    }
+
+    #[test]
+    fn falloff_default_keeps_damage() {
+        let falloff = FalloffModel::default();
+        assert_eq!(1.0, falloff.get_rate(0.0));
+        assert_eq!(1.0, falloff.get_rate(5000.0));
+    }
+}
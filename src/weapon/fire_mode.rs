@@ -0,0 +1,150 @@
+use super::*;
+use crate::{CHARGE_MAX_DAMAGE_RATE, FireMode};
+
+impl FireMode {
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            FireMode::Semi => "SEMI",
+            FireMode::Burst => "BURST",
+            FireMode::Auto => "AUTO",
+            FireMode::Charge => "CHARGE",
+        }
+    }
+}
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    pub fn get_fire_mode(&self) -> FireMode {
+        self.fire_mode
+    }
+
+    /// 读档时恢复射击模式，武器不支持的模式忽略
+    pub fn set_fire_mode(&mut self, fire_mode: FireMode) {
+        if self.fire_modes.contains(&fire_mode) {
+            self.fire_mode = fire_mode;
+            self.cease_fire();
+        }
+    }
+
+    /// 切换到下一个射击模式，只有一种模式时返回None
+    pub fn next_fire_mode(&mut self) -> Option<FireMode> {
+        if self.fire_modes.len() <= 1 {
+            return None;
+        }
+        let index = self
+            .fire_modes
+            .iter()
+            .position(|fire_mode| *fire_mode == self.fire_mode)
+            .map_or(0, |index| (index + 1) % self.fire_modes.len());
+        self.fire_mode = self.fire_modes[index];
+        self.cease_fire();
+        self.update_fire_mode_hud();
+        Some(self.fire_mode)
+    }
+
+    /// 玩家按住开火键时每帧调用
+    pub fn pull_trigger(
+        &mut self,
+        player_damage: i64,
+        player_distance: real,
+        player_penetrate: real,
+        player_repel: real,
+    ) {
+        self.trigger_pulled = true;
+        self.trigger_stats = (
+            player_damage,
+            player_distance,
+            player_penetrate,
+            player_repel,
+        );
+        match self.fire_mode {
+            FireMode::Auto => {
+                self.fire(
+                    player_damage,
+                    player_distance,
+                    player_penetrate,
+                    player_repel,
+                );
+            }
+            FireMode::Semi => {
+                if !self.trigger_spent {
+                    self.trigger_spent = self.fire(
+                        player_damage,
+                        player_distance,
+                        player_penetrate,
+                        player_repel,
+                    );
+                }
+            }
+            FireMode::Burst => {
+                if !self.trigger_spent && 0 == self.left_burst_shots {
+                    self.left_burst_shots = self.burst_count;
+                    self.trigger_spent = self.fire(
+                        player_damage,
+                        player_distance,
+                        player_penetrate,
+                        player_repel,
+                    );
+                    if !self.trigger_spent {
+                        self.left_burst_shots = 0;
+                    }
+                }
+            }
+            // 蓄力在process里累计，松开时打出
+            FireMode::Charge => {}
+        }
+    }
+
+    /// 切换武器或射击模式时打断点射和蓄力
+    pub fn cease_fire(&mut self) {
+        self.trigger_pulled = false;
+        self.trigger_spent = false;
+        self.left_burst_shots = 0;
+        self.charge = 0.0;
+    }
+
+    // 打完点射剩下的子弹，累计蓄力，松开开火键时打出蓄力射击
+    pub(super) fn update_trigger(&mut self, delta: f64) {
+        let (damage, distance, penetrate, repel) = self.trigger_stats;
+        if self.left_burst_shots > 0
+            && self.current_fire_cooldown <= 0.0
+            && !self.fire(damage, distance, penetrate, repel)
+        {
+            // 没子弹或被换弹打断时放弃剩下的点射
+            self.left_burst_shots = 0;
+        }
+        let pulled = std::mem::take(&mut self.trigger_pulled);
+        if !pulled {
+            self.trigger_spent = false;
+        }
+        self.update_spin(delta, pulled);
+        if FireMode::Charge != self.fire_mode {
+            return;
+        }
+        if pulled {
+            if self.charge < 1.0 && self.can_fire() {
+                self.charge = (self.charge + delta as real / self.charge_time).min(1.0);
+                self.update_fire_mode_hud();
+            }
+        } else if self.charge > 0.0 {
+            self.shot_damage_rate = 1.0 + (CHARGE_MAX_DAMAGE_RATE - 1.0) * self.charge;
+            self.fire(damage, distance, penetrate, repel);
+            self.shot_damage_rate = 1.0;
+            self.charge = 0.0;
+            self.update_fire_mode_hud();
+        }
+    }
+
+    pub fn update_fire_mode_hud(&self) {
+        let title = self.fire_mode.get_title();
+        let text = match self.fire_mode {
+            FireMode::Burst => format!("FIRE {} {}", title, self.burst_count),
+            FireMode::Charge if self.charge > 0.0 => {
+                format!("FIRE {} {:.0}%", title, self.charge * 100.0)
+            }
+            _ => format!("FIRE {}", title),
+        };
+        self.get_hud()
+            .call_deferred("update_fire_mode_hud", &[text.to_variant()]);
+    }
+}
@@ -0,0 +1,249 @@
+use super::*;
+use crate::bullet::BulletHit;
+use crate::{
+    HITSCAN_COLLISION_MASK, HITSCAN_MAX_HITS, RICOCHET_DAMAGE_RATE, RICOCHET_DISTANCE,
+    RICOCHET_MAX_BOUNCES, TRACER_TIME, TRACER_WIDTH, is_boss, is_survivor, is_zombie,
+};
+use godot::builtin::{Color, Rid};
+use godot::classes::{Line2D, PhysicsDirectSpaceState2D, PhysicsRayQueryParameters2D};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const HIT_AUDIO: LazyLock<Gd<AudioStream>> =
+    LazyLock::new(|| load("res://asserts/player/weapons/bullet_hit.mp3"));
+
+/// 等物理帧结算的即时命中射击
+#[derive(Debug, Clone)]
+pub struct HitscanShot {
+    from: Vector2,
+    direction: Vector2,
+    distance: real,
+    penetrate: real,
+    hit: BulletHit,
+}
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    // 即时命中的武器自带曳光和命中音效，不用每发子弹创建节点
+    pub(super) fn init_hitscan(&mut self) {
+        if !self.hitscan {
+            return;
+        }
+        let mut tracer = Line2D::new_alloc();
+        tracer.set_name("Tracer");
+        tracer.set_as_top_level(true);
+        tracer.set_width(TRACER_WIDTH);
+        tracer.set_default_color(Color::from_rgb(1.0, 1.0, 0.5));
+        tracer.set_visible(false);
+        self.base_mut().add_child(&tracer);
+        let mut hit_audio = AudioStreamPlayer2D::new_alloc();
+        hit_audio.set_name("HitAudio");
+        hit_audio.set_as_top_level(true);
+        #[allow(clippy::borrow_interior_mutable_const)]
+        hit_audio.set_stream(&*HIT_AUDIO);
+        hit_audio.set_volume_db(12.0);
+        hit_audio.set_max_distance(4096.0);
+        hit_audio.set_attenuation(1e-05);
+        self.base_mut().add_child(&hit_audio);
+    }
+
+    // 射线检测只能在物理帧里做，先把这一发记下来
+    pub(super) fn queue_hitscan(
+        &mut self,
+        player_damage: i64,
+        player_distance: real,
+        player_penetrate: real,
+        player_repel: real,
+        bullet_point: Vector2,
+        direction: Vector2,
+    ) {
+        let (mut damage, crit) = self.roll_bullet_damage(player_damage);
+        let mut penetrate = player_penetrate + self.penetrate;
+        self.shot_ammo_type.adjust(&mut damage, &mut penetrate);
+        self.hitscan_shots.push(HitscanShot {
+            from: bullet_point,
+            direction,
+            distance: player_distance + self.distance,
+            penetrate,
+            hit: BulletHit {
+                damage,
+                direction,
+                repel: player_repel + self.repel,
+                origin: bullet_point,
+                position: bullet_point,
+                falloff: self.falloff,
+                crit,
+                effect: self.roll_effect(),
+                ammo_type: self.shot_ammo_type,
+                round: self.shot_round.clone(),
+                player_index: self.get_player_index(),
+                weapon_name: self.base().get_name().to_string(),
+            },
+        });
+        if self.jitter > 0.0 {
+            self.current_jitter = (self.current_jitter + self.jitter / 5.0).min(self.jitter);
+            self.update_jitter_hud();
+        }
+    }
+
+    pub(super) fn resolve_hitscans(&mut self) {
+        if self.hitscan_shots.is_empty() {
+            return;
+        }
+        let shots = std::mem::take(&mut self.hitscan_shots);
+        let Some(mut space) = self
+            .base()
+            .get_world_2d()
+            .and_then(|world| world.get_direct_space_state())
+        else {
+            return;
+        };
+        for shot in shots {
+            let (points, hit_position) = self.trace(&mut space, shot);
+            if RustNetwork::is_host() {
+                // 客户端没有结算，只需要曳光经过的点和命中位置
+                self.base_mut().rpc(
+                    "show_shot",
+                    &[
+                        points.to_variant(),
+                        hit_position.is_some().to_variant(),
+                        hit_position.unwrap_or_default().to_variant(),
+                    ],
+                );
+            }
+            self.play_shot(points, hit_position);
+        }
+    }
+
+    // 沿射线依次命中碰撞体，穿透耗尽或到达射程时停下，返回曳光经过的点和第一次命中僵尸的位置
+    fn trace(
+        &mut self,
+        space: &mut Gd<PhysicsDirectSpaceState2D>,
+        shot: HitscanShot,
+    ) -> (PackedVector2Array, Option<Vector2>) {
+        let HitscanShot {
+            mut from,
+            mut direction,
+            distance,
+            penetrate,
+            mut hit,
+        } = shot;
+        let mut to = from + direction * distance;
+        let mut points = PackedVector2Array::from(&[from]);
+        let mut exclude: Array<Rid> = Array::new();
+        let mut hit_count: i16 = 0;
+        let mut zombie_count = 0;
+        let mut bounces = 0;
+        let mut hit_position = None;
+        for _ in 0..HITSCAN_MAX_HITS {
+            let Some(mut query) = PhysicsRayQueryParameters2D::create(from, to) else {
+                break;
+            };
+            query.set_collision_mask(HITSCAN_COLLISION_MASK);
+            query.set_exclude(&exclude);
+            let result = space.intersect_ray(&query);
+            let (Some(rid), Some(collider), Some(position)) = (
+                result.get("rid").and_then(|rid| rid.try_to::<Rid>().ok()),
+                result
+                    .get("collider")
+                    .and_then(|collider| collider.try_to::<Gd<Node2D>>().ok()),
+                result
+                    .get("position")
+                    .and_then(|position| position.try_to::<Vector2>().ok()),
+            ) else {
+                break;
+            };
+            exclude.push(rid);
+            if !is_zombie(&***collider) {
+                if AmmoType::Explosive == hit.ammo_type && !is_survivor(&***collider) {
+                    // 爆炸弹药打到僵尸以外的物体也会爆炸
+                    hit.position = position;
+                    hit.hit_obstacle();
+                    points.push(position);
+                    return (points, hit_position);
+                }
+                continue;
+            }
+            hit_position.get_or_insert(position);
+            let boss = is_boss(&***collider);
+            if !boss {
+                zombie_count += 1;
+                RustBullet::check_skewer(zombie_count, &hit.weapon_name);
+            }
+            hit.position = position;
+            hit.direction = direction;
+            let current = hit.clone();
+            let mut stopped = false;
+            let target = if AmmoType::Ricochet == hit.ammo_type && bounces < RICOCHET_MAX_BOUNCES {
+                collider.get_tree().and_then(|tree| {
+                    RustBullet::find_ricochet_target(&tree, position, collider.instance_id())
+                })
+            } else {
+                None
+            };
+            if let Some(target) = target {
+                // 跳弹从命中点重新发出一条射线
+                bounces += 1;
+                hit.damage = (hit.damage as real * RICOCHET_DAMAGE_RATE) as i64;
+                direction = position.direction_to(target).normalized();
+                points.push(position);
+                hit.origin = position;
+                from = position;
+                to = from + direction * RICOCHET_DISTANCE;
+            } else {
+                hit_count += if boss {
+                    hit.ammo_type.get_boss_cost()
+                } else {
+                    1
+                };
+                //达到最大穿透上限
+                stopped = hit_count >= penetrate as i16;
+            }
+            current.apply(collider);
+            if stopped {
+                points.push(position);
+                return (points, hit_position);
+            }
+        }
+        points.push(to);
+        (points, hit_position)
+    }
+
+    /// 显示曳光，命中僵尸时在命中点播放音效
+    pub(super) fn play_shot(&mut self, points: PackedVector2Array, hit_position: Option<Vector2>) {
+        if let Some(mut tracer) = self.base().try_get_node_as::<Line2D>("Tracer") {
+            tracer.set_points(&points);
+            tracer.set_modulate(Color::WHITE);
+            tracer.set_visible(true);
+            self.tracer_time = TRACER_TIME;
+        }
+        if let Some(position) = hit_position {
+            if let Some(mut hit_audio) = self
+                .base()
+                .try_get_node_as::<AudioStreamPlayer2D>("HitAudio")
+            {
+                hit_audio.set_global_position(position);
+                hit_audio.play();
+            }
+        }
+    }
+
+    // 曳光在很短时间内淡出
+    pub(super) fn fade_tracer(&mut self, delta: f64) {
+        if self.tracer_time <= 0.0 {
+            return;
+        }
+        self.tracer_time -= delta as real;
+        if let Some(mut tracer) = self.base().try_get_node_as::<Line2D>("Tracer") {
+            if self.tracer_time <= 0.0 {
+                tracer.set_visible(false);
+            } else {
+                tracer.set_modulate(Color::from_rgba(
+                    1.0,
+                    1.0,
+                    1.0,
+                    self.tracer_time / TRACER_TIME,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use super::*;
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    pub fn has_spin(&self) -> bool {
+        self.spin_model.is_enabled()
+    }
+
+    pub fn has_heat(&self) -> bool {
+        self.heat_model.is_enabled()
+    }
+
+    /// 转管还没转到全速时不能开火
+    pub(super) fn is_spinning_up(&self) -> bool {
+        self.has_spin() && self.spin_up < 1.0
+    }
+
+    pub(super) fn is_overheated(&self) -> bool {
+        self.overheat_time > 0.0
+    }
+
+    /// 转动时的移动速度比例，随转速线性变化
+    pub fn get_spin_weight_rate(&self) -> real {
+        1.0 + (self.spin_model.weight - 1.0) * self.spin_up
+    }
+
+    // 按住开火键时转管加速，松开后减速
+    pub(super) fn update_spin(&mut self, delta: f64, pulled: bool) {
+        if !self.has_spin() {
+            return;
+        }
+        let spin_up = self.spin_up;
+        self.spin_up = if pulled && WeaponState::Reloading != self.state {
+            (spin_up + delta as real / self.spin_model.up_time).min(1.0)
+        } else {
+            (spin_up - delta as real / self.spin_model.down_time).max(0.0)
+        };
+        if spin_up != self.spin_up {
+            self.update_mechanic_hud();
+        }
+    }
+
+    // 热量随时间散去，过热时等锁定结束后热量清零
+    pub(super) fn update_heat(&mut self, delta: f64) {
+        if !self.has_heat() {
+            return;
+        }
+        if self.is_overheated() {
+            self.overheat_time = (self.overheat_time - delta as real).max(0.0);
+            self.heat = if self.heat_model.lockout > 0.0 {
+                self.overheat_time / self.heat_model.lockout
+            } else {
+                0.0
+            };
+            self.update_mechanic_hud();
+        } else if self.heat > 0.0 {
+            self.heat = (self.heat - self.heat_model.cooling * delta as real).max(0.0);
+            self.update_mechanic_hud();
+        }
+    }
+
+    // 每打出一发增加热量，热量满了进入过热锁定
+    pub(super) fn add_heat(&mut self) {
+        if !self.has_heat() {
+            return;
+        }
+        self.heat = (self.heat + self.heat_model.per_shot).min(1.0);
+        if self.heat >= 1.0 {
+            self.overheat_time = self.heat_model.lockout.max(real::EPSILON);
+            self.left_burst_shots = 0;
+        }
+        self.update_mechanic_hud();
+    }
+
+    pub fn update_mechanic_hud(&self) {
+        // 收起的武器照样散热和减速，但不占用HUD
+        if !self.base().is_visible() {
+            return;
+        }
+        let text = if self.has_heat() {
+            if self.is_overheated() {
+                format!("OVERHEAT {:.1}s", self.overheat_time)
+            } else {
+                format!("HEAT {:.0}%", self.heat * 100.0)
+            }
+        } else if self.has_spin() {
+            format!("SPIN {:.0}%", self.spin_up * 100.0)
+        } else {
+            String::new()
+        };
+        self.get_hud()
+            .call_deferred("update_mechanic_hud", &[text.to_variant()]);
+    }
+}
@@ -1,25 +1,48 @@
+use crate::ammo::SpecialRound;
 use crate::bullet::RustBullet;
+use crate::combat::{CombatEvent, CombatEventBus};
 use crate::grenade::RustGrenade;
 use crate::hud::RustHUD;
+use crate::mastery::WeaponMastery;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
+use crate::weapon::definition::{
+    FalloffModel, HeatModel, ReloadMode, SpinModel, WeaponDefinition, WeaponStats,
+};
+use crate::weapon::hitscan::HitscanShot;
 use crate::{
-    BULLET_DAMAGE, BULLET_DISTANCE, BULLET_PENETRATE, BULLET_REPEL, BULLET_SPEED, MAX_AMMO,
-    NO_NOISE, RELOAD_TIME, WEAPON_FIRE_COOLDOWN, WeaponState,
+    AmmoType, Attachment, BULLET_DAMAGE, BULLET_DISTANCE, BULLET_PENETRATE, BULLET_REPEL,
+    BULLET_SPEED, BURST_COUNT, BURST_INTERVAL, CHARGE_TIME, FIRST_SHOT_SPREAD_RATE, FireMode,
+    MAX_AMMO, NO_NOISE, RECOIL_RECOVERY, RELOAD_TIME, StatusEffect, WEAPON_CRIT_CHANCE,
+    WEAPON_CRIT_DAMAGE, WEAPON_FIRE_COOLDOWN, WeaponState, random_chance,
 };
 use crossbeam_utils::atomic::AtomicCell;
-use godot::builtin::{Array, Callable, Vector2, real};
+use godot::builtin::{Array, Callable, PackedVector2Array, Vector2, real};
 use godot::classes::{
-    AudioStreamPlayer2D, Control, GpuParticles2D, INode2D, Node2D, Object, PackedScene, Sprite2D,
+    AudioStream, AudioStreamPlayer2D, Control, GpuParticles2D, INode2D, Node2D, Object,
+    PackedScene, Sprite2D, Texture2D,
 };
 use godot::global::godot_error;
 use godot::meta::ToGodot;
-use godot::obj::{Base, Gd, OnReady, WithBaseField};
+use godot::obj::{Base, Gd, NewAlloc, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
-use godot::tools::load;
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use godot::tools::{load, try_load};
 use std::sync::LazyLock;
 
+pub mod definition;
+
+pub mod attachment;
+
+pub mod fire_mode;
+
+pub mod recoil;
+
+pub mod ammo;
+
+pub mod hitscan;
+
+pub mod mechanic;
+
 pub mod save;
 
 static NOISE_POSITION: AtomicCell<Vector2> = AtomicCell::new(NO_NOISE);
@@ -82,6 +105,39 @@ pub struct RustWeapon {
     #[doc = "是否支持单发装填"]
     #[export]
     reload_part: bool,
+    #[doc = "命中时附加的状态效果"]
+    #[export]
+    effect: StatusEffect,
+    #[doc = "附加状态效果的概率"]
+    #[export]
+    effect_chance: real,
+    #[doc = "暴击率"]
+    #[export]
+    crit_chance: real,
+    #[doc = "暴击伤害倍率"]
+    #[export]
+    crit_damage: real,
+    #[doc = "开镜倍率，1表示不开镜"]
+    #[export]
+    zoom: real,
+    attachments: Vec<Attachment>,
+    fire_modes: Vec<FireMode>,
+    fire_mode: FireMode,
+    burst_count: u32,
+    burst_interval: real,
+    charge_time: real,
+    // 这一帧玩家是否按着开火键
+    trigger_pulled: bool,
+    // 半自动和点射在松开开火键前只触发一次
+    trigger_spent: bool,
+    // 扣扳机时玩家的属性，点射剩下的子弹和蓄力射击沿用
+    trigger_stats: (i64, real, real, real),
+    left_burst_shots: u32,
+    charge: real,
+    // 蓄力射击的伤害倍率
+    shot_damage_rate: real,
+    // 属性里已经算上的熟练度等级
+    mastery_tier: u32,
     state: WeaponState,
     reloading: real,
     part_reload_time: real,
@@ -89,7 +145,36 @@ pub struct RustWeapon {
     current_fire_cooldown: real,
     current_flash_cooldown: f64,
     current_jitter: real,
-    current_jitter_cooldown: real,
+    ammo_type: AmmoType,
+    // 子弹都是普通子弹场景时才能使用特殊弹药
+    special_ammo: bool,
+    // 这一发实际使用的弹药，散弹枪的弹丸共用同一发
+    shot_ammo_type: AmmoType,
+    shot_round: SpecialRound,
+    recoil_pattern: Vec<real>,
+    recoil_recovery: real,
+    first_shot_rate: real,
+    // 连续射击的发数，停火后逐渐恢复
+    recoil_shots: real,
+    // 玩家移动、奔跑等状态对散布的影响
+    accuracy_rate: real,
+    falloff: FalloffModel,
+    // 玩家升级带来的暴击加成
+    crit_chance_bonus: real,
+    crit_damage_bonus: real,
+    // 用射线即时命中代替子弹实体
+    hitscan: bool,
+    hitscan_shots: Vec<HitscanShot>,
+    // 曳光剩余的显示时间
+    tracer_time: real,
+    spin_model: SpinModel,
+    // 转管转速，1表示全速
+    spin_up: real,
+    heat_model: HeatModel,
+    // 当前热量，1表示过热
+    heat: real,
+    // 过热锁定的剩余时间
+    overheat_time: real,
     bullet_points: OnReady<Gd<Control>>,
     deploy_audio: OnReady<Gd<AudioStreamPlayer2D>>,
     fire_flash: OnReady<Gd<GpuParticles2D>>,
@@ -123,13 +208,50 @@ impl INode2D for RustWeapon {
             reload_time: RELOAD_TIME,
             state: WeaponState::Ready,
             reload_part: false,
+            effect: StatusEffect::None,
+            effect_chance: 0.0,
+            crit_chance: WEAPON_CRIT_CHANCE,
+            crit_damage: WEAPON_CRIT_DAMAGE,
+            zoom: 1.0,
+            attachments: Vec::new(),
+            fire_modes: vec![FireMode::Auto],
+            fire_mode: FireMode::Auto,
+            burst_count: BURST_COUNT,
+            burst_interval: BURST_INTERVAL,
+            charge_time: CHARGE_TIME,
+            trigger_pulled: false,
+            trigger_spent: false,
+            trigger_stats: (0, 0.0, 0.0, 0.0),
+            left_burst_shots: 0,
+            charge: 0.0,
+            shot_damage_rate: 1.0,
+            mastery_tier: 0,
             reloading: 0.0,
             part_reload_time: 0.0,
             ammo: MAX_AMMO,
             current_fire_cooldown: WEAPON_FIRE_COOLDOWN,
             current_flash_cooldown: 0.0,
             current_jitter: 0.0,
-            current_jitter_cooldown: WEAPON_FIRE_COOLDOWN,
+            ammo_type: AmmoType::Standard,
+            special_ammo: false,
+            shot_ammo_type: AmmoType::Standard,
+            shot_round: SpecialRound::default(),
+            recoil_pattern: Vec::new(),
+            recoil_recovery: RECOIL_RECOVERY,
+            first_shot_rate: FIRST_SHOT_SPREAD_RATE,
+            recoil_shots: 0.0,
+            accuracy_rate: 1.0,
+            falloff: FalloffModel::default(),
+            crit_chance_bonus: 0.0,
+            crit_damage_bonus: 0.0,
+            hitscan: false,
+            hitscan_shots: Vec::new(),
+            tracer_time: 0.0,
+            spin_model: SpinModel::default(),
+            spin_up: 0.0,
+            heat_model: HeatModel::default(),
+            heat: 0.0,
+            overheat_time: 0.0,
             bullet_scenes: Array::new(),
             bullet_points: OnReady::from_node("BulletPoints"),
             deploy_audio: OnReady::from_node("DeployAudio"),
@@ -147,14 +269,10 @@ impl INode2D for RustWeapon {
     fn process(&mut self, delta: f64) {
         self.current_fire_cooldown -= delta as real;
         self.current_flash_cooldown -= delta;
-        if self.current_jitter > 0.0 && WeaponState::Firing != self.state {
-            self.current_jitter_cooldown -= delta as real;
-            if self.current_jitter_cooldown <= 0.0 {
-                self.current_jitter = (self.current_jitter - self.jitter / 25.0).max(0.0);
-                self.current_jitter_cooldown = self.fire_cooldown;
-                self.update_jitter_hud();
-            }
-        }
+        self.recover_recoil(delta);
+        self.update_trigger(delta);
+        self.fade_tracer(delta);
+        self.update_heat(delta);
         if self.ammo >= self.clip || WeaponState::Reloading != self.state {
             return;
         }
@@ -170,11 +288,19 @@ impl INode2D for RustWeapon {
         }
     }
 
+    fn physics_process(&mut self, _delta: f64) {
+        self.resolve_hitscans();
+    }
+
     fn exit_tree(&mut self) {
         self.bullet_scenes.clear();
+        self.hitscan_shots.clear();
     }
 
     fn ready(&mut self) {
+        self.apply_definition();
+        self.init_hitscan();
+        self.refresh_stats();
         self.ammo = self.clip;
         self.update_ammo_hud();
         let gd = self.to_gd();
@@ -214,41 +340,203 @@ impl RustWeapon {
     #[signal]
     pub fn sig();
 
+    /// 客户端只显示主机结算好的即时命中射击
+    #[rpc(authority, call_remote, unreliable)]
+    pub fn show_shot(&mut self, points: PackedVector2Array, hit: bool, hit_position: Vector2) {
+        self.play_shot(points, hit.then_some(hit_position));
+    }
+
     pub fn deploy(&mut self) {
         self.deploy_audio.play();
     }
 
+    // 按节点名找到武器定义，用定义里的属性覆盖场景
+    fn apply_definition(&mut self) {
+        let name = self.base().get_name().to_string();
+        let Some(definition) = WeaponDefinition::get(&name) else {
+            godot_error!("Weapon definition not found for: {}", name);
+            return;
+        };
+        self.explode = definition.explode;
+        self.reload_part = ReloadMode::Single == definition.reload.mode;
+        self.pull_after_deploy = definition.reload.bolt_after_deploy;
+        self.pull_after_reload = definition.reload.bolt_after_reload;
+        self.pull_after_fire = definition.reload.bolt_after_fire;
+        if !definition.fire.modes.is_empty() {
+            self.fire_modes = definition.fire.modes.clone();
+        }
+        self.fire_mode = self.fire_modes[0];
+        self.burst_count = definition.fire.burst_count.max(1);
+        self.burst_interval = definition.fire.burst_interval;
+        self.charge_time = definition.fire.charge_time;
+        self.recoil_pattern = definition.recoil.pattern.clone();
+        self.recoil_recovery = definition.recoil.recovery;
+        self.first_shot_rate = definition.recoil.first_shot_rate;
+        self.falloff = definition.falloff;
+        self.spin_model = definition.spin;
+        self.heat_model = definition.heat;
+        self.bullet_scenes.clear();
+        for path in &definition.bullets {
+            match try_load::<PackedScene>(path) {
+                Ok(bullet_scene) => self.bullet_scenes.push(&bullet_scene),
+                Err(_) => godot_error!("Failed to load bullet scene: {}", path),
+            }
+        }
+        self.special_ammo = self.bullet_scenes.iter_shared().all(|bullet_scene| {
+            bullet_scene
+                .get_state()
+                .is_some_and(|state| "RustBullet" == state.get_node_type(0).to_string())
+        });
+        self.hitscan = definition.hitscan && self.special_ammo;
+        let audio = &definition.audio;
+        for (player, path) in [
+            (&mut self.deploy_audio, &audio.deploy),
+            (&mut self.fire_audio, &audio.fire),
+            (&mut self.fire_bolt_pull_audio, &audio.fire_bolt_pull),
+            (&mut self.clip_out_audio, &audio.clip_out),
+            (&mut self.clip_part_in_audio, &audio.clip_part_in),
+            (&mut self.clip_in_audio, &audio.clip_in),
+            (&mut self.reload_bolt_pull_audio, &audio.reload_bolt_pull),
+        ] {
+            if let Some(stream) = path
+                .as_deref()
+                .and_then(|path| try_load::<AudioStream>(path).ok())
+            {
+                player.set_stream(&stream);
+            }
+        }
+        if let Some(sprite) = &definition.sprite {
+            let mut sprite2d = self.base().get_node_as::<Sprite2D>("Sprite2D");
+            if let Ok(texture) = try_load::<Texture2D>(&sprite.texture) {
+                sprite2d.set_texture(&texture);
+            }
+            sprite2d.set_position(Vector2::new(sprite.offset[0], sprite.offset[1]));
+            sprite2d.set_scale(Vector2::new(sprite.scale, sprite.scale));
+        }
+        if !definition.bullet_points.is_empty() {
+            for mut bullet_point in self.bullet_points.get_children().iter_shared() {
+                self.bullet_points.remove_child(&bullet_point);
+                bullet_point.queue_free();
+            }
+            for [x, y] in &definition.bullet_points {
+                let mut bullet_point = Node2D::new_alloc();
+                bullet_point.set_position(Vector2::new(*x, *y));
+                self.bullet_points.add_child(&bullet_point);
+            }
+        }
+    }
+
+    /// 从武器定义的属性开始，依次叠加配件和熟练度加成
+    pub fn refresh_stats(&mut self) {
+        let name = self.base().get_name().to_string();
+        let Some(definition) = WeaponDefinition::get(&name) else {
+            return;
+        };
+        let mut stats = definition.stats;
+        for attachment in &self.attachments {
+            attachment.apply(&mut stats);
+        }
+        let mastery = WeaponMastery::get(&name);
+        mastery.apply(&mut stats);
+        self.set_stats(stats);
+        // 用热量代替弹匣的武器不消耗子弹
+        self.ammo = if self.has_heat() {
+            self.clip
+        } else {
+            self.ammo.min(self.clip)
+        };
+        self.mastery_tier = mastery.get_tier();
+        let (_, color) = WeaponMastery::get_variant(self.mastery_tier);
+        self.base()
+            .get_node_as::<Sprite2D>("Sprite2D")
+            .set_self_modulate(color);
+    }
+
+    pub fn get_mastery_tier(&self) -> u32 {
+        self.mastery_tier
+    }
+
+    fn set_stats(&mut self, stats: WeaponStats) {
+        self.silenced = stats.silenced;
+        self.damage = stats.damage;
+        self.weight = stats.weight;
+        self.distance = stats.distance;
+        self.speed = stats.speed;
+        self.clip = stats.clip;
+        self.jitter = stats.jitter;
+        self.repel = stats.repel;
+        self.penetrate = stats.penetrate;
+        self.fire_cooldown = stats.fire_cooldown;
+        self.reload_time = stats.reload_time;
+        self.zoom = stats.zoom;
+        self.effect = stats.effect;
+        self.effect_chance = stats.effect_chance;
+        self.crit_chance = stats.crit_chance;
+        self.crit_damage = stats.crit_damage;
+        self.current_jitter = self.current_jitter.min(self.jitter);
+    }
+
     pub fn weapon_ready(&mut self) {
         self.state = WeaponState::Ready;
     }
 
+    // 武器挂在玩家的Weapon节点下
+    fn get_player(&self) -> Gd<RustPlayer> {
+        RustPlayer::get_by_index(self.get_player_index()).unwrap_or_else(RustPlayer::get)
+    }
+
+    fn get_player_index(&self) -> usize {
+        RustPlayer::find_owner_index(self.to_gd().upcast())
+    }
+
+    fn get_hud(&self) -> Gd<RustHUD> {
+        self.get_player().get_node_as::<RustHUD>("RustHUD")
+    }
+
     pub fn update_ammo_hud(&self) {
-        RustHUD::get().call_deferred(
+        if self.has_heat() {
+            // 热量武器不消耗子弹，只有拿在手上时才隐藏弹药
+            if self.base().is_visible() {
+                self.get_hud().call_deferred("hide_ammo_hud", &[]);
+            }
+            return;
+        }
+        self.get_hud().call_deferred(
             "update_ammo_hud",
             &[self.ammo.to_variant(), self.clip.to_variant()],
         );
     }
 
     pub fn update_jitter_hud(&self) {
-        RustHUD::get().call_deferred("update_jitter_hud", &[self.current_jitter.to_variant()]);
+        self.get_hud()
+            .call_deferred("update_jitter_hud", &[self.current_jitter.to_variant()]);
+    }
+
+    fn can_fire(&self) -> bool {
+        !(0 == self.ammo
+            || self.is_spinning_up()
+            || self.is_overheated()
+            || self.current_fire_cooldown > 0.0
+            || WeaponState::Reloading == self.state && !self.reload_part
+            || self.deploy_audio.is_playing()
+            || self.fire_bolt_pull_audio.is_playing()
+            || self.clip_part_in_audio.is_playing())
     }
 
+    /// 开一枪，返回是否射出了子弹
     pub fn fire(
         &mut self,
         player_damage: i64,
         player_distance: real,
         player_penetrate: real,
         player_repel: real,
-    ) {
-        if 0 == self.ammo
-            || self.current_fire_cooldown > 0.0
-            || WeaponState::Reloading == self.state && !self.reload_part
-            || self.deploy_audio.is_playing()
-            || self.fire_bolt_pull_audio.is_playing()
-            || self.clip_part_in_audio.is_playing()
-        {
-            return;
+    ) -> bool {
+        if !self.can_fire() {
+            return false;
         }
+        self.shot_ammo_type = self.load_round();
+        self.shot_round = SpecialRound::default();
+        let mut fired = false;
         let mut rng = rand::thread_rng();
         let vec: Vec<Gd<PackedScene>> = self.bullet_scenes.iter_shared().collect();
         for bullet_scene in vec {
@@ -265,7 +553,7 @@ impl RustWeapon {
                         player_repel,
                         &bullet_scene,
                         bullet_point.cast::<Node2D>().get_global_position(),
-                        self.get_random_direction(&mut rng, self.current_jitter),
+                        self.get_recoil_direction(&mut rng),
                     );
                     if r.is_err() {
                         break;
@@ -288,13 +576,14 @@ impl RustWeapon {
                     player_repel,
                     &bullet_scene,
                     bullet_point,
-                    self.get_random_direction(&mut rng, self.current_jitter),
+                    self.get_recoil_direction(&mut rng),
                 )
             };
             if r.is_err() {
                 godot_error!("Failed to instantiate bullet or grenade");
-                return;
+                break;
             }
+            fired = true;
             if self.current_flash_cooldown <= 0.0 {
                 self.fire_flash.restart();
                 self.current_flash_cooldown = self.fire_flash.get_lifetime() * 0.25;
@@ -307,8 +596,17 @@ impl RustWeapon {
                 }
             }
             self.fire_audio.play();
-            self.current_fire_cooldown = self.fire_cooldown;
-            self.ammo -= 1;
+            // 点射中途按点射间隔，打完一串后按开火冷却
+            self.current_fire_cooldown = if self.left_burst_shots > 1 {
+                self.burst_interval
+            } else {
+                self.fire_cooldown
+            };
+            if self.has_heat() {
+                self.add_heat();
+            } else {
+                self.ammo -= 1;
+            }
             self.state = WeaponState::Firing;
             self.update_ammo_hud();
             if !self.silenced {
@@ -329,20 +627,31 @@ impl RustWeapon {
                 NOISE_POSITION.store(NO_NOISE);
             }
         }
+        if fired {
+            self.left_burst_shots = self.left_burst_shots.saturating_sub(1);
+            self.recoil_shots += 1.0;
+        }
+        fired
     }
 
-    fn get_random_direction(&self, rng: &mut ThreadRng, jitter: real) -> Vector2 {
-        //增加子弹散射
-        self.base()
-            .get_global_position()
-            .direction_to(
-                self.get_mouse_position()
-                    + Vector2::new(
-                        rng.gen_range(-jitter..=jitter),
-                        rng.gen_range(-jitter..=jitter),
-                    ),
-            )
-            .normalized()
+    fn get_shot_damage(&self, player_damage: i64) -> i64 {
+        (player_damage.saturating_add(self.damage) as real * self.shot_damage_rate) as i64
+    }
+
+    /// 玩家升级的暴击加成，扣扳机时由玩家设置
+    pub fn set_crit_bonus(&mut self, crit_chance: real, crit_damage: real) {
+        self.crit_chance_bonus = crit_chance;
+        self.crit_damage_bonus = crit_damage;
+    }
+
+    // 子弹伤害，每发单独判定暴击
+    fn roll_bullet_damage(&self, player_damage: i64) -> (i64, bool) {
+        let damage = self.get_shot_damage(player_damage);
+        if !random_chance(self.crit_chance + self.crit_chance_bonus) {
+            return (damage, false);
+        }
+        let crit_damage = self.crit_damage + self.crit_damage_bonus;
+        ((damage as real * crit_damage) as i64, true)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -356,23 +665,41 @@ impl RustWeapon {
         bullet_point: Vector2,
         direction: Vector2,
     ) -> std::io::Result<()> {
+        if self.hitscan {
+            self.queue_hitscan(
+                player_damage,
+                player_distance,
+                player_penetrate,
+                player_repel,
+                bullet_point,
+                direction,
+            );
+            return Ok(());
+        }
         if let Some(mut bullet) = bullet_scene.try_instantiate_as::<RustBullet>() {
             bullet.set_global_position(bullet_point);
             let mut gd_mut = bullet.bind_mut();
             gd_mut.set_speed(self.speed);
             gd_mut.set_bullet_point(bullet_point);
             gd_mut.set_final_distance(player_distance + self.distance);
-            gd_mut.set_final_damage(player_damage.saturating_add(self.damage));
+            let (damage, crit) = self.roll_bullet_damage(player_damage);
+            gd_mut.set_final_damage(damage);
+            gd_mut.set_crit(crit);
+            gd_mut.set_falloff(self.falloff);
             gd_mut.set_final_penetrate(player_penetrate + self.penetrate);
             gd_mut.set_final_repel(player_repel + self.repel);
+            gd_mut.set_final_effect(self.roll_effect());
             gd_mut.set_direction(direction);
+            gd_mut.set_player_index(self.get_player_index());
+            gd_mut.set_weapon_name(self.base().get_name());
+            gd_mut.set_ammo_type(self.shot_ammo_type, self.shot_round.clone());
             drop(gd_mut);
-            if let Some(mut parent) = RustPlayer::get().get_parent() {
+            if let Some(mut parent) = self.get_player().get_parent() {
+                RustNetwork::name_spawned(&mut bullet.clone().upcast());
                 parent.add_child(&bullet);
                 if self.jitter > 0.0 {
                     self.current_jitter =
                         (self.current_jitter + self.jitter / 5.0).min(self.jitter);
-                    self.current_jitter_cooldown = self.fire_cooldown;
                     self.update_jitter_hud();
                 }
                 return Ok(());
@@ -383,11 +710,18 @@ impl RustWeapon {
             gd_mut.set_speed(self.speed);
             gd_mut.set_bullet_point(bullet_point);
             gd_mut.set_final_distance(player_distance + self.distance);
-            gd_mut.set_final_damage(player_damage.saturating_add(self.damage));
+            gd_mut.set_final_damage(self.get_shot_damage(player_damage));
             gd_mut.set_final_repel(player_repel + self.repel);
+            let effect = self.roll_effect();
+            if StatusEffect::None != effect {
+                gd_mut.set_effect(effect);
+            }
             gd_mut.set_direction(direction);
+            gd_mut.set_player_index(self.get_player_index());
+            gd_mut.set_weapon_name(self.base().get_name());
             drop(gd_mut);
-            if let Some(mut parent) = RustPlayer::get().get_parent() {
+            if let Some(mut parent) = self.get_player().get_parent() {
+                RustNetwork::name_spawned(&mut grenade.clone().upcast());
                 parent.add_child(&grenade);
                 if let Some(mut rocket) = self.base().try_get_node_as::<Sprite2D>("Rocket") {
                     rocket.set_visible(false);
@@ -400,12 +734,20 @@ impl RustWeapon {
         ))
     }
 
+    fn roll_effect(&self) -> StatusEffect {
+        if random_chance(self.effect_chance) {
+            self.effect
+        } else {
+            StatusEffect::None
+        }
+    }
+
     #[func]
     pub fn on_deploy_finished(&mut self) {
         if self.pull_after_deploy {
             self.fire_bolt_pull_audio.play();
         }
-        RustPlayer::get().call_deferred("zoom", &[]);
+        self.get_player().call_deferred("zoom", &[]);
     }
 
     #[func]
@@ -443,9 +785,16 @@ impl RustWeapon {
         self.reloading = self.reloading.max(0.0);
         self.clip_out_audio.play();
         self.state = WeaponState::Reloading;
+        CombatEventBus::emit(self.get_player_index(), CombatEvent::Reload);
         true
     }
 
+    /// 返还子弹，不超过弹匣容量
+    pub fn refund_ammo(&mut self, amount: i32) {
+        self.ammo = self.ammo.saturating_add(amount).min(self.clip);
+        self.update_ammo_hud();
+    }
+
     #[func]
     pub fn on_clip_out_finished(&mut self) {
         if self.reload_part {
@@ -475,7 +824,7 @@ impl RustWeapon {
         self.ammo += 1;
         self.ammo = self.ammo.min(self.clip);
         self.update_ammo_hud();
-        RustPlayer::get().call_deferred("reloading", &[]);
+        self.get_player().call_deferred("reloading", &[]);
         if self.ammo == self.clip {
             self.clip_in_audio.play();
             return;
@@ -505,11 +854,11 @@ impl RustWeapon {
         self.reloading = 0.0;
         self.current_fire_cooldown = 0.0;
         self.current_jitter = 0.0;
-        self.current_jitter_cooldown = 0.0;
+        self.recoil_shots = 0.0;
         self.ammo = self.clip;
         self.update_jitter_hud();
         self.update_ammo_hud();
-        RustPlayer::get().call_deferred("reloaded", &[]);
+        self.get_player().call_deferred("reloaded", &[]);
     }
 
     pub fn stop_reload(&mut self) {
@@ -522,15 +871,6 @@ impl RustWeapon {
         self.weapon_ready();
     }
 
-    pub fn get_mouse_position(&self) -> Vector2 {
-        self.base().get_canvas_transform().affine_inverse()
-            * self
-                .base()
-                .get_viewport()
-                .expect("Viewport not found")
-                .get_mouse_position()
-    }
-
     pub fn must_reload(&self) -> bool {
         0 == self.ammo
     }
@@ -0,0 +1,70 @@
+use super::*;
+use crate::{BASE_SPREAD_RATE, MAX_PLAYERS};
+use rand::Rng;
+use rand::rngs::ThreadRng;
+
+// 每个玩家当前武器的散布，HUD据此放大准星
+static SPREADS: [AtomicCell<real>; MAX_PLAYERS] = [const { AtomicCell::new(0.0) }; MAX_PLAYERS];
+
+#[godot_api(secondary)]
+impl RustWeapon {
+    pub fn set_accuracy_rate(&mut self, accuracy_rate: real) {
+        self.accuracy_rate = accuracy_rate;
+    }
+
+    /// 瞄准点附近随机散布的范围，固有散布加上连射累积的散布
+    pub fn get_spread(&self) -> real {
+        let mut base_spread = self.jitter * BASE_SPREAD_RATE;
+        if self.recoil_shots <= 0.0 {
+            base_spread *= self.first_shot_rate;
+        }
+        (base_spread + self.current_jitter) * self.accuracy_rate
+    }
+
+    pub fn get_player_spread(player_index: usize) -> real {
+        SPREADS[player_index].load()
+    }
+
+    // 按弹道取当前这一发的横向偏移，两发之间线性插值
+    fn get_recoil_kick(&self) -> real {
+        let len = self.recoil_pattern.len();
+        if 0 == len {
+            return 0.0;
+        }
+        let shots = self.recoil_shots.max(0.0);
+        let index = shots.floor() as usize;
+        let from = self.recoil_pattern[index % len];
+        let to = self.recoil_pattern[(index + 1) % len];
+        from + (to - from) * shots.fract()
+    }
+
+    pub(super) fn get_recoil_direction(&self, rng: &mut ThreadRng) -> Vector2 {
+        let position = self.base().get_global_position();
+        let aim_position = RustPlayer::get_aim_position(self.get_player_index());
+        let side = position.direction_to(aim_position).orthogonal();
+        //弹道偏移是固定的，只有散布是随机的
+        let spread = self.get_spread();
+        let offset = side * self.get_recoil_kick() * self.jitter
+            + Vector2::new(
+                rng.gen_range(-spread..=spread),
+                rng.gen_range(-spread..=spread),
+            );
+        position.direction_to(aim_position + offset).normalized()
+    }
+
+    // 松开扳机后恢复弹道和散布，同时上报当前武器的散布
+    pub(super) fn recover_recoil(&mut self, delta: f64) {
+        if !self.trigger_pulled && 0 == self.left_burst_shots && self.current_fire_cooldown <= 0.0 {
+            let recovered = self.recoil_recovery * delta as real;
+            self.recoil_shots = (self.recoil_shots - recovered).max(0.0);
+            if self.current_jitter > 0.0 {
+                self.current_jitter =
+                    (self.current_jitter - self.jitter / 5.0 * recovered).max(0.0);
+                self.update_jitter_hud();
+            }
+        }
+        if self.base().is_visible() {
+            SPREADS[self.get_player_index()].store(self.get_spread());
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::SAVE;
+use crate::{AmmoType, Attachment, FireMode, SAVE};
 use godot::builtin::StringName;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
@@ -26,6 +26,12 @@ impl Serialize for RustWeapon {
         state.serialize_field("reload_part", &self.reload_part)?;
         state.serialize_field("reloading", &self.reloading)?;
         state.serialize_field("ammo", &self.ammo)?;
+        state.serialize_field("attachments", &self.attachments)?;
+        state.serialize_field("fire_mode", &self.fire_mode)?;
+        state.serialize_field("ammo_type", &self.ammo_type)?;
+        state.serialize_field("spin_up", &self.spin_up)?;
+        state.serialize_field("heat", &self.heat)?;
+        state.serialize_field("overheat_time", &self.overheat_time)?;
         state.end()
     }
 }
@@ -47,6 +53,18 @@ struct WeaponData {
     reload_part: bool,
     reloading: real,
     ammo: i32,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+    #[serde(default)]
+    fire_mode: Option<FireMode>,
+    #[serde(default)]
+    ammo_type: AmmoType,
+    #[serde(default)]
+    spin_up: real,
+    #[serde(default)]
+    heat: real,
+    #[serde(default)]
+    overheat_time: real,
 }
 
 #[godot_api(secondary)]
@@ -85,6 +103,24 @@ impl RustWeapon {
                     self.reload_part = save_data.reload_part;
                     self.reloading = save_data.reloading;
                     self.ammo = save_data.ammo;
+                    self.attachments = save_data.attachments;
+                    if !self.attachments.is_empty() {
+                        // 存档只记录了部分属性，按配件重新计算
+                        self.refresh_stats();
+                    }
+                    if let Some(fire_mode) = save_data.fire_mode {
+                        self.set_fire_mode(fire_mode);
+                    }
+                    self.set_ammo_type(save_data.ammo_type);
+                    if self.has_spin() {
+                        self.spin_up = save_data.spin_up.clamp(0.0, 1.0);
+                    }
+                    if self.has_heat() {
+                        self.ammo = self.clip;
+                        self.heat = save_data.heat.clamp(0.0, 1.0);
+                        self.overheat_time = save_data.overheat_time.max(0.0);
+                    }
+                    self.update_mechanic_hud();
                     self.update_ammo_hud();
                 }
             }
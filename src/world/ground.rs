@@ -304,6 +304,17 @@ impl RustGround {
         RustWorld::get().get_node_as::<Self>("RustGround")
     }
 
+    /// 坐标是否位于草丛中
+    pub fn is_in_bush(position: Vector2) -> bool {
+        let Some(ground) = RustWorld::get().try_get_node_as::<Self>("RustGround") else {
+            return false;
+        };
+        let objects_high = &ground.bind().objects_high;
+        let cell = objects_high.local_to_map(objects_high.to_local(position));
+        objects_high.get_cell_source_id(cell) != -1
+            && objects_high.get_cell_atlas_coords(cell) == BUSH_ATLAS_POSITION
+    }
+
     pub fn get_objects_z_index() -> i32 {
         Self::get().bind().objects.get_z_index()
     }
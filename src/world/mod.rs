@@ -1,8 +1,10 @@
-use crate::PlayerState;
 use crate::entrance::RustEntrance;
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::save::RustSaveLoader;
+use crate::{CoopLives, MAX_PLAYERS, PlayerClass, PlayerState};
+use crossbeam_utils::atomic::AtomicCell;
 use godot::builtin::Vector2;
 use godot::classes::{
     Button, CanvasLayer, Control, Engine, HBoxContainer, INode2D, InputEvent, Label, Node, Node2D,
@@ -16,6 +18,12 @@ pub mod ground;
 
 static PAUSED: AtomicBool = AtomicBool::new(false);
 
+static COOP_LIVES: AtomicCell<CoopLives> = AtomicCell::new(CoopLives::Shared);
+
+// 入口界面给本机1P和2P选择的职业，联机队友的职业随加入请求发给主机
+static SELECTED_CLASSES: [AtomicCell<PlayerClass>; 2] =
+    [const { AtomicCell::new(PlayerClass::Survivor) }; 2];
+
 #[derive(GodotClass)]
 #[class(base=Node2D)]
 pub struct RustWorld {
@@ -25,7 +33,11 @@ pub struct RustWorld {
     #[doc = "是否加载存档"]
     #[export]
     load: bool,
+    #[doc = "双人模式的生命计算方式"]
+    #[export]
+    coop_lives: CoopLives,
     entrance_scene: OnReady<Gd<PackedScene>>,
+    player_scene: OnReady<Gd<PackedScene>>,
     rust_player: OnReady<Gd<RustPlayer>>,
     rust_level: OnReady<Gd<RustLevel>>,
     game_over: OnReady<Gd<CanvasLayer>>,
@@ -38,7 +50,9 @@ impl INode2D for RustWorld {
         Self {
             hell: false,
             load: false,
+            coop_lives: CoopLives::Shared,
             entrance_scene: OnReady::from_loaded("res://scenes/rust_entrance.tscn"),
+            player_scene: OnReady::from_loaded("res://scenes/rust_player.tscn"),
             rust_player: OnReady::from_node("RustPlayer"),
             rust_level: OnReady::from_node("RustLevel"),
             game_over: OnReady::from_node("CanvasLayer"),
@@ -50,6 +64,7 @@ impl INode2D for RustWorld {
         if Self::is_paused() {
             Self::resume();
         }
+        COOP_LIVES.store(self.coop_lives);
         let gd = self.to_gd();
         let container = self
             .game_over
@@ -79,13 +94,16 @@ impl INode2D for RustWorld {
         if self.hell {
             self.rust_level.bind_mut().enable_hell();
         }
-        if self.load {
+        // 存档只在主机上读写
+        if self.load && RustNetwork::is_authority() {
             RustSaveLoader::get().bind().load_game();
         }
     }
 
     fn input(&mut self, event: Gd<InputEvent>) {
-        if event.is_action_pressed("esc") {
+        if event.is_action_pressed("p2_join") && !Self::is_paused() {
+            self.join_teammate();
+        } else if event.is_action_pressed("esc") {
             if self.game_over.is_visible() {
                 self.game_over.set_visible(false);
                 Self::resume();
@@ -121,7 +139,9 @@ impl RustWorld {
 
     #[func]
     pub fn on_exit_pressed(&mut self) {
-        RustSaveLoader::get().bind().save_game();
+        if RustNetwork::is_authority() {
+            RustSaveLoader::get().bind().save_game();
+        }
         if let Some(world) = self.entrance_scene.try_instantiate_as::<RustEntrance>() {
             if let Some(tree) = self.base().get_tree() {
                 if let Some(mut root) = tree.get_root() {
@@ -135,8 +155,16 @@ impl RustWorld {
     #[func]
     pub fn on_continue_pressed(&mut self) {
         self.game_over.set_visible(false);
-        if PlayerState::Dead == RustPlayer::get_state() {
+        let team_out = (0..MAX_PLAYERS).all(|index| {
+            !RustPlayer::is_joined(index) || PlayerState::Dead == RustPlayer::get_state_of(index)
+        });
+        if team_out {
             self.rust_player.bind_mut().reborn();
+            for index in 1..MAX_PLAYERS {
+                if let Some(mut teammate) = RustPlayer::get_by_index(index) {
+                    teammate.bind_mut().reborn();
+                }
+            }
             self.rust_level.bind_mut().reset();
         } else {
             self.rust_level.bind_mut().start();
@@ -146,6 +174,66 @@ impl RustWorld {
         }
     }
 
+    // 2P中途加入，出生在1P身边
+    fn join_teammate(&mut self) {
+        if RustPlayer::is_joined(1) || RustNetwork::is_online() {
+            return;
+        }
+        if let Some(mut teammate) = self.player_scene.try_instantiate_as::<RustPlayer>() {
+            teammate.bind_mut().set_player_index(1);
+            teammate
+                .bind_mut()
+                .set_player_class(Self::get_player_class(1));
+            teammate.set_name("RustPlayer2P");
+            let position = self.rust_player.get_global_position() + Vector2::new(64.0, 0.0);
+            teammate.set_global_position(position);
+            self.base_mut().add_child(&teammate);
+        }
+    }
+
+    /// 联机队友中途加入，由主机生成后同步给所有客户端
+    pub fn join_remote_player(&mut self, peer_id: i32, player_class: PlayerClass) {
+        let Some(index) = (1..MAX_PLAYERS).find(|index| !RustPlayer::is_joined(*index)) else {
+            RustNetwork::kick(peer_id);
+            return;
+        };
+        if let Some(mut teammate) = self.player_scene.try_instantiate_as::<RustPlayer>() {
+            // 客户端通过名字得知玩家编号和所属连接
+            teammate.set_name(&RustPlayer::get_network_name(index, peer_id));
+            teammate.bind_mut().set_player_class(player_class);
+            let position = self.rust_player.get_global_position() + Vector2::new(64.0, 0.0);
+            teammate.set_global_position(position);
+            self.base_mut().add_child(&teammate);
+        }
+    }
+
+    pub fn leave_remote_player(&mut self, peer_id: i32) {
+        for index in 1..MAX_PLAYERS {
+            if let Some(mut teammate) = RustPlayer::get_by_index(index) {
+                if peer_id == teammate.bind().get_peer_id() {
+                    teammate.queue_free();
+                }
+            }
+        }
+    }
+
+    pub fn get_coop_lives() -> CoopLives {
+        COOP_LIVES.load()
+    }
+
+    /// 0为1P，1为本地2P
+    pub fn set_player_class(index: usize, player_class: PlayerClass) {
+        if let Some(selected) = SELECTED_CLASSES.get(index) {
+            selected.store(player_class);
+        }
+    }
+
+    pub fn get_player_class(index: usize) -> PlayerClass {
+        SELECTED_CLASSES
+            .get(index)
+            .map_or(PlayerClass::Survivor, AtomicCell::load)
+    }
+
     pub fn random_position() -> Vector2 {
         crate::random_position(275.0, 500.0)
     }
@@ -1,5 +1,7 @@
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
-use crate::{PlayerState, ZombieState};
+use crate::{PlayerState, StatusEffect, ZombieState, random_chance};
+use godot::builtin::real;
 use godot::classes::{AnimatedSprite2D, IAnimatedSprite2D, Object};
 use godot::obj::{Base, Gd, WithBaseField, WithUserSignals};
 use godot::register::{GodotClass, godot_api};
@@ -8,6 +10,8 @@ use godot::register::{GodotClass, godot_api};
 #[class(base=AnimatedSprite2D)]
 pub struct ZombieAnimation {
     damage: i64,
+    effect: StatusEffect,
+    effect_chance: real,
     hurt_frames: Vec<i32>,
     player_in_area: bool,
     zombie_state: ZombieState,
@@ -19,6 +23,8 @@ impl IAnimatedSprite2D for ZombieAnimation {
     fn init(base: Base<AnimatedSprite2D>) -> Self {
         Self {
             damage: 0,
+            effect: StatusEffect::None,
+            effect_chance: 0.0,
             hurt_frames: Vec::new(),
             player_in_area: false,
             zombie_state: ZombieState::Guard,
@@ -35,7 +41,8 @@ impl IAnimatedSprite2D for ZombieAnimation {
             if !frames.has_animation("attack") {
                 return;
             }
-            match RustPlayer::get_state() {
+            let player_index = RustPlayer::get_nearest_index(base.get_global_position());
+            match RustPlayer::get_state_of(player_index) {
                 PlayerState::Dead => frames.set_animation_loop("attack", false),
                 _ => {
                     frames.set_animation_loop("attack", true);
@@ -84,18 +91,34 @@ impl ZombieAnimation {
         self.damage = damage;
     }
 
+    pub fn set_effect(&mut self, effect: StatusEffect, effect_chance: real) {
+        self.effect = effect;
+        self.effect_chance = effect_chance;
+    }
+
     #[func]
     pub fn on_animated_sprite_2d_frame_changed(&mut self) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         let base = self.base();
+        // 攻击离僵尸最近的玩家
+        let player_index = RustPlayer::get_nearest_index(base.get_global_position());
         if self.player_in_area
-            && PlayerState::Dead != RustPlayer::get_state()
+            && PlayerState::Dead != RustPlayer::get_state_of(player_index)
             && ZombieState::Attack == self.zombie_state
             && base.get_animation() == "attack".into()
             && self.hurt_frames.contains(&base.get_frame())
         {
             // 伤害玩家
             let position = base.get_global_position();
-            RustPlayer::get().bind_mut().on_hit(self.damage, position);
+            let Some(mut player) = RustPlayer::get_by_index(player_index) else {
+                return;
+            };
+            player.bind_mut().on_hit(self.damage, position);
+            if random_chance(self.effect_chance) {
+                player.bind_mut().apply_effect(self.effect);
+            }
         }
     }
 }
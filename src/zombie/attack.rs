@@ -1,4 +1,5 @@
 use crate::is_survivor;
+use crate::network::RustNetwork;
 use crate::zombie::RustZombie;
 use crate::zombie::animation::ZombieAnimation;
 use crate::zombie::boss::RustBoss;
@@ -36,6 +37,9 @@ impl ZombieAttackArea {
 
     #[func]
     pub fn on_area_2d_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if is_survivor(&***body) {
             self.get_zombie_animation()
                 .signals()
@@ -47,6 +51,9 @@ impl ZombieAttackArea {
 
     #[func]
     pub fn on_area_2d_body_exited(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if is_survivor(&***body) {
             self.attack();
             if let Some(mut tree) = self.base().get_tree() {
@@ -114,6 +121,9 @@ impl ZombieDamageArea {
 
     #[func]
     pub fn on_area_2d_body_exited(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if is_survivor(&***body) {
             self.get_zombie_animation()
                 .signals()
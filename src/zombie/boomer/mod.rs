@@ -1,16 +1,19 @@
-use crate::common::RustMessage;
+use crate::damage_number::RustDamageNumbers;
+use crate::effect::StatusEffects;
 use crate::grenade::RustGrenade;
+use crate::hit_zone::ZoneHit;
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::weapon::RustWeapon;
 use crate::world::RustWorld;
-use crate::zombie::NEXT_ATTACK_DIRECTION;
 use crate::zombie::animation::ZombieAnimation;
 use crate::zombie::explode::ZombieExplodeArea;
+use crate::zombie::{NEXT_ATTACK_DIRECTION, ZOMBIE_SYNC_PROPERTIES};
 use crate::{
     BOOMER_ALARM_DISTANCE, BOOMER_EXPLODE_COUNTDOWN, BOOMER_MOVE_SPEED, GRENADE_ALARM_DISTANCE,
-    GUN_ALARM_DISTANCE, MESSAGE, NO_NOISE, PITCHER_ALARM_DISTANCE, PLAYER_ALARM_DISTANCE,
-    PlayerState, SAVE, ZOMBIE_ALARM_TIME, ZOMBIE_MAX_DISTANCE, ZOMBIE_MAX_HEALTH,
+    GUN_ALARM_DISTANCE, HitZone, NO_NOISE, PITCHER_ALARM_DISTANCE, PLAYER_ALARM_DISTANCE,
+    PlayerState, SAVE, StatusEffect, ZOMBIE_ALARM_TIME, ZOMBIE_MAX_DISTANCE, ZOMBIE_MAX_HEALTH,
     ZOMBIE_MIN_TRACK_DISTANCE, ZOMBIE_PURSUIT_DISTANCE, ZOMBIE_RAMPAGE_TIME,
     ZOMBIE_ROTATE_COOLDOWN, ZombieState, not_normal_zombie, random_bool, random_direction,
     random_position,
@@ -19,9 +22,8 @@ use crossbeam_utils::atomic::AtomicCell;
 use godot::builtin::{GString, Vector2, real};
 use godot::classes::{
     AnimatedSprite2D, Area2D, AudioStreamPlayer2D, CharacterBody2D, CollisionShape2D, Control,
-    GpuParticles2D, ICharacterBody2D, InputEvent, Label, ProgressBar, RemoteTransform2D,
+    GpuParticles2D, ICharacterBody2D, InputEvent, Label, Node, ProgressBar, RemoteTransform2D,
 };
-use godot::meta::ToGodot;
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
 
@@ -58,6 +60,10 @@ pub struct RustBoomer {
     alarm_time: real,
     state: ZombieState,
     current_speed: real,
+    // 追击的玩家编号
+    target: usize,
+    zone_hit: ZoneHit,
+    effects: StatusEffects,
     collision: Vector2,
     current_alarm_time: real,
     current_rotate_cooldown: real,
@@ -99,10 +105,13 @@ impl ICharacterBody2D for RustBoomer {
             health: ZOMBIE_MAX_HEALTH,
             state: ZombieState::Guard,
             current_speed: BOOMER_MOVE_SPEED * 0.75,
+            target: 0,
             collision: Vector2::ZERO,
             current_alarm_time: 0.0,
             current_rotate_cooldown: 0.0,
             pursuit_direction: random_bool(),
+            zone_hit: ZoneHit::default(),
+            effects: StatusEffects::default(),
             current_flash_cooldown: 0.0,
             hud: OnReady::from_node("RemoteTransform2D"),
             head_shape2d: OnReady::from_node("HeadShape2D"),
@@ -128,10 +137,20 @@ impl ICharacterBody2D for RustBoomer {
         if self.hud.is_instance_valid() {
             self.hud.set_global_rotation_degrees(0.0);
         }
+        if !RustNetwork::is_authority() {
+            // 客户端只展示主机同步的结果
+            return;
+        }
         if RustWorld::is_paused() || ZombieState::Dead == self.state {
             return;
         }
-        let player_state = RustPlayer::get_state();
+        self.update_effects(delta);
+        if ZombieState::Dead == self.state || self.effects.is_stunned() {
+            return;
+        }
+        // 双人时追击威胁最大的玩家
+        self.target = RustPlayer::get_target_index(self.base().get_global_position());
+        let player_state = RustPlayer::get_state_of(self.target);
         if PlayerState::Dead == player_state {
             self.move_back();
             return;
@@ -142,7 +161,7 @@ impl ICharacterBody2D for RustBoomer {
         self.current_rotate_cooldown -= delta as real;
         self.rampage_time = (self.rampage_time - delta as real).max(0.0);
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         let distance = zombie_position.distance_to(player_position);
         if distance >= ZOMBIE_MAX_DISTANCE {
             //解决刷新僵尸导致的体积碰撞问题
@@ -168,7 +187,7 @@ impl ICharacterBody2D for RustBoomer {
             } else {
                 self.guard();
             }
-            if distance <= ZOMBIE_PURSUIT_DISTANCE
+            if distance <= ZOMBIE_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target)
                 && self.current_alarm_time > 0.0
                 && self.is_face_to_user()
             {
@@ -201,7 +220,9 @@ impl ICharacterBody2D for RustBoomer {
         }
         //撞到僵尸了
         self.collision = Vector2::ZERO;
-        if let Some(collision) = character_body2d.move_and_collide(velocity) {
+        if let Some(collision) =
+            character_body2d.move_and_collide(velocity * self.effects.speed_rate())
+        {
             // 发出排斥力的方向
             let from = collision.get_normal();
             if let Some(object) = collision.get_collider() {
@@ -224,6 +245,8 @@ impl ICharacterBody2D for RustBoomer {
     }
 
     fn ready(&mut self) {
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &ZOMBIE_SYNC_PROPERTIES);
         self.die_flash.set_visible(false);
         let gd = self.to_gd();
         self.attack_scream_audio
@@ -272,19 +295,33 @@ impl RustBoomer {
         }
     }
 
+    #[func]
+    pub fn on_zone_hit(
+        &mut self,
+        hit_val: i64,
+        direction: Vector2,
+        repel: real,
+        hit_position: Vector2,
+        zone: HitZone,
+        crit: bool,
+    ) {
+        let effect = self.zone_hit.record(zone, crit);
+        self.apply_effect(effect);
+        self.on_hit(hit_val, direction, repel, hit_position);
+    }
+
     #[func]
     pub fn on_hit(&mut self, hit_val: i64, direction: Vector2, repel: real, hit_position: Vector2) {
+        let hit_val = self.effects.scale_damage(hit_val);
+        let (hit_zone, hit_crit) = self.zone_hit.take();
         let zombie_position = self.base().get_global_position();
-        #[allow(clippy::borrow_interior_mutable_const)]
-        if let Some(mut hit_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
-            hit_label.set_global_position(zombie_position);
-            if let Some(tree) = self.base().get_tree() {
-                if let Some(mut root) = tree.get_root() {
-                    root.add_child(&hit_label);
-                    hit_label.bind_mut().show_hit_value(hit_val);
-                }
-            }
-        }
+        RustDamageNumbers::push(
+            self.base().instance_id(),
+            zombie_position,
+            hit_val,
+            hit_zone,
+            hit_crit,
+        );
         if !self.invincible {
             let health = self.health;
             self.health = if hit_val > 0 {
@@ -312,8 +349,55 @@ impl RustBoomer {
         }
     }
 
+    #[func]
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(effect);
+    }
+
+    /// 玩家施加的效果，持续伤害的分数记给这名玩家
+    #[func]
+    pub fn apply_player_effect(&mut self, effect: StatusEffect, player_index: u32) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply_from(effect, Some(player_index as usize));
+    }
+
+    // 被闪光弹致盲后丢失警觉
+    #[func]
+    pub fn on_flashed(&mut self) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(StatusEffect::Stun);
+        self.effects.apply(StatusEffect::Blind);
+        self.current_alarm_time = 0.0;
+    }
+
+    // 持续伤害不会击退僵尸
+    pub fn update_effects(&mut self, delta: f64) {
+        let zombie = self.base().instance_id();
+        let position = self.base().get_global_position();
+        let damage = self.effects.process_zombie(delta, zombie, position);
+        if damage > 0 {
+            if !self.invincible {
+                self.health = self.health.saturating_sub(damage as u32);
+            }
+            if 0 == self.health {
+                self.die();
+            }
+        }
+        if ZombieState::Dead != self.state {
+            let color = self.effects.color();
+            self.animated_sprite2d.set_modulate(color);
+        }
+    }
+
     pub fn flash(&mut self) {
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         self.base_mut().look_at(-player_position);
         self.base_mut()
             .set_global_position(player_position + random_position(900.0, 1100.0));
@@ -332,7 +416,7 @@ impl RustBoomer {
         //僵尸往玩家相反的方向移动一段距离
         self.guard();
         let zombie_position = self.base().get_global_position();
-        let from_player_dir = RustPlayer::get_position()
+        let from_player_dir = RustPlayer::get_position_of(self.target)
             .direction_to(zombie_position)
             .normalized();
         let speed = self.current_speed;
@@ -342,6 +426,7 @@ impl RustBoomer {
     }
 
     // 看到玩家不会马上狂暴，而是累计时间条，类似刺客信条
+    #[func]
     pub fn is_alarmed(&self) -> bool {
         self.current_alarm_time >= self.alarm_time
     }
@@ -356,7 +441,9 @@ impl RustBoomer {
         } else {
             alarm_progress.set_visible(true);
         }
-        if self.get_to_player_distance() <= ZOMBIE_PURSUIT_DISTANCE && self.is_face_to_user() {
+        // 潜行和草丛会缩短僵尸的发现距离
+        let pursuit_distance = ZOMBIE_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target);
+        if self.get_to_player_distance() <= pursuit_distance && self.is_face_to_user() {
             self.current_alarm_time =
                 (self.current_alarm_time + delta as real).min(self.alarm_time);
         } else {
@@ -377,7 +464,7 @@ impl RustBoomer {
     }
 
     pub fn is_rampage_run(&self) -> bool {
-        if PlayerState::Dead == RustPlayer::get_state() {
+        if PlayerState::Dead == RustPlayer::get_state_of(self.target) {
             return false;
         }
         self.rampage_time <= 0.0
@@ -385,7 +472,7 @@ impl RustBoomer {
 
     pub fn get_to_player_distance(&self) -> real {
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         zombie_position.distance_to(player_position)
     }
 
@@ -396,8 +483,14 @@ impl RustBoomer {
     }
 
     pub fn is_face_to_user(&self) -> bool {
+        if self.effects.has(StatusEffect::Blind) {
+            return false;
+        }
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
+        if RustGrenade::is_vision_blocked(zombie_position, player_position) {
+            return false;
+        }
         let to_player_dir = zombie_position.direction_to(player_position).normalized();
         let angle = self
             .get_current_direction()
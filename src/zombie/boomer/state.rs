@@ -1,8 +1,13 @@
 use super::*;
+use crate::combat::CombatEventBus;
+use crate::damage::DamageSource;
 use crate::level::generator::ZombieGenerator;
-use crate::{BOOMER_DAMAGE, BOOMER_REPEL, EXPLODE_AUDIOS, is_survivor, is_zombie};
+use crate::perk::RustPerkDrop;
+use crate::{Achievement, PERK_DROP_CHANCE};
+use crate::{BOOMER_DAMAGE, BOOMER_REPEL, EXPLODE_AUDIOS, StatusEffect, is_survivor, is_zombie};
 use godot::builtin::Callable;
 use godot::global::godot_error;
+use godot::meta::ToGodot;
 
 #[godot_api(secondary)]
 impl RustBoomer {
@@ -79,6 +84,7 @@ impl RustBoomer {
         }
         self.current_speed = 0.0;
         self.state = ZombieState::Dead;
+        self.effects.clear();
         if self.die_audio.is_inside_tree() && self.detonable {
             //播放爆炸音效
             if self.die_audio.get_stream().is_none() {
@@ -93,6 +99,8 @@ impl RustBoomer {
             self.die_flash.play_ex().name("default").done();
             self.animated_sprite2d.queue_free();
             let position = self.base().get_global_position();
+            let zombie_damage =
+                (BOOMER_DAMAGE as real * DamageSource::zombie().get_zombie_rate()) as i64;
             for mut body in self
                 .zombie_damage_area
                 .get_overlapping_bodies()
@@ -102,22 +110,24 @@ impl RustBoomer {
                     continue;
                 }
                 if is_survivor(&***body) {
-                    body.cast::<RustPlayer>()
-                        .bind_mut()
-                        .on_hit(BOOMER_DAMAGE, position);
+                    let mut player = body.cast::<RustPlayer>();
+                    player.bind_mut().on_hit(BOOMER_DAMAGE, position);
+                    player.bind_mut().apply_effect(StatusEffect::Burning);
                 } else if is_zombie(&***body) {
-                    if position != body.get_global_position() {
+                    if zombie_damage > 0 && position != body.get_global_position() {
                         let direction = position.direction_to(body.get_global_position());
                         body.call_deferred(
                             "on_hit",
                             &[
-                                BOOMER_DAMAGE.to_variant(),
+                                zombie_damage.to_variant(),
                                 direction.to_variant(),
                                 BOOMER_REPEL.to_variant(),
                                 position.to_variant(),
                             ],
                         );
-                        RustPlayer::get().call_deferred("add_score", &[BOOMER_DAMAGE.to_variant()]);
+                        body.call_deferred("apply_effect", &[StatusEffect::Burning.to_variant()]);
+                        // 被自爆者炸到不给分数，也不算任何玩家的击杀
+                        CombatEventBus::clear_hit(body.instance_id());
                     }
                 } else if body.is_class("RustGrenade") {
                     // ok
@@ -152,6 +162,13 @@ impl RustBoomer {
         self.run_audio.queue_free();
         self.rampage_audio.queue_free();
         self.attack_scream_audio.queue_free();
+        let position = self.base().get_global_position();
+        CombatEventBus::on_zombie_killed(
+            self.base().instance_id(),
+            position,
+            Achievement::BoomerBuster,
+        );
+        RustPerkDrop::try_drop(position, PERK_DROP_CHANCE);
         // 击杀僵尸确认
         if let Some(level) = RustLevel::get() {
             level
@@ -1,20 +1,25 @@
+use crate::achievement::RustAchievements;
 use crate::common::RustMessage;
+use crate::damage_number::RustDamageNumbers;
+use crate::effect::StatusEffects;
+use crate::hit_zone::ZoneHit;
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::world::RustWorld;
-use crate::zombie::NEXT_ATTACK_DIRECTION;
 use crate::zombie::animation::ZombieAnimation;
 use crate::zombie::attack::{ZombieAttackArea, ZombieDamageArea};
 use crate::zombie::bump::BossBumpArea;
+use crate::zombie::{NEXT_ATTACK_DIRECTION, ZOMBIE_SYNC_PROPERTIES};
 use crate::{
     BOSS_BUMP_DISTANCE, BOSS_DAMAGE, BOSS_MAX_BODY_COUNT, BOSS_MAX_HEALTH, BOSS_MOVE_SPEED,
-    MESSAGE, PlayerState, ZOMBIE_MAX_DISTANCE, ZombieState, is_boss, not_boss, random_bool,
-    random_position,
+    BOSS_VULNERABLE_CHANCE, HitZone, MAX_PLAYERS, MESSAGE, PlayerState, StatusEffect,
+    ZOMBIE_MAX_DISTANCE, ZombieState, is_boss, not_boss, random_bool, random_position,
 };
 use godot::builtin::{GString, Vector2, real};
 use godot::classes::{
     AudioStreamPlayer2D, CharacterBody2D, CollisionShape2D, Control, GpuParticles2D,
-    ICharacterBody2D, InputEvent, KinematicCollision2D, Label, PhysicsBody2D, ProgressBar,
+    ICharacterBody2D, InputEvent, KinematicCollision2D, Label, Node, PhysicsBody2D, ProgressBar,
     RemoteTransform2D,
 };
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
@@ -47,12 +52,20 @@ pub struct RustBoss {
     speed: real,
     state: ZombieState,
     current_speed: real,
+    // 追击的玩家编号
+    target: usize,
+    zone_hit: ZoneHit,
+    effects: StatusEffects,
     hurt_frames: Vec<i32>,
     collision: Vector2,
     pursuit_direction: bool,
     last_player_position: Vector2,
     last_record_time: Instant,
     record_cooldown: Duration,
+    // 出现时每个玩家的累计受伤次数，被击杀时击杀者的次数未变化即为无伤
+    hurt_marks: [u64; MAX_PLAYERS],
+    // 被近战重击后剩余的硬直时间
+    left_stagger_time: f64,
     hud: OnReady<Gd<RemoteTransform2D>>,
     head_shape2d: OnReady<Gd<CollisionShape2D>>,
     collision_shape2d: OnReady<Gd<CollisionShape2D>>,
@@ -85,13 +98,18 @@ impl ICharacterBody2D for RustBoss {
             health: BOSS_MAX_HEALTH,
             state: ZombieState::Guard,
             current_speed: BOSS_MOVE_SPEED * 0.75,
+            target: 0,
             // hurt_frames: vec![26, 27, 28, 29, 30],
             hurt_frames: vec![2, 3, 4, 5],
             collision: Vector2::ZERO,
             pursuit_direction: random_bool(),
+            zone_hit: ZoneHit::default(),
+            effects: StatusEffects::default(),
             last_player_position: Vector2::ZERO,
             last_record_time: Instant::now(),
             record_cooldown: Duration::from_secs(3),
+            hurt_marks: [0; MAX_PLAYERS],
+            left_stagger_time: 0.0,
             hud: OnReady::from_node("RemoteTransform2D"),
             head_shape2d: OnReady::from_node("HeadShape2D"),
             collision_shape2d: OnReady::from_node("CollisionShape2D"),
@@ -112,20 +130,35 @@ impl ICharacterBody2D for RustBoss {
         }
     }
 
-    fn process(&mut self, _delta: f64) {
+    fn process(&mut self, delta: f64) {
         if self.hud.is_instance_valid() {
             self.hud.set_global_rotation_degrees(0.0);
         }
+        if !RustNetwork::is_authority() {
+            // 客户端只展示主机同步的结果
+            return;
+        }
         if RustWorld::is_paused() {
             return;
         }
+        if ZombieState::Dead != self.state {
+            self.update_effects(delta);
+        }
         if ZombieState::Dead == self.state {
             if BODY_COUNT.load(Ordering::Acquire) >= BOSS_MAX_BODY_COUNT {
                 self.clean_body();
             }
             return;
         }
-        let player_state = RustPlayer::get_state();
+        if self.left_stagger_time > 0.0 {
+            // 硬直中无法移动和攻击
+            self.left_stagger_time -= delta;
+            self.update_hp_progress_hud();
+            return;
+        }
+        // 双人时追击威胁最大的玩家
+        self.target = RustPlayer::get_target_index(self.base().get_global_position());
+        let player_state = RustPlayer::get_state_of(self.target);
         if PlayerState::Dead == player_state {
             self.move_back();
             return;
@@ -133,7 +166,7 @@ impl ICharacterBody2D for RustBoss {
         if ZombieState::Attack == self.state || PlayerState::Impact == player_state {
             return;
         }
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         let now = Instant::now();
         if now.duration_since(self.last_record_time) >= self.record_cooldown {
             self.last_player_position = player_position;
@@ -172,7 +205,11 @@ impl ICharacterBody2D for RustBoss {
         }
         let speed = self.current_speed * 8.0;
         self.collision = Vector2::ZERO;
-        if let Some(collision) = self.base.to_gd().move_and_collide(velocity) {
+        if let Some(collision) = self
+            .base
+            .to_gd()
+            .move_and_collide(velocity * self.effects.speed_rate())
+        {
             // 发出排斥力的方向
             let from = collision.get_normal();
             if let Some(object) = collision.get_collider() {
@@ -198,16 +235,20 @@ impl ICharacterBody2D for RustBoss {
     }
 
     fn ready(&mut self) {
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &ZOMBIE_SYNC_PROPERTIES);
         let gd = self.to_gd();
         self.die_audio
             .signals()
             .finished()
             .connect_obj(&gd, Self::clean_audio);
         self.born_audio.play();
+        self.hurt_marks = std::array::from_fn(RustAchievements::get_hurt_count);
         self.guard();
         let mut animated_sprite2d = self.animated_sprite2d.bind_mut();
         animated_sprite2d.set_hurt_frames(self.hurt_frames.clone());
         animated_sprite2d.set_damage(BOSS_DAMAGE);
+        animated_sprite2d.set_effect(StatusEffect::Vulnerable, BOSS_VULNERABLE_CHANCE);
         if !self.boss_name.is_empty() {
             let name = self.boss_name.clone();
             let mut name_label = self.hud.get_node_as::<Label>("Name");
@@ -242,19 +283,33 @@ impl RustBoss {
         }
     }
 
+    #[func]
+    pub fn on_zone_hit(
+        &mut self,
+        hit_val: i64,
+        direction: Vector2,
+        repel: real,
+        hit_position: Vector2,
+        zone: HitZone,
+        crit: bool,
+    ) {
+        let effect = self.zone_hit.record(zone, crit);
+        self.apply_effect(effect);
+        self.on_hit(hit_val, direction, repel, hit_position);
+    }
+
     #[func]
     pub fn on_hit(&mut self, hit_val: i64, direction: Vector2, repel: real, hit_position: Vector2) {
+        let hit_val = self.effects.scale_damage(hit_val);
+        let (hit_zone, hit_crit) = self.zone_hit.take();
         let zombie_position = self.base().get_global_position();
-        #[allow(clippy::borrow_interior_mutable_const)]
-        if let Some(mut hit_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
-            hit_label.set_global_position(zombie_position);
-            if let Some(tree) = self.base().get_tree() {
-                if let Some(mut root) = tree.get_root() {
-                    root.add_child(&hit_label);
-                    hit_label.bind_mut().show_hit_value(hit_val);
-                }
-            }
-        }
+        RustDamageNumbers::push(
+            self.base().instance_id(),
+            zombie_position,
+            hit_val,
+            hit_zone,
+            hit_crit,
+        );
         if !self.invincible {
             let health = self.health;
             self.health = if hit_val > 0 {
@@ -281,8 +336,56 @@ impl RustBoss {
         }
     }
 
+    #[func]
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        self.apply_effect_from(effect, None);
+    }
+
+    /// 玩家施加的效果，持续伤害的分数记给这名玩家
+    #[func]
+    pub fn apply_player_effect(&mut self, effect: StatusEffect, player_index: u32) {
+        self.apply_effect_from(effect, Some(player_index as usize));
+    }
+
+    fn apply_effect_from(&mut self, effect: StatusEffect, source: Option<usize>) {
+        // BOSS免疫眩晕
+        if ZombieState::Dead == self.state || StatusEffect::Stun == effect {
+            return;
+        }
+        self.effects.apply_from(effect, source);
+    }
+
+    // BOSS不会被闪晕，只会减速
+    #[func]
+    pub fn on_flashed(&mut self) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(StatusEffect::Slow);
+        self.effects.apply(StatusEffect::Blind);
+    }
+
+    // 持续伤害不会击退僵尸
+    pub fn update_effects(&mut self, delta: f64) {
+        let zombie = self.base().instance_id();
+        let position = self.base().get_global_position();
+        let damage = self.effects.process_zombie(delta, zombie, position);
+        if damage > 0 {
+            if !self.invincible {
+                self.health = self.health.saturating_sub(damage as u32);
+            }
+            if 0 == self.health {
+                self.die();
+            }
+        }
+        if ZombieState::Dead != self.state {
+            let color = self.effects.color();
+            self.animated_sprite2d.set_modulate(color);
+        }
+    }
+
     pub fn flash(&mut self) {
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         self.base_mut()
             .set_global_position(player_position + random_position(1000.0, 1100.0));
     }
@@ -304,7 +407,7 @@ impl RustBoss {
         //僵尸往玩家相反的方向移动一段距离
         self.guard();
         let zombie_position = self.base().get_global_position();
-        let from_player_dir = RustPlayer::get_position()
+        let from_player_dir = RustPlayer::get_position_of(self.target)
             .direction_to(zombie_position)
             .normalized();
         let speed = self.current_speed;
@@ -1,6 +1,11 @@
 use super::*;
+use crate::Achievement;
+use crate::achievement::RustAchievements;
+use crate::combat::CombatEventBus;
 use crate::level::generator::ZombieGenerator;
+use crate::perk::RustPerkDrop;
 use crate::world::ground::RustGround;
+use godot::builtin::Color;
 
 #[godot_api(secondary)]
 impl RustBoss {
@@ -50,11 +55,41 @@ impl RustBoss {
         self.notify_animation();
     }
 
+    /// 近战重击和连击最后一下会打出硬直
+    pub fn stagger(&mut self, time: f64) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        if self.left_stagger_time <= 0.0 {
+            let zombie_position = self.base().get_global_position();
+            #[allow(clippy::borrow_interior_mutable_const)]
+            if let Some(mut stagger_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
+                stagger_label.set_global_position(zombie_position);
+                if let Some(tree) = self.base().get_tree() {
+                    if let Some(mut root) = tree.get_root() {
+                        root.add_child(&stagger_label);
+                        stagger_label.bind_mut().show_message("STAGGER");
+                    }
+                }
+            }
+        }
+        self.left_stagger_time = self.left_stagger_time.max(time);
+        self.animated_sprite2d.play_ex().name("guard").done();
+        self.current_speed = 0.0;
+        self.state = ZombieState::Hit;
+        self.notify_animation();
+    }
+
     pub fn attack(&mut self) {
-        if ZombieState::Dead == self.state || !self.attackable {
+        if ZombieState::Dead == self.state
+            || !self.attackable
+            || self.effects.is_stunned()
+            || self.left_stagger_time > 0.0
+        {
             return;
         }
-        self.base_mut().look_at(RustPlayer::get_position());
+        let player_position = RustPlayer::get_position_of(self.target);
+        self.base_mut().look_at(player_position);
         self.animated_sprite2d.play_ex().name("attack").done();
         self.current_speed = self.speed * 0.75;
         self.state = ZombieState::Attack;
@@ -62,7 +97,7 @@ impl RustBoss {
             + self
                 .base()
                 .get_global_position()
-                .direction_to(RustPlayer::get_position());
+                .direction_to(RustPlayer::get_position_of(self.target));
         NEXT_ATTACK_DIRECTION.store(direction.normalized());
         if self.attack_audio.is_inside_tree() {
             self.attack_audio.play();
@@ -78,6 +113,8 @@ impl RustBoss {
         if ZombieState::Dead == self.state {
             return;
         }
+        self.effects.clear();
+        self.animated_sprite2d.set_modulate(Color::WHITE);
         self.animated_sprite2d.play_ex().name("die").done();
         self.current_speed = 0.0;
         self.state = ZombieState::Dead;
@@ -109,6 +146,18 @@ impl RustBoss {
                 timer.connect("timeout", &self.base().callable("clean_body"));
             }
         }
+        let position = self.base().get_global_position();
+        let killer = CombatEventBus::on_zombie_killed(
+            self.base().instance_id(),
+            position,
+            Achievement::BossHunter,
+        );
+        RustPerkDrop::try_drop(position, 1.0);
+        if let Some(player_index) = killer {
+            if RustAchievements::get_hurt_count(player_index) == self.hurt_marks[player_index] {
+                RustAchievements::unlock(Achievement::Untouchable);
+            }
+        }
         // 击杀僵尸确认
         if let Some(level) = RustLevel::get() {
             level
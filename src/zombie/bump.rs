@@ -1,6 +1,7 @@
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::zombie::boss::RustBoss;
-use crate::{BOSS_DAMAGE, ZombieState, is_survivor};
+use crate::{BOSS_DAMAGE, StatusEffect, ZombieState, is_survivor};
 use godot::classes::{Area2D, IArea2D, Node2D, Object};
 use godot::obj::{Base, Gd, WithBaseField, WithUserSignals};
 use godot::register::{GodotClass, godot_api};
@@ -49,6 +50,9 @@ impl BossBumpArea {
 
     #[func]
     pub fn on_area_2d_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if let Ok(boss) = self
             .base()
             .get_parent()
@@ -66,9 +70,10 @@ impl BossBumpArea {
         {
             // 撞击玩家，如果无冷却就会一直撞击，不攻击
             let position = self.base().get_global_position();
-            RustPlayer::get()
-                .bind_mut()
-                .on_impact(BOSS_DAMAGE * 4, position);
+            let mut player = body.cast::<RustPlayer>();
+            player.bind_mut().on_impact(BOSS_DAMAGE * 4, position);
+            // 被撞后短暂减速
+            player.bind_mut().apply_effect(StatusEffect::Slow);
             self.last_bump_time = now;
         }
     }
@@ -1,4 +1,5 @@
 use crate::is_survivor;
+use crate::network::RustNetwork;
 use crate::zombie::boomer::RustBoomer;
 use godot::classes::{Area2D, IArea2D, Node, Node2D, Object};
 use godot::obj::{Base, Gd, WithBaseField, WithUserSignals};
@@ -30,6 +31,9 @@ impl ZombieExplodeArea {
 
     #[func]
     pub fn on_area_2d_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if is_survivor(&***body) {
             if let Ok(mut boomer) = self.get_parent().try_cast::<RustBoomer>() {
                 if !boomer.bind().is_face_to_user() {
@@ -1,6 +1,9 @@
-use crate::common::RustMessage;
+use crate::damage_number::RustDamageNumbers;
+use crate::effect::StatusEffects;
 use crate::grenade::RustGrenade;
+use crate::hit_zone::ZoneHit;
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::weapon::RustWeapon;
 use crate::world::RustWorld;
@@ -8,17 +11,18 @@ use crate::zombie::animation::ZombieAnimation;
 use crate::zombie::attack::{ZombieAttackArea, ZombieDamageArea};
 use crate::zombie::boomer::RustBoomer;
 use crate::{
-    BOOMER_ALARM_DISTANCE, GRENADE_ALARM_DISTANCE, GUN_ALARM_DISTANCE, MESSAGE,
-    PITCHER_ALARM_DISTANCE, PLAYER_ALARM_DISTANCE, PlayerState, ZOMBIE_ALARM_TIME, ZOMBIE_DAMAGE,
-    ZOMBIE_MAX_BODY_COUNT, ZOMBIE_MAX_DISTANCE, ZOMBIE_MAX_HEALTH, ZOMBIE_MIN_TRACK_DISTANCE,
-    ZOMBIE_MOVE_SPEED, ZOMBIE_PURSUIT_DISTANCE, ZOMBIE_RAMPAGE_TIME, ZOMBIE_ROTATE_COOLDOWN,
-    ZOMBIE_SKIP_FRAME, ZombieState, is_zombie, random_bool, random_direction, random_position,
+    BOOMER_ALARM_DISTANCE, GRENADE_ALARM_DISTANCE, GUN_ALARM_DISTANCE, HitZone,
+    PITCHER_ALARM_DISTANCE, PLAYER_ALARM_DISTANCE, PlayerState, StatusEffect, ZOMBIE_ALARM_TIME,
+    ZOMBIE_BLEED_CHANCE, ZOMBIE_DAMAGE, ZOMBIE_MAX_BODY_COUNT, ZOMBIE_MAX_DISTANCE,
+    ZOMBIE_MAX_HEALTH, ZOMBIE_MIN_TRACK_DISTANCE, ZOMBIE_MOVE_SPEED, ZOMBIE_PURSUIT_DISTANCE,
+    ZOMBIE_RAMPAGE_TIME, ZOMBIE_ROTATE_COOLDOWN, ZOMBIE_SKIP_FRAME, ZombieState, is_zombie,
+    random_bool, random_direction, random_position,
 };
 use crossbeam_utils::atomic::AtomicCell;
 use godot::builtin::{GString, Vector2, real};
 use godot::classes::{
     AudioStreamPlayer2D, CharacterBody2D, CollisionShape2D, Control, GpuParticles2D,
-    ICharacterBody2D, InputEvent, Label, ProgressBar, RemoteTransform2D,
+    ICharacterBody2D, InputEvent, Label, Node, ProgressBar, RemoteTransform2D,
 };
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
@@ -48,6 +52,14 @@ static BODY_COUNT: AtomicU32 = AtomicU32::new(0);
 
 static NEXT_ATTACK_DIRECTION: AtomicCell<Vector2> = AtomicCell::new(Vector2::ZERO);
 
+// 联机时主机同步给客户端的僵尸属性
+const ZOMBIE_SYNC_PROPERTIES: [&str; 4] = [
+    ".:position",
+    ".:rotation",
+    "AnimatedSprite2D:animation",
+    "AnimatedSprite2D:frame",
+];
+
 #[derive(GodotClass)]
 #[class(base=CharacterBody2D)]
 pub struct RustZombie {
@@ -77,6 +89,10 @@ pub struct RustZombie {
     current_rotate_cooldown: real,
     state: ZombieState,
     current_speed: real,
+    // 追击的玩家编号
+    target: usize,
+    zone_hit: ZoneHit,
+    effects: StatusEffects,
     hurt_frames: Vec<i32>,
     collision: Vector2,
     frame_counter: u128,
@@ -119,10 +135,13 @@ impl ICharacterBody2D for RustZombie {
             current_rotate_cooldown: 0.0,
             state: ZombieState::Guard,
             current_speed: ZOMBIE_MOVE_SPEED * 0.2,
+            target: 0,
             hurt_frames: vec![2, 3, 4, 5],
             collision: Vector2::ZERO,
             frame_counter: 0,
             pursuit_direction: random_bool(),
+            zone_hit: ZoneHit::default(),
+            effects: StatusEffects::default(),
             current_flash_cooldown: 0.0,
             hud: OnReady::from_node("RemoteTransform2D"),
             head_shape2d: OnReady::from_node("HeadShape2D"),
@@ -148,9 +167,16 @@ impl ICharacterBody2D for RustZombie {
         if self.hud.is_instance_valid() {
             self.hud.set_global_rotation_degrees(0.0);
         }
+        if !RustNetwork::is_authority() {
+            // 客户端只展示主机同步的结果
+            return;
+        }
         if RustWorld::is_paused() {
             return;
         }
+        if ZombieState::Dead != self.state {
+            self.update_effects(delta);
+        }
         if self.skip_frame {
             self.frame_counter = self.frame_counter.wrapping_add(1);
             if 0 == self.frame_counter % ZOMBIE_SKIP_FRAME {
@@ -163,7 +189,12 @@ impl ICharacterBody2D for RustZombie {
             }
             return;
         }
-        let player_state = RustPlayer::get_state();
+        if self.effects.is_stunned() {
+            return;
+        }
+        // 双人时追击威胁最大的玩家
+        self.target = RustPlayer::get_target_index(self.base().get_global_position());
+        let player_state = RustPlayer::get_state_of(self.target);
         if PlayerState::Dead == player_state {
             self.move_back();
             return;
@@ -175,7 +206,7 @@ impl ICharacterBody2D for RustZombie {
         self.current_flash_cooldown -= delta;
         self.rampage_time = (self.rampage_time - delta as real).max(0.0);
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         let distance = zombie_position.distance_to(player_position);
         if distance >= ZOMBIE_MAX_DISTANCE {
             //解决刷新僵尸导致的体积碰撞问题
@@ -201,7 +232,7 @@ impl ICharacterBody2D for RustZombie {
             } else {
                 self.guard();
             }
-            if distance <= ZOMBIE_PURSUIT_DISTANCE
+            if distance <= ZOMBIE_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target)
                 && self.current_alarm_time > 0.0
                 && self.is_face_to_user()
             {
@@ -234,7 +265,9 @@ impl ICharacterBody2D for RustZombie {
         }
         //撞到僵尸了
         self.collision = Vector2::ZERO;
-        if let Some(collision) = character_body2d.move_and_collide(velocity) {
+        if let Some(collision) =
+            character_body2d.move_and_collide(velocity * self.effects.speed_rate())
+        {
             // 发出排斥力的方向
             let from = collision.get_normal();
             if let Some(object) = collision.get_collider() {
@@ -257,6 +290,8 @@ impl ICharacterBody2D for RustZombie {
     }
 
     fn ready(&mut self) {
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &ZOMBIE_SYNC_PROPERTIES);
         if let Some(mut born_audio) = self
             .base()
             .try_get_node_as::<AudioStreamPlayer2D>("BornAudio")
@@ -272,6 +307,7 @@ impl ICharacterBody2D for RustZombie {
         let mut animated_sprite2d = self.animated_sprite2d.bind_mut();
         animated_sprite2d.set_hurt_frames(self.hurt_frames.clone());
         animated_sprite2d.set_damage(ZOMBIE_DAMAGE);
+        animated_sprite2d.set_effect(StatusEffect::Bleed, ZOMBIE_BLEED_CHANCE);
         drop(animated_sprite2d);
         self.guard();
         if !self.zombie_name.is_empty() {
@@ -309,19 +345,33 @@ impl RustZombie {
         }
     }
 
+    #[func]
+    pub fn on_zone_hit(
+        &mut self,
+        hit_val: i64,
+        direction: Vector2,
+        repel: real,
+        hit_position: Vector2,
+        zone: HitZone,
+        crit: bool,
+    ) {
+        let effect = self.zone_hit.record(zone, crit);
+        self.apply_effect(effect);
+        self.on_hit(hit_val, direction, repel, hit_position);
+    }
+
     #[func]
     pub fn on_hit(&mut self, hit_val: i64, direction: Vector2, repel: real, hit_position: Vector2) {
+        let hit_val = self.effects.scale_damage(hit_val);
+        let (hit_zone, hit_crit) = self.zone_hit.take();
         let zombie_position = self.base().get_global_position();
-        #[allow(clippy::borrow_interior_mutable_const)]
-        if let Some(mut hit_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
-            hit_label.set_global_position(zombie_position);
-            if let Some(tree) = self.base().get_tree() {
-                if let Some(mut root) = tree.get_root() {
-                    root.add_child(&hit_label);
-                    hit_label.bind_mut().show_hit_value(hit_val);
-                }
-            }
-        }
+        RustDamageNumbers::push(
+            self.base().instance_id(),
+            zombie_position,
+            hit_val,
+            hit_zone,
+            hit_crit,
+        );
         if !self.invincible {
             let health = self.health;
             self.health = if hit_val > 0 {
@@ -348,8 +398,55 @@ impl RustZombie {
         }
     }
 
+    #[func]
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(effect);
+    }
+
+    /// 玩家施加的效果，持续伤害的分数记给这名玩家
+    #[func]
+    pub fn apply_player_effect(&mut self, effect: StatusEffect, player_index: u32) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply_from(effect, Some(player_index as usize));
+    }
+
+    // 被闪光弹致盲后丢失警觉
+    #[func]
+    pub fn on_flashed(&mut self) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(StatusEffect::Stun);
+        self.effects.apply(StatusEffect::Blind);
+        self.current_alarm_time = 0.0;
+    }
+
+    // 持续伤害不会击退僵尸
+    pub fn update_effects(&mut self, delta: f64) {
+        let zombie = self.base().instance_id();
+        let position = self.base().get_global_position();
+        let damage = self.effects.process_zombie(delta, zombie, position);
+        if damage > 0 {
+            if !self.invincible {
+                self.health = self.health.saturating_sub(damage as u32);
+            }
+            if 0 == self.health {
+                self.die();
+            }
+        }
+        if ZombieState::Dead != self.state {
+            let color = self.effects.color();
+            self.animated_sprite2d.set_modulate(color);
+        }
+    }
+
     pub fn flash(&mut self) {
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         self.base_mut().look_at(-player_position);
         self.base_mut()
             .set_global_position(player_position + random_position(900.0, 1100.0));
@@ -372,7 +469,7 @@ impl RustZombie {
         //僵尸往玩家相反的方向移动一段距离
         self.guard();
         let zombie_position = self.base().get_global_position();
-        let from_player_dir = RustPlayer::get_position()
+        let from_player_dir = RustPlayer::get_position_of(self.target)
             .direction_to(zombie_position)
             .normalized();
         let speed = self.current_speed;
@@ -382,6 +479,7 @@ impl RustZombie {
     }
 
     // 看到玩家不会马上狂暴，而是累计时间条，类似刺客信条
+    #[func]
     pub fn is_alarmed(&self) -> bool {
         self.current_alarm_time >= self.alarm_time
     }
@@ -396,7 +494,9 @@ impl RustZombie {
         } else {
             alarm_progress.set_visible(true);
         }
-        if self.get_to_player_distance() <= ZOMBIE_PURSUIT_DISTANCE && self.is_face_to_user() {
+        // 潜行和草丛会缩短僵尸的发现距离
+        let pursuit_distance = ZOMBIE_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target);
+        if self.get_to_player_distance() <= pursuit_distance && self.is_face_to_user() {
             self.current_alarm_time =
                 (self.current_alarm_time + delta as real).min(self.alarm_time);
         } else {
@@ -413,7 +513,7 @@ impl RustZombie {
     }
 
     pub fn is_rampage_run(&self) -> bool {
-        if PlayerState::Dead == RustPlayer::get_state() {
+        if PlayerState::Dead == RustPlayer::get_state_of(self.target) {
             return false;
         }
         self.rampage_time <= 0.0
@@ -421,7 +521,7 @@ impl RustZombie {
 
     pub fn get_to_player_distance(&self) -> real {
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         zombie_position.distance_to(player_position)
     }
 
@@ -432,8 +532,14 @@ impl RustZombie {
     }
 
     pub fn is_face_to_user(&self) -> bool {
+        if self.effects.has(StatusEffect::Blind) {
+            return false;
+        }
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
+        if RustGrenade::is_vision_blocked(zombie_position, player_position) {
+            return false;
+        }
         let to_player_dir = zombie_position.direction_to(player_position).normalized();
         let angle = self
             .get_current_direction()
@@ -1,4 +1,5 @@
 use crate::is_survivor;
+use crate::network::RustNetwork;
 use crate::zombie::pitcher::RustPitcher;
 use godot::classes::{Area2D, IArea2D, Node, Node2D, Object};
 use godot::meta::ToGodot;
@@ -34,6 +35,9 @@ impl ZombiePitchArea {
 
     #[func]
     pub fn on_area_2d_body_entered(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if !body.is_instance_valid() {
             return;
         }
@@ -51,6 +55,9 @@ impl ZombiePitchArea {
 
     #[func]
     pub fn on_area_2d_body_exited(&mut self, body: Gd<Node2D>) {
+        if !RustNetwork::is_authority() {
+            return;
+        }
         if !body.is_instance_valid() {
             return;
         }
@@ -1,18 +1,21 @@
-use crate::common::RustMessage;
+use crate::damage_number::RustDamageNumbers;
+use crate::effect::StatusEffects;
 use crate::grenade::RustGrenade;
+use crate::hit_zone::ZoneHit;
 use crate::level::RustLevel;
+use crate::network::RustNetwork;
 use crate::player::RustPlayer;
 use crate::weapon::RustWeapon;
 use crate::world::RustWorld;
-use crate::zombie::BODY_COUNT;
 use crate::zombie::animation::ZombieAnimation;
 use crate::zombie::boomer::RustBoomer;
 use crate::zombie::pitch::ZombiePitchArea;
+use crate::zombie::{BODY_COUNT, ZOMBIE_SYNC_PROPERTIES};
 use crate::{
-    BOOMER_ALARM_DISTANCE, GRENADE_ALARM_DISTANCE, GUN_ALARM_DISTANCE, MESSAGE,
+    BOOMER_ALARM_DISTANCE, GRENADE_ALARM_DISTANCE, GUN_ALARM_DISTANCE, HitZone,
     PITCHER_ALARM_DISTANCE, PITCHER_ATTACK_DISTANCE, PITCHER_DAMAGE, PITCHER_GRENADE_COUNTDOWN,
     PITCHER_MOVE_SPEED, PITCHER_PURSUIT_DISTANCE, PITCHER_REPEL, PLAYER_ALARM_DISTANCE,
-    PlayerState, ZOMBIE_ALARM_TIME, ZOMBIE_GRENADE_DISTANCE, ZOMBIE_MAX_BODY_COUNT,
+    PlayerState, StatusEffect, ZOMBIE_ALARM_TIME, ZOMBIE_GRENADE_DISTANCE, ZOMBIE_MAX_BODY_COUNT,
     ZOMBIE_MAX_DISTANCE, ZOMBIE_MAX_HEALTH, ZOMBIE_MIN_TRACK_DISTANCE, ZOMBIE_RAMPAGE_TIME,
     ZOMBIE_ROTATE_COOLDOWN, ZombieState, not_normal_zombie, random_bool, random_direction,
     random_position,
@@ -21,7 +24,7 @@ use crossbeam_utils::atomic::AtomicCell;
 use godot::builtin::{Array, GString, Vector2, real};
 use godot::classes::{
     AudioStreamPlayer2D, CharacterBody2D, CollisionShape2D, Control, GpuParticles2D,
-    ICharacterBody2D, InputEvent, Label, Node2D, PackedScene, ProgressBar, RemoteTransform2D,
+    ICharacterBody2D, InputEvent, Label, Node, Node2D, PackedScene, ProgressBar, RemoteTransform2D,
 };
 use godot::obj::{Base, Gd, OnReady, WithBaseField};
 use godot::register::{GodotClass, godot_api};
@@ -73,6 +76,10 @@ pub struct RustPitcher {
     current_rotate_cooldown: real,
     state: ZombieState,
     current_speed: real,
+    // 追击的玩家编号
+    target: usize,
+    zone_hit: ZoneHit,
+    effects: StatusEffects,
     collision: Vector2,
     pursuit_direction: bool,
     current_flash_cooldown: f64,
@@ -117,8 +124,11 @@ impl ICharacterBody2D for RustPitcher {
             current_rotate_cooldown: 0.0,
             state: ZombieState::Guard,
             current_speed: PITCHER_MOVE_SPEED * 0.2,
+            target: 0,
             collision: Vector2::ZERO,
             pursuit_direction: random_bool(),
+            zone_hit: ZoneHit::default(),
+            effects: StatusEffects::default(),
             current_flash_cooldown: 0.0,
             hud: OnReady::from_node("RemoteTransform2D"),
             head_shape2d: OnReady::from_node("HeadShape2D"),
@@ -144,22 +154,34 @@ impl ICharacterBody2D for RustPitcher {
         if self.hud.is_instance_valid() {
             self.hud.set_global_rotation_degrees(0.0);
         }
-        let player_state = RustPlayer::get_state();
+        if !RustNetwork::is_authority() {
+            // 客户端只展示主机同步的结果
+            return;
+        }
+        // 双人时追击威胁最大的玩家
+        self.target = RustPlayer::get_target_index(self.base().get_global_position());
+        let player_state = RustPlayer::get_state_of(self.target);
         if RustWorld::is_paused() || PlayerState::Impact == player_state {
             return;
         }
+        if ZombieState::Dead != self.state {
+            self.update_effects(delta);
+        }
         if ZombieState::Dead == self.state {
             if BODY_COUNT.load(Ordering::Acquire) >= ZOMBIE_MAX_BODY_COUNT {
                 self.clean_body();
             }
             return;
         }
+        if self.effects.is_stunned() {
+            return;
+        }
         if PlayerState::Dead == player_state {
             self.move_back();
             return;
         }
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         let distance = zombie_position.distance_to(player_position);
         self.current_grenade_cooldown -= delta as real;
         if self.attacking || self.is_face_to_user() && distance <= PITCHER_ATTACK_DISTANCE {
@@ -194,7 +216,7 @@ impl ICharacterBody2D for RustPitcher {
             } else {
                 self.guard();
             }
-            if distance <= PITCHER_PURSUIT_DISTANCE
+            if distance <= PITCHER_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target)
                 && self.current_alarm_time > 0.0
                 && self.is_face_to_user()
             {
@@ -222,10 +244,12 @@ impl ICharacterBody2D for RustPitcher {
                 self.get_current_direction() * self.current_speed
             }
         };
-        self.move_and_collide(to_player_dir, velocity);
+        self.move_and_collide(to_player_dir, velocity * self.effects.speed_rate());
     }
 
     fn ready(&mut self) {
+        let mut node = self.to_gd().upcast::<Node>();
+        RustNetwork::replicate(&mut node, &ZOMBIE_SYNC_PROPERTIES);
         let gd = self.to_gd();
         self.die_audio
             .signals()
@@ -301,19 +325,33 @@ impl RustPitcher {
         }
     }
 
+    #[func]
+    pub fn on_zone_hit(
+        &mut self,
+        hit_val: i64,
+        direction: Vector2,
+        repel: real,
+        hit_position: Vector2,
+        zone: HitZone,
+        crit: bool,
+    ) {
+        let effect = self.zone_hit.record(zone, crit);
+        self.apply_effect(effect);
+        self.on_hit(hit_val, direction, repel, hit_position);
+    }
+
     #[func]
     pub fn on_hit(&mut self, hit_val: i64, direction: Vector2, repel: real, hit_position: Vector2) {
+        let hit_val = self.effects.scale_damage(hit_val);
+        let (hit_zone, hit_crit) = self.zone_hit.take();
         let zombie_position = self.base().get_global_position();
-        #[allow(clippy::borrow_interior_mutable_const)]
-        if let Some(mut hit_label) = MESSAGE.try_instantiate_as::<RustMessage>() {
-            hit_label.set_global_position(zombie_position);
-            if let Some(tree) = self.base().get_tree() {
-                if let Some(mut root) = tree.get_root() {
-                    root.add_child(&hit_label);
-                    hit_label.bind_mut().show_hit_value(hit_val);
-                }
-            }
-        }
+        RustDamageNumbers::push(
+            self.base().instance_id(),
+            zombie_position,
+            hit_val,
+            hit_zone,
+            hit_crit,
+        );
         if !self.invincible {
             let health = self.health;
             self.health = if hit_val > 0 {
@@ -340,8 +378,55 @@ impl RustPitcher {
         }
     }
 
+    #[func]
+    pub fn apply_effect(&mut self, effect: StatusEffect) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(effect);
+    }
+
+    /// 玩家施加的效果，持续伤害的分数记给这名玩家
+    #[func]
+    pub fn apply_player_effect(&mut self, effect: StatusEffect, player_index: u32) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply_from(effect, Some(player_index as usize));
+    }
+
+    // 被闪光弹致盲后丢失警觉
+    #[func]
+    pub fn on_flashed(&mut self) {
+        if ZombieState::Dead == self.state {
+            return;
+        }
+        self.effects.apply(StatusEffect::Stun);
+        self.effects.apply(StatusEffect::Blind);
+        self.current_alarm_time = 0.0;
+    }
+
+    // 持续伤害不会击退僵尸
+    pub fn update_effects(&mut self, delta: f64) {
+        let zombie = self.base().instance_id();
+        let position = self.base().get_global_position();
+        let damage = self.effects.process_zombie(delta, zombie, position);
+        if damage > 0 {
+            if !self.invincible {
+                self.health = self.health.saturating_sub(damage as u32);
+            }
+            if 0 == self.health {
+                self.die();
+            }
+        }
+        if ZombieState::Dead != self.state {
+            let color = self.effects.color();
+            self.animated_sprite2d.set_modulate(color);
+        }
+    }
+
     pub fn flash(&mut self) {
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         self.base_mut().look_at(-player_position);
         self.base_mut()
             .set_global_position(player_position + random_position(900.0, 1100.0));
@@ -349,7 +434,7 @@ impl RustPitcher {
 
     #[func]
     pub fn throw_grenade(&mut self) {
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         self.base_mut().look_at(player_position);
         if self.current_grenade_cooldown > 0.0 {
             return;
@@ -371,6 +456,7 @@ impl RustPitcher {
                 gd_mut.set_direction(direction);
                 drop(gd_mut);
                 if let Some(mut parent) = RustPlayer::get().get_parent() {
+                    RustNetwork::name_spawned(&mut grenade.clone().upcast());
                     parent.add_child(&grenade);
                     self.current_grenade_cooldown = self.grenade_cooldown;
                 }
@@ -396,7 +482,7 @@ impl RustPitcher {
         //僵尸往玩家相反的方向移动一段距离
         self.guard();
         let zombie_position = self.base().get_global_position();
-        let from_player_dir = RustPlayer::get_position()
+        let from_player_dir = RustPlayer::get_position_of(self.target)
             .direction_to(zombie_position)
             .normalized();
         let speed = self.current_speed;
@@ -406,6 +492,7 @@ impl RustPitcher {
     }
 
     // 看到玩家不会马上狂暴，而是累计时间条，类似刺客信条
+    #[func]
     pub fn is_alarmed(&self) -> bool {
         self.current_alarm_time >= self.alarm_time
     }
@@ -420,7 +507,9 @@ impl RustPitcher {
         } else {
             alarm_progress.set_visible(true);
         }
-        if self.get_to_player_distance() <= PITCHER_PURSUIT_DISTANCE && self.is_face_to_user() {
+        // 潜行和草丛会缩短僵尸的发现距离
+        let pursuit_distance = PITCHER_PURSUIT_DISTANCE * RustPlayer::get_visibility(self.target);
+        if self.get_to_player_distance() <= pursuit_distance && self.is_face_to_user() {
             self.current_alarm_time =
                 (self.current_alarm_time + delta as real).min(self.alarm_time);
         } else {
@@ -437,7 +526,7 @@ impl RustPitcher {
     }
 
     pub fn is_rampage_run(&self) -> bool {
-        if PlayerState::Dead == RustPlayer::get_state() {
+        if PlayerState::Dead == RustPlayer::get_state_of(self.target) {
             return false;
         }
         self.rampage_time <= 0.0
@@ -445,7 +534,7 @@ impl RustPitcher {
 
     pub fn get_to_player_distance(&self) -> real {
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         zombie_position.distance_to(player_position)
     }
 
@@ -456,8 +545,14 @@ impl RustPitcher {
     }
 
     pub fn is_face_to_user(&self) -> bool {
+        if self.effects.has(StatusEffect::Blind) {
+            return false;
+        }
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
+        if RustGrenade::is_vision_blocked(zombie_position, player_position) {
+            return false;
+        }
         let to_player_dir = zombie_position.direction_to(player_position).normalized();
         let angle = self
             .get_current_direction()
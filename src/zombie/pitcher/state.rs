@@ -1,6 +1,10 @@
 use super::*;
+use crate::combat::CombatEventBus;
 use crate::level::generator::ZombieGenerator;
+use crate::perk::RustPerkDrop;
 use crate::world::ground::RustGround;
+use crate::{Achievement, PERK_DROP_CHANCE};
+use godot::builtin::Color;
 
 #[godot_api(secondary)]
 impl RustPitcher {
@@ -80,7 +84,7 @@ impl RustPitcher {
         }
         self.guard();
         let zombie_position = self.base().get_global_position();
-        let player_position = RustPlayer::get_position();
+        let player_position = RustPlayer::get_position_of(self.target);
         let distance = zombie_position.distance_to(player_position);
         if distance >= ZOMBIE_GRENADE_DISTANCE {
             let to_player_dir = zombie_position.direction_to(player_position).normalized();
@@ -96,7 +100,7 @@ impl RustPitcher {
             + self
                 .base()
                 .get_global_position()
-                .direction_to(RustPlayer::get_position());
+                .direction_to(RustPlayer::get_position_of(self.target));
         NEXT_ATTACK_DIRECTION.store(direction.normalized());
         if self.attack_audio.is_inside_tree() {
             self.attack_audio.play();
@@ -112,6 +116,8 @@ impl RustPitcher {
         if ZombieState::Dead == self.state {
             return;
         }
+        self.effects.clear();
+        self.animated_sprite2d.set_modulate(Color::WHITE);
         self.animated_sprite2d.play_ex().name("die").done();
         self.current_speed = 0.0;
         self.state = ZombieState::Dead;
@@ -142,6 +148,13 @@ impl RustPitcher {
                 timer.connect("timeout", &self.base().callable("clean_body"));
             }
         }
+        let position = self.base().get_global_position();
+        CombatEventBus::on_zombie_killed(
+            self.base().instance_id(),
+            position,
+            Achievement::PitcherHunter,
+        );
+        RustPerkDrop::try_drop(position, PERK_DROP_CHANCE);
         // 击杀僵尸确认
         if let Some(level) = RustLevel::get() {
             level
@@ -1,7 +1,10 @@
 use super::*;
+use crate::Achievement;
 use crate::ZOMBIE_REFRESH_BARRIER;
+use crate::combat::CombatEventBus;
 use crate::level::generator::ZombieGenerator;
 use crate::world::ground::RustGround;
+use godot::builtin::Color;
 
 #[godot_api(secondary)]
 impl RustZombie {
@@ -88,10 +91,11 @@ impl RustZombie {
     }
 
     pub fn attack(&mut self) {
-        if ZombieState::Dead == self.state || !self.attackable {
+        if ZombieState::Dead == self.state || !self.attackable || self.effects.is_stunned() {
             return;
         }
-        self.base_mut().look_at(RustPlayer::get_position());
+        let player_position = RustPlayer::get_position_of(self.target);
+        self.base_mut().look_at(player_position);
         self.animated_sprite2d.play_ex().name("attack").done();
         self.current_speed = self.speed * 0.5;
         self.state = ZombieState::Attack;
@@ -99,7 +103,7 @@ impl RustZombie {
             + self
                 .base()
                 .get_global_position()
-                .direction_to(RustPlayer::get_position());
+                .direction_to(RustPlayer::get_position_of(self.target));
         NEXT_ATTACK_DIRECTION.store(direction.normalized());
         if self.attack_audio.is_inside_tree() {
             self.attack_audio.play();
@@ -115,6 +119,8 @@ impl RustZombie {
         if ZombieState::Dead == self.state {
             return;
         }
+        self.effects.clear();
+        self.animated_sprite2d.set_modulate(Color::WHITE);
         self.animated_sprite2d.play_ex().name("die").done();
         self.current_speed = 0.0;
         self.state = ZombieState::Dead;
@@ -145,6 +151,12 @@ impl RustZombie {
                 timer.connect("timeout", &self.base().callable("clean_body"));
             }
         }
+        let position = self.base().get_global_position();
+        CombatEventBus::on_zombie_killed(
+            self.base().instance_id(),
+            position,
+            Achievement::ZombieSlayer,
+        );
         // 击杀僵尸确认
         if let Some(level) = RustLevel::get() {
             level